@@ -0,0 +1,160 @@
+// Long-lived FFT state: the planner, the plans it has made, window
+// coefficients and the work buffers, kept across frames and files so a
+// batch of analyses plans and allocates once per size rather than per call.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+use tracing::{debug_span, instrument};
+
+use crate::kernels::{self, window_frame};
+use crate::spectrum::{Spectrum, SpectrumScale};
+use crate::stft::{Spectrogram, StftConfig, StftStream};
+use crate::window::Window;
+
+// largest transform whose plan, window and work buffers are kept; every STFT
+// size fits, while a whole-file spectrum (often an odd, one-off length) is
+// planned fresh and its memory handed back afterwards
+const MAX_CACHED_SIZE: usize = 1 << 16;
+
+thread_local! {
+    static SHARED: RefCell<SpectrumAnalyzer> = RefCell::new(SpectrumAnalyzer::new());
+}
+
+/// Run `f` with this thread's shared analyzer. The free functions
+/// (`compute_spectrum_sized`, `stft_with_window`, `Spectrogram::compute`,
+/// `StftStream::new`) go through it, so repeated calls on one thread plan
+/// and allocate once per size too.
+pub fn with_shared<T>(f: impl FnOnce(&mut SpectrumAnalyzer) -> T) -> T {
+    SHARED.with(|shared| match shared.try_borrow_mut() {
+        Ok(mut analyzer) => f(&mut analyzer),
+        // only if `f` itself reaches a free function; rare, so no caching
+        Err(_) => f(&mut SpectrumAnalyzer::new()),
+    })
+}
+
+/// Plans, windows and scratch memory shared by every transform run through
+/// it. Not `Sync`; give each thread its own.
+pub struct SpectrumAnalyzer {
+    planner: FftPlanner<f32>,
+    windows: HashMap<(Window, usize), Arc<[f32]>>,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectrumAnalyzer {
+    pub fn new() -> Self {
+        SpectrumAnalyzer { planner: FftPlanner::new(), windows: HashMap::new(), buffer: Vec::new(), scratch: Vec::new() }
+    }
+
+    /// Forward FFT of `n` points, planned on first use. Sizes above
+    /// `MAX_CACHED_SIZE` get a throwaway planner, so their plans (and the
+    /// twiddle tables behind them) don't stay cached.
+    pub fn fft(&mut self, n: usize) -> Arc<dyn Fft<f32>> {
+        if n > MAX_CACHED_SIZE {
+            return FftPlanner::new().plan_fft_forward(n);
+        }
+        self.planner.plan_fft_forward(n)
+    }
+
+    /// Coefficients of `window` at length `n`, computed on first use (or
+    /// every time, above `MAX_CACHED_SIZE`).
+    pub fn window(&mut self, window: Window, n: usize) -> Arc<[f32]> {
+        if n > MAX_CACHED_SIZE {
+            return window.coefficients(n).into();
+        }
+        self.windows.entry((window, n)).or_insert_with(|| window.coefficients(n).into()).clone()
+    }
+
+    /// Same as `compute_spectrum_sized`: an FFT of exactly `fft_size` points
+    /// over `samples` zero-padded to that size.
+    ///
+    /// Panics if `samples` is longer than `fft_size`.
+    #[instrument(name = "fft", level = "debug", skip_all, fields(fft_size = fft_size))]
+    pub fn spectrum(&mut self, samples: &[f32], sample_rate: u32, fft_size: usize) -> Spectrum {
+        assert!(samples.len() <= fft_size, "FFT size {} is shorter than the input ({} samples)",
+            fft_size, samples.len());
+
+        let fft = self.fft(fft_size);
+        self.buffer.clear();
+        self.buffer.extend(samples.iter().map(|&s| Complex { re: s, im: 0.0 }));
+        self.buffer.resize(fft_size, Complex { re: 0.0, im: 0.0 });
+        self.process(&fft);
+
+        let mut magnitudes = vec![0.0; fft_size / 2];
+        kernels::magnitudes(&self.buffer, &mut magnitudes);
+        let freq_resolution = sample_rate as f32 / fft_size as f32;
+        let frequencies = (0..magnitudes.len()).map(|i| i as f32 * freq_resolution).collect();
+
+        self.release_oversized();
+        let len = samples.len() as f32;
+        Spectrum { fft_size, frequencies, magnitudes, window_sum: len, window_power: len, scale: SpectrumScale::Raw }
+    }
+
+    /// Same as `stft_with_window`: the FFT of every frame under `window`
+    /// (of length `config.nfft`), bins 0..=nfft/2.
+    #[instrument(name = "stft", level = "debug", skip_all, fields(nfft = config.nfft, hop = config.hop))]
+    pub fn stft(&mut self, samples: &[f32], config: StftConfig, window: &[f32]) -> Vec<Vec<Complex<f32>>> {
+        let fft = self.fft(config.nfft);
+        self.buffer.resize(config.nfft, Complex { re: 0.0, im: 0.0 });
+        let frames = (0..config.num_frames(samples.len())).map(|frame| {
+            let start = frame * config.hop;
+            window_frame(&samples[start..start + config.nfft], window, &mut self.buffer);
+            self.process(&fft);
+            self.buffer[..=config.nfft / 2].to_vec()
+        }).collect();
+        self.release_oversized();
+        frames
+    }
+
+    /// Same as `Spectrogram::compute_windowed`, without keeping the complex
+    /// frames around.
+    pub fn spectrogram(&mut self, samples: &[f32], sample_rate: u32, config: StftConfig, window: Window) -> Spectrogram {
+        let coefficients = self.window(window, config.nfft);
+        let fft = self.fft(config.nfft);
+        self.buffer.resize(config.nfft, Complex { re: 0.0, im: 0.0 });
+        let frames = {
+            let _span = debug_span!("stft", nfft = config.nfft, hop = config.hop).entered();
+            (0..config.num_frames(samples.len())).map(|frame| {
+                let start = frame * config.hop;
+                window_frame(&samples[start..start + config.nfft], &coefficients, &mut self.buffer);
+                self.process(&fft);
+                let mut magnitudes = vec![0.0; config.nfft / 2 + 1];
+                kernels::magnitudes(&self.buffer, &mut magnitudes);
+                magnitudes
+            }).collect()
+        };
+        self.release_oversized();
+        Spectrogram { config, sample_rate, frames, window }
+    }
+
+    /// A streaming STFT sharing this analyzer's plan and window.
+    pub fn stream(&mut self, config: StftConfig, window: Window) -> StftStream {
+        StftStream::with_plan(config, self.window(window, config.nfft), self.fft(config.nfft))
+    }
+
+    // in-place transform of `buffer` through the shared scratch
+    fn process(&mut self, fft: &Arc<dyn Fft<f32>>) {
+        self.scratch.resize(fft.get_inplace_scratch_len(), Complex { re: 0.0, im: 0.0 });
+        fft.process_with_scratch(&mut self.buffer, &mut self.scratch);
+    }
+
+    // hand back work buffers a transform above MAX_CACHED_SIZE grew, rather
+    // than hold a whole file's worth for the rest of the thread
+    fn release_oversized(&mut self) {
+        if self.buffer.capacity() > MAX_CACHED_SIZE {
+            self.buffer = Vec::new();
+        }
+        if self.scratch.capacity() > MAX_CACHED_SIZE {
+            self.scratch = Vec::new();
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// Animated images assembled from equally timed RGB frames: GIF for
+// anything that shows animations, APNG for full color without dithering,
+// and video encoded by an ffmpeg child process.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+// NeuQuant sampling factor for GIF palettes: 1 is best, 30 fastest
+const GIF_QUANTIZER_SPEED: i32 = 10;
+
+/// Container of an animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
+}
+
+impl AnimationFormat {
+    /// The format an output path's extension names: .gif, or .png/.apng.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "png" | "apng" => Some(AnimationFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+enum Encoder {
+    Gif(gif::Encoder<BufWriter<File>>),
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+/// Writes RGB frames (8 bits per channel, rows top first) of one size to an
+/// animation that loops forever.
+pub struct AnimationWriter {
+    encoder: Encoder,
+    width: u16,
+    height: u16,
+    delay: u16, // GIF frame delay in hundredths of a second
+}
+
+impl AnimationWriter {
+    /// Start an animation of `frames` frames shown `fps` per second. GIF
+    /// rounds the frame time to whole hundredths of a second.
+    pub fn create<P: AsRef<Path>>(path: P, format: AnimationFormat, width: u16, height: u16, frames: u32, fps: u16) -> io::Result<Self> {
+        if fps == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame rate must be positive"));
+        }
+        let file = BufWriter::new(File::create(path)?);
+        let encoder = match format {
+            AnimationFormat::Gif => {
+                let mut encoder = gif::Encoder::new(file, width, height, &[]).map_err(io::Error::other)?;
+                encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+                Encoder::Gif(encoder)
+            }
+            AnimationFormat::Apng => {
+                let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(frames.max(1), 0).map_err(io::Error::other)?;
+                encoder.set_frame_delay(1, fps).map_err(io::Error::other)?;
+                Encoder::Apng(encoder.write_header().map_err(io::Error::other)?)
+            }
+        };
+        let delay = (100.0 / fps as f32).round().max(1.0) as u16;
+        Ok(AnimationWriter { encoder, width, height, delay })
+    }
+
+    /// Append one frame of `width * height * 3` bytes.
+    pub fn push(&mut self, rgb: &[u8]) -> io::Result<()> {
+        if rgb.len() != self.width as usize * self.height as usize * 3 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame size does not match the animation"));
+        }
+        match &mut self.encoder {
+            Encoder::Gif(encoder) => {
+                let mut frame = gif::Frame::from_rgb_speed(self.width, self.height, rgb, GIF_QUANTIZER_SPEED);
+                frame.delay = self.delay;
+                encoder.write_frame(&frame).map_err(io::Error::other)
+            }
+            Encoder::Apng(writer) => writer.write_image_data(rgb).map_err(io::Error::other),
+        }
+    }
+
+    /// Write the trailer. An APNG must have received every frame it was
+    /// created for.
+    pub fn finish(self) -> io::Result<()> {
+        match self.encoder {
+            Encoder::Gif(encoder) => encoder.into_inner()?.flush(),
+            Encoder::Apng(writer) => writer.finish().map_err(io::Error::other),
+        }
+    }
+}
+
+/// Pipes RGB frames (8 bits per channel, rows top first) of one size into
+/// an ffmpeg child process, which encodes them with the default codec of
+/// the output's container, e.g. H.264 for .mp4.
+pub struct VideoWriter {
+    child: Child,
+    stdin: BufWriter<ChildStdin>,
+    frame_len: usize,
+}
+
+impl VideoWriter {
+    /// Start ffmpeg on a video of `fps` frames per second. With `audio`,
+    /// that file's sound is muxed in and the video ends with the shorter of
+    /// the two.
+    pub fn spawn<P: AsRef<Path>>(path: P, width: u16, height: u16, fps: u16, audio: Option<&Path>) -> io::Result<Self> {
+        if fps == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame rate must be positive"));
+        }
+        // 4:2:0 chroma, which players expect, halves both dimensions
+        if !width.is_multiple_of(2) || !height.is_multiple_of(2) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "video width and height must be even"));
+        }
+
+        let mut command = Command::new("ffmpeg");
+        command.args(["-hide_banner", "-loglevel", "error", "-y"]);
+        command.args(["-f", "rawvideo", "-pixel_format", "rgb24"]);
+        command.arg("-video_size").arg(format!("{}x{}", width, height));
+        command.arg("-framerate").arg(fps.to_string());
+        command.args(["-i", "-"]);
+        if let Some(audio) = audio {
+            command.arg("-i").arg(audio);
+            command.args(["-map", "0:v", "-map", "1:a", "-shortest"]);
+        }
+        command.args(["-pix_fmt", "yuv420p"]).arg(path.as_ref());
+
+        let mut child = command.stdin(Stdio::piped()).spawn().map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => io::Error::new(e.kind(), "ffmpeg not found; install it or add it to PATH"),
+            _ => e,
+        })?;
+        let stdin = BufWriter::new(child.stdin.take().expect("stdin is piped"));
+        Ok(VideoWriter { child, stdin, frame_len: width as usize * height as usize * 3 })
+    }
+
+    /// Append one frame of `width * height * 3` bytes.
+    pub fn push(&mut self, rgb: &[u8]) -> io::Result<()> {
+        if rgb.len() != self.frame_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame size does not match the video"));
+        }
+        self.stdin.write_all(rgb).map_err(|e| match e.kind() {
+            io::ErrorKind::BrokenPipe => io::Error::other("ffmpeg stopped reading frames"),
+            _ => e,
+        })
+    }
+
+    /// Close the pipe and wait for ffmpeg to finish encoding.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.stdin.flush()?;
+        drop(self.stdin);
+        let status = self.child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("ffmpeg failed ({})", status)));
+        }
+        Ok(())
+    }
+}
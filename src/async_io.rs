@@ -0,0 +1,51 @@
+// Async front-end for services running on tokio: the WAV stream is read
+// without blocking, and the CPU-bound FFT is moved onto the blocking pool.
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::spectrum::{compute_spectrum, Spectrum};
+use crate::wav::{ChunkHeader, Header, WavFile};
+
+impl WavFile {
+    /// Parse a whole WAV stream from an async reader, a chunk at a time.
+    /// Each chunk body is read into its own buffer, and the data chunk's
+    /// buffer becomes the parsed file's payload without another copy.
+    pub async fn parse_async<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Self> {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header).await?;
+        let mut wav = WavFile::from_header(Header::from_bytes(&header))?;
+
+        let mut pos: u64 = 12;
+        loop {
+            let mut buf = [0u8; 8];
+            match reader.read_exact(&mut buf).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+            let hdr = ChunkHeader::from_bytes(&buf);
+            let offset = pos + 8;
+            pos = offset + hdr.size as u64 + (hdr.size % 2) as u64;
+
+            let mut body = vec![0u8; hdr.size as usize];
+            reader.read_exact(&mut body).await?;
+            if hdr.size % 2 == 1 {
+                // a missing pad byte at the very end is tolerated
+                let _ = reader.read_exact(&mut [0u8; 1]).await;
+            }
+            wav.add_chunk(&hdr, body, offset);
+        }
+
+        Ok(wav)
+    }
+}
+
+/// `compute_spectrum` on tokio's blocking thread pool, so large transforms
+/// don't stall the async worker threads.
+pub async fn compute_spectrum_async(samples: Vec<f32>, sample_rate: u32) -> io::Result<Spectrum> {
+    tokio::task::spawn_blocking(move || compute_spectrum(&samples, sample_rate))
+        .await
+        .map_err(io::Error::other)
+}
@@ -0,0 +1,80 @@
+// Auditory frequency scales: warpings of the frequency axis that space
+// bands the way the ear resolves them, for plot axes and filterbanks.
+
+use std::ops::Range;
+
+/// A frequency axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FreqScale {
+    #[default]
+    Linear,
+    /// O'Shaughnessy's mel scale, 2595 log10(1 + f / 700).
+    Mel,
+    /// Traunmueller's (1990) critical-band rate, 26.81 f / (1960 + f) - 0.53.
+    Bark,
+    /// Glasberg and Moore's (1990) ERB-rate, 21.4 log10(1 + 0.00437 f): the
+    /// number of equivalent rectangular bandwidths below f.
+    Erb,
+}
+
+impl FreqScale {
+    /// Position of `hz` on the scale.
+    pub fn from_hz(self, hz: f32) -> f32 {
+        match self {
+            FreqScale::Linear => hz,
+            FreqScale::Mel => 2595.0 * (1.0 + hz / 700.0).log10(),
+            FreqScale::Bark => 26.81 * hz / (1960.0 + hz) - 0.53,
+            FreqScale::Erb => 21.4 * (1.0 + 0.00437 * hz).log10(),
+        }
+    }
+
+    /// Frequency in Hz at position `value` on the scale.
+    pub fn to_hz(self, value: f32) -> f32 {
+        match self {
+            FreqScale::Linear => value,
+            FreqScale::Mel => 700.0 * (10f32.powf(value / 2595.0) - 1.0),
+            FreqScale::Bark => 1960.0 * (value + 0.53) / (26.28 - value),
+            FreqScale::Erb => (10f32.powf(value / 21.4) - 1.0) / 0.00437,
+        }
+    }
+
+    /// Name of the scale's unit, e.g. for axis labels.
+    pub fn unit(self) -> &'static str {
+        match self {
+            FreqScale::Linear => "Hz",
+            FreqScale::Mel => "mel",
+            FreqScale::Bark => "Bark",
+            FreqScale::Erb => "ERB",
+        }
+    }
+
+    /// Split 0 Hz..`nyquist` into `count` cells evenly spaced on this scale
+    /// and give the range of spectrum bins (`bins` of them, 0 Hz to
+    /// Nyquist) each covers; a cell narrower than a bin takes the nearest
+    /// one, so no range is empty.
+    pub fn bin_ranges(self, nyquist: f32, bins: usize, count: usize) -> Vec<Range<usize>> {
+        let (bottom, top) = (self.from_hz(0.0), self.from_hz(nyquist));
+        // fractional bin at the lower edge of (fractional) cell c
+        let position = |c: f32| self.to_hz(bottom + (top - bottom) * c / count as f32) / nyquist * bins as f32;
+        // first bin of cell c; exact integers on the linear scale
+        let edge = |c: usize| match self {
+            FreqScale::Linear => (c * bins).div_ceil(count),
+            _ => (position(c as f32).ceil() as usize).min(bins),
+        };
+        (0..count).map(|c| {
+            let (lo, hi) = (edge(c), edge(c + 1));
+            if hi > lo {
+                lo..hi
+            } else {
+                let nearest = (position(c as f32 + 0.5) as usize).min(bins - 1);
+                nearest..nearest + 1
+            }
+        }).collect()
+    }
+}
+
+/// Equivalent rectangular bandwidth in Hz of the auditory filter centered
+/// on `hz` (Glasberg and Moore): 24.7 (0.00437 f + 1).
+pub fn erb_bandwidth(hz: f32) -> f32 {
+    24.7 * (0.00437 * hz + 1.0)
+}
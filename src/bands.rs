@@ -0,0 +1,150 @@
+// Fractional-octave band analysis with IEC 61260-1 base-10 band centers,
+// and auditory bands: Zwicker's critical bands and ERB-wide bands.
+
+use crate::auditory::FreqScale;
+use crate::spectrum::Spectrum;
+
+// octave frequency ratio G = 10^(3/10) of the base-10 system
+const OCTAVE_RATIO: f64 = 1.995_262_314_968_879_5;
+
+// R10 preferred numbers, used for the nominal band labels
+const NOMINAL: [f32; 10] = [1.0, 1.25, 1.6, 2.0, 2.5, 3.15, 4.0, 5.0, 6.3, 8.0];
+
+// Zwicker's (1961) 24 critical bands, one Bark wide: edges and centers in Hz
+const BARK_EDGES: [f32; 25] = [
+    0.0, 100.0, 200.0, 300.0, 400.0, 510.0, 630.0, 770.0, 920.0, 1080.0, 1270.0, 1480.0, 1720.0,
+    2000.0, 2320.0, 2700.0, 3150.0, 3700.0, 4400.0, 5300.0, 6400.0, 7700.0, 9500.0, 12000.0, 15500.0,
+];
+const BARK_CENTERS: [f32; 24] = [
+    50.0, 150.0, 250.0, 350.0, 450.0, 570.0, 700.0, 840.0, 1000.0, 1170.0, 1370.0, 1600.0, 1850.0,
+    2150.0, 2500.0, 2900.0, 3400.0, 4000.0, 4800.0, 5800.0, 7000.0, 8500.0, 10500.0, 13500.0,
+];
+
+/// Bandwidth of each band: a fraction of an octave, one critical band
+/// (Bark) or one equivalent rectangular bandwidth (ERB).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BandFraction {
+    Octave,
+    Third,
+    Bark,
+    Erb,
+}
+
+
+/// Energy of one fractional-octave band.
+#[derive(Debug, Clone)]
+pub struct Band {
+    pub nominal: f32, // rounded label frequency, e.g. 31.5 or 1000
+    pub center: f32,  // exact midband frequency in Hz
+    pub lower: f32,
+    pub upper: f32,
+    pub level_db: f32, // summed bin power in dB (dBFS, or re the loudest band)
+}
+
+/// The IEC 61260 bands from 16 Hz to 20 kHz (or the critical bands up to
+/// 15.5 kHz, or the ERB bands up to 20 kHz) that fit below `nyquist`, with
+/// `level_db` not yet measured (-inf).
+pub fn band_layout(fraction: BandFraction, nyquist: f32) -> Vec<Band> {
+    let b = match fraction {
+        BandFraction::Octave => 1,
+        BandFraction::Third => 3,
+        BandFraction::Bark => return critical_bands(nyquist),
+        BandFraction::Erb => return erb_bands(nyquist),
+    };
+
+    // band index x counts thirds (or octaves) away from 1 kHz
+    let index = |f: f64| (b as f64 * f.log(OCTAVE_RATIO)).round() as i32;
+    let (first, last) = (index(16.0 / 1000.0), index(20000.0 / 1000.0));
+
+    (first..=last).filter_map(|x| {
+        let center = 1000.0 * OCTAVE_RATIO.powf(x as f64 / b as f64);
+        let half = OCTAVE_RATIO.powf(1.0 / (2.0 * b as f64));
+        let (lower, upper) = ((center / half) as f32, (center * half) as f32);
+        if upper > nyquist {
+            return None;
+        }
+
+        // R10 label: third-octave steps walk the preferred numbers directly
+        let third = x * 3 / b;
+        let nominal = NOMINAL[third.rem_euclid(10) as usize]
+            * 10f32.powi(3 + third.div_euclid(10));
+
+        Some(Band { nominal, center: center as f32, lower, upper, level_db: f32::NEG_INFINITY })
+    }).collect()
+}
+
+fn critical_bands(nyquist: f32) -> Vec<Band> {
+    BARK_CENTERS.iter().zip(BARK_EDGES.windows(2))
+        .filter(|(_, edges)| edges[1] <= nyquist)
+        .map(|(&center, edges)| Band {
+            nominal: center,
+            center,
+            lower: edges[0],
+            upper: edges[1],
+            level_db: f32::NEG_INFINITY,
+        })
+        .collect()
+}
+
+// bands one ERB wide, centered on whole ERB-rate numbers from 1 (26 Hz)
+fn erb_bands(nyquist: f32) -> Vec<Band> {
+    let erb = FreqScale::Erb;
+    let top = erb.from_hz(nyquist.min(20000.0));
+    (1..).map(|n| n as f32)
+        .take_while(|&n| n + 0.5 <= top)
+        .map(|n| {
+            let center = erb.to_hz(n);
+            Band {
+                nominal: center.round(),
+                center,
+                lower: erb.to_hz(n - 0.5),
+                upper: erb.to_hz(n + 0.5),
+                level_db: f32::NEG_INFINITY,
+            }
+        })
+        .collect()
+}
+
+/// Level of each band of `band_layout` in dBFS (mean square re full
+/// scale), from a spectrum of `signal_len` samples, by Parseval: a band's
+/// mean square is 2 sum |X|^2 / (N L) for an N-point FFT of L samples.
+pub fn band_levels_dbfs(spectrum: &Spectrum, signal_len: usize, fraction: BandFraction) -> Vec<Band> {
+    let bin_width = spectrum.frequencies.get(1).copied().unwrap_or(0.0);
+    let nyquist = spectrum.fft_size as f32 / 2.0 * bin_width;
+    let scale = 2.0 / (spectrum.fft_size as f64 * signal_len.max(1) as f64);
+
+    let mut bands = band_layout(fraction, nyquist);
+    for band in &mut bands {
+        let power: f64 = spectrum.frequencies.iter().zip(&spectrum.magnitudes)
+            .filter(|(&f, _)| f >= band.lower && f < band.upper)
+            .map(|(_, &m)| m as f64 * m as f64)
+            .sum();
+        band.level_db = (10.0 * (power * scale + 1e-20).log10()) as f32;
+    }
+    bands
+}
+
+/// Band levels in dB relative to the loudest band.
+pub fn band_levels(spectrum: &Spectrum, fraction: BandFraction) -> Vec<Band> {
+    let mut bands = band_levels_dbfs(spectrum, spectrum.fft_size, fraction);
+    let loudest = bands.iter().map(|band| band.level_db).fold(f32::NEG_INFINITY, f32::max);
+    for band in &mut bands {
+        band.level_db -= loudest;
+    }
+    bands
+}
+
+/// Share of the total energy in each band split at `edges` (Hz, ascending):
+/// below the first edge, between each pair, and above the last, so
+/// `edges.len() + 1` fractions that sum to 1. The energy of all `spectra`
+/// (one per channel, say) is pooled; silence gives all zeros.
+pub fn energy_distribution(spectra: &[Spectrum], edges: &[f32]) -> Vec<f32> {
+    let mut energy = vec![0f64; edges.len() + 1];
+    for spectrum in spectra {
+        for (&f, &m) in spectrum.frequencies.iter().zip(&spectrum.magnitudes) {
+            energy[edges.partition_point(|&edge| edge <= f)] += m as f64 * m as f64;
+        }
+    }
+    let total: f64 = energy.iter().sum();
+    energy.iter().map(|&e| if total > 0.0 { (e / total) as f32 } else { 0.0 }).collect()
+}
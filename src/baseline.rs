@@ -0,0 +1,150 @@
+// Spectral baselines for regression tests of rendered audio: third-octave
+// band levels and the strongest spectral peaks of a reference render,
+// stored as JSON and compared against later renders level by level.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bands::{band_levels_dbfs, BandFraction};
+use crate::partials::pick_peaks;
+use crate::spectrum::compute_spectrum;
+use crate::stft::{Spectrogram, StftConfig};
+
+// peaks come from the mean of STFT frames this long (or the longest power
+// of two that fits a shorter render)
+const PEAK_NFFT: usize = 8192;
+const MIN_NFFT: usize = 256;
+const MAX_PEAKS: usize = 10;
+const PEAK_FLOOR_DB: f32 = -80.0;
+// a later render's peak may sit this many bins from the baseline's
+const PEAK_BINS: usize = 2;
+
+// levels below this (dBFS) count as silence and compare equal
+const FLOOR_DB: f32 = -100.0;
+
+/// Level of one third-octave band, dBFS.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandLevel {
+    pub nominal: f32,
+    pub level_db: f32,
+}
+
+/// A spectral peak: frequency in Hz and sine amplitude in dBFS.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeakLevel {
+    pub frequency: f32,
+    pub level_db: f32,
+}
+
+/// The spectral fingerprint of a reference render.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub sample_rate: u32,
+    pub nfft: usize, // STFT size the peaks were measured with
+    pub bands: Vec<BandLevel>,
+    pub peaks: Vec<PeakLevel>,
+}
+
+/// One compared level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: String, // e.g. "band 1000 Hz", "peak 440.0 Hz"
+    pub expected_db: f32,
+    pub actual_db: f32,
+}
+
+impl Check {
+    /// Difference from the baseline in dB, with levels under the silence
+    /// floor counted as the floor.
+    pub fn deviation_db(&self) -> f32 {
+        self.actual_db.max(FLOOR_DB) - self.expected_db.max(FLOOR_DB)
+    }
+
+    pub fn passes(&self, tolerance_db: f32) -> bool {
+        self.deviation_db().abs() <= tolerance_db
+    }
+}
+
+impl Baseline {
+    /// Measure a mono render.
+    pub fn measure(samples: &[f32], sample_rate: u32) -> Result<Baseline, String> {
+        let nfft = peak_nfft(samples.len())?;
+        let bands = bands(samples, sample_rate);
+        let (mean, to_db) = mean_spectrum(samples, sample_rate, nfft);
+        let threshold = 10f32.powf(PEAK_FLOOR_DB / 20.0) / to_db;
+        // levels are read back the way `compare` reads them
+        let peaks = pick_peaks(&mean, threshold, MAX_PEAKS).into_iter()
+            .map(|(bin, _)| PeakLevel {
+                frequency: bin * sample_rate as f32 / nfft as f32,
+                level_db: peak_level_db(&mean, bin.round() as usize, to_db),
+            })
+            .collect();
+        Ok(Baseline { sample_rate, nfft, bands, peaks })
+    }
+
+    /// Measure a mono render the way this baseline was and pair every level
+    /// with the baseline's. A peak is read as the strongest bin within a
+    /// couple of bins of the baseline's frequency.
+    pub fn compare(&self, samples: &[f32], sample_rate: u32) -> Result<Vec<Check>, String> {
+        if sample_rate != self.sample_rate {
+            return Err(format!("baseline was measured at {} Hz, the render is at {} Hz", self.sample_rate, sample_rate));
+        }
+        if samples.len() < self.nfft {
+            return Err(format!("render is shorter than the baseline's {}-point analysis", self.nfft));
+        }
+
+        let mut checks: Vec<Check> = self.bands.iter().zip(bands(samples, sample_rate))
+            .map(|(expected, actual)| Check {
+                name: format!("band {} Hz", expected.nominal),
+                expected_db: expected.level_db,
+                actual_db: actual.level_db,
+            })
+            .collect();
+
+        let (mean, to_db) = mean_spectrum(samples, sample_rate, self.nfft);
+        for peak in &self.peaks {
+            let bin = (peak.frequency * self.nfft as f32 / sample_rate as f32).round() as usize;
+            checks.push(Check {
+                name: format!("peak {:.1} Hz", peak.frequency),
+                expected_db: peak.level_db,
+                actual_db: peak_level_db(&mean, bin, to_db),
+            });
+        }
+        Ok(checks)
+    }
+}
+
+// the strongest bin within PEAK_BINS of `bin`, as a sine amplitude in dBFS
+fn peak_level_db(mean: &[f32], bin: usize, to_db: f32) -> f32 {
+    let range = bin.saturating_sub(PEAK_BINS)..(bin + PEAK_BINS + 1).min(mean.len());
+    let magnitude = mean.get(range).unwrap_or(&[]).iter().fold(0f32, |a, &b| a.max(b));
+    20.0 * (magnitude * to_db).max(1e-12).log10()
+}
+
+fn peak_nfft(len: usize) -> Result<usize, String> {
+    let fits = if len >= PEAK_NFFT { PEAK_NFFT } else { (len + 1).next_power_of_two() / 2 };
+    if fits < MIN_NFFT {
+        return Err(format!("render is too short to measure ({} samples, need {})", len, MIN_NFFT));
+    }
+    Ok(fits)
+}
+
+fn bands(samples: &[f32], sample_rate: u32) -> Vec<BandLevel> {
+    band_levels_dbfs(&compute_spectrum(samples, sample_rate), samples.len(), BandFraction::Third).into_iter()
+        .map(|band| BandLevel { nominal: band.nominal, level_db: band.level_db })
+        .collect()
+}
+
+// mean magnitude of the Hann STFT frames, and the factor taking it to a
+// sine's amplitude
+fn mean_spectrum(samples: &[f32], sample_rate: u32, nfft: usize) -> (Vec<f32>, f32) {
+    let spectrogram = Spectrogram::compute(samples, sample_rate, StftConfig { nfft, hop: nfft / 2 });
+    let mut mean = vec![0f32; nfft / 2 + 1];
+    for frame in &spectrogram.frames {
+        for (m, &value) in mean.iter_mut().zip(frame) {
+            *m += value;
+        }
+    }
+    let count = spectrogram.frames.len().max(1) as f32;
+    mean.iter_mut().for_each(|m| *m /= count);
+    (mean, 4.0 / nfft as f32)
+}
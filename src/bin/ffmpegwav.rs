@@ -138,7 +138,7 @@ fn read_simple_field<R: Read>(
             let mut buf = vec![0u8; take_len];
             r.read_exact(&mut buf)?;
 
-            if buf.len() % 2 != 0 {
+            if !buf.len().is_multiple_of(2) {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidData,
                     "odd number of bytes for Vec<i16>",
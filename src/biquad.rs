@@ -0,0 +1,89 @@
+// Second-order IIR sections (transposed direct form II).
+
+use std::f64::consts::PI;
+
+/// One biquad section with its own filter state.
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    // coefficients, normalized so a0 == 1
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    // state
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    /// Build a section from raw coefficients; they're divided through by `a0`.
+    pub fn new(b: [f64; 3], a: [f64; 3]) -> Self {
+        let a0 = a[0];
+        Biquad {
+            b0: b[0] / a0,
+            b1: b[1] / a0,
+            b2: b[2] / a0,
+            a1: a[1] / a0,
+            a2: a[2] / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// RBJ cookbook band-pass (0 dB peak gain) centered on `f0` Hz.
+    pub fn bandpass(sample_rate: u32, f0: f64, q: f64) -> Self {
+        let w0 = 2.0 * PI * f0 / sample_rate as f64;
+        let alpha = w0.sin() / (2.0 * q);
+        Biquad::new(
+            [alpha, 0.0, -alpha],
+            [1.0 + alpha, -2.0 * w0.cos(), 1.0 - alpha],
+        )
+    }
+
+    /// RBJ cookbook low-pass; `q` of 1/sqrt(2) gives a Butterworth response.
+    pub fn lowpass(sample_rate: u32, f0: f64, q: f64) -> Self {
+        let w0 = 2.0 * PI * f0 / sample_rate as f64;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+        Biquad::new(
+            [(1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// RBJ cookbook high-pass; `q` of 1/sqrt(2) gives a Butterworth response.
+    pub fn highpass(sample_rate: u32, f0: f64, q: f64) -> Self {
+        let w0 = 2.0 * PI * f0 / sample_rate as f64;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+        Biquad::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// RBJ cookbook notch at `f0` Hz; higher `q` gives a narrower notch.
+    pub fn notch(sample_rate: u32, f0: f64, q: f64) -> Self {
+        let w0 = 2.0 * PI * f0 / sample_rate as f64;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+        Biquad::new(
+            [1.0, -2.0 * cos, 1.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    pub fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+
+    /// Clear the filter memory, keeping the coefficients.
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
@@ -0,0 +1,119 @@
+// Effective bit depth: how much of the container's resolution the samples
+// actually use, e.g. 16-bit data padded into a 24-bit file.
+
+use std::collections::HashSet;
+
+use crate::sample::Samples;
+
+// n distinct values scattered over a span of S codes include two adjacent
+// codes with probability ~1 - exp(-n^2 / S); demanding n^2 >= 10 S keeps a
+// chance miss below 1e-4
+const ADJACENCY_MARGIN: f64 = 10.0;
+
+/// Result of `effective_bit_depth`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitDepth {
+    pub container: u32, // bits per sample in the file
+    /// Bits left after dropping low-order bits that are zero in every
+    /// sample (for float data: the integer grid the values sit on, if any).
+    pub used_bits: Option<u32>,
+    /// Resolution implied by the histogram: the smallest step between two
+    /// values that occur. Catches lower-depth data scaled by a gain, whose
+    /// codes are spread out without leaving the low bits zero.
+    pub histogram_bits: Option<f32>,
+    pub distinct_values: usize,
+}
+
+impl BitDepth {
+    /// The best single estimate: the lower of the two measures.
+    pub fn effective(&self) -> Option<f32> {
+        match (self.used_bits, self.histogram_bits) {
+            (Some(used), Some(hist)) => Some((used as f32).min(hist)),
+            (Some(used), None) => Some(used as f32),
+            (None, hist) => hist,
+        }
+    }
+}
+
+/// Accumulates `effective_bit_depth` over a file fed block by block, so
+/// only the distinct values seen are kept, not the samples.
+#[derive(Debug, Clone)]
+pub struct BitDepthMeter {
+    container: u32,
+    float: bool,
+    or: i64,           // integer data: every sample OR'ed together
+    grid: Option<u32>, // float data: finest integer grid needed so far; None once a value fits none
+    values: HashSet<u64>, // distinct values (integers as i64 bits, floats as f64 bits)
+}
+
+impl Default for BitDepthMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitDepthMeter {
+    pub fn new() -> Self {
+        BitDepthMeter { container: 0, float: false, or: 0, grid: Some(8), values: HashSet::new() }
+    }
+
+    /// Add the next block of samples; every block must hold the same type.
+    pub fn add(&mut self, samples: &Samples) {
+        match samples {
+            Samples::U8(v) => self.add_integers(v.iter().map(|&s| s as i64 - 128), 8),
+            Samples::I16(v) => self.add_integers(v.iter().map(|&s| s as i64), 16),
+            Samples::I24(v) => self.add_integers(v.iter().map(|s| s.0 as i64), 24),
+            Samples::I32(v) => self.add_integers(v.iter().map(|&s| s as i64), 32),
+            Samples::F32(v) => self.add_floats(v.iter().map(|&s| s as f64), 32),
+            Samples::F64(v) => self.add_floats(v.iter().copied(), 64),
+        }
+    }
+
+    fn add_integers(&mut self, values: impl Iterator<Item = i64>, container: u32) {
+        self.container = container;
+        for v in values {
+            self.or |= v;
+            self.values.insert(v as u64);
+        }
+    }
+
+    fn add_floats(&mut self, values: impl Iterator<Item = f64>, container: u32) {
+        (self.container, self.float) = (container, true);
+        for v in values {
+            // grids k / 2^(n-1) nest, so the finest one any value needs
+            // holds them all
+            self.grid = self.grid.and_then(|finest| {
+                (finest..=24u32).find(|&n| (v * (1u64 << (n - 1)) as f64).fract() == 0.0)
+            });
+            self.values.insert(v.to_bits());
+        }
+    }
+
+    pub fn finish(self) -> BitDepth {
+        let container = self.container;
+        if self.float {
+            return BitDepth { container, used_bits: self.grid, histogram_bits: None, distinct_values: self.values.len() };
+        }
+
+        let used_bits = (self.or != 0).then(|| container.saturating_sub(self.or.trailing_zeros()));
+        let mut distinct: Vec<i64> = self.values.into_iter().map(|v| v as i64).collect();
+        distinct.sort_unstable();
+        let min_step = distinct.windows(2).map(|w| w[1] - w[0]).min();
+        let span = match (distinct.first(), distinct.last()) {
+            (Some(lo), Some(hi)) => (hi - lo) as f64 + 1.0,
+            _ => 0.0,
+        };
+        let enough = (distinct.len() as f64).powi(2) >= ADJACENCY_MARGIN * span;
+        let histogram_bits = min_step.filter(|_| enough)
+            .map(|step| container as f32 - (step as f32).log2());
+
+        BitDepth { container, used_bits, histogram_bits, distinct_values: distinct.len() }
+    }
+}
+
+/// Measure how many bits of resolution `samples` really use.
+pub fn effective_bit_depth(samples: &Samples) -> BitDepth {
+    let mut meter = BitDepthMeter::new();
+    meter.add(samples);
+    meter.finish()
+}
@@ -0,0 +1,75 @@
+// Memory budgets (--max-mem): sizes like "2G", and the block sizes that
+// keep an analysis of a long input under them.
+
+use std::fmt;
+use std::str::FromStr;
+
+// each decoded block gets this fraction of the budget; the rest is left to
+// the STFT state, the per-frame features and the write buffers
+const BLOCK_SHARE: u64 = 8;
+const MIN_BLOCK_FRAMES: u64 = 1 << 12;
+const MAX_BLOCK_FRAMES: u64 = 1 << 22;
+
+/// An upper bound on the memory an analysis may hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBudget {
+    pub bytes: u64,
+}
+
+impl MemoryBudget {
+    pub fn fits(&self, bytes: u64) -> bool {
+        bytes <= self.bytes
+    }
+
+    /// Frames to decode at a time when every frame of a block costs
+    /// `bytes_per_frame` (raw, decoded and mixed down).
+    pub fn block_frames(&self, bytes_per_frame: u64) -> u64 {
+        (self.bytes / BLOCK_SHARE / bytes_per_frame.max(1)).clamp(MIN_BLOCK_FRAMES, MAX_BLOCK_FRAMES)
+    }
+}
+
+/// Accepts a byte count with an optional binary suffix: "2G", "1.5GiB",
+/// "512M", "64k", "1048576".
+impl FromStr for MemoryBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let lower = s.trim().to_ascii_lowercase();
+        let number = lower.trim_end_matches("ib").trim_end_matches('b');
+        let (number, shift) = match number.chars().last() {
+            Some('k') => (&number[..number.len() - 1], 10),
+            Some('m') => (&number[..number.len() - 1], 20),
+            Some('g') => (&number[..number.len() - 1], 30),
+            Some('t') => (&number[..number.len() - 1], 40),
+            _ => (number, 0),
+        };
+        let value: f64 = number.trim().parse().map_err(|_| format!("not a size: {} (e.g. 2G, 512M)", s))?;
+        let bytes = value * (1u64 << shift) as f64;
+        if !bytes.is_finite() || bytes < 1.0 {
+            return Err(format!("memory budget must be positive (got {})", s));
+        }
+        Ok(MemoryBudget { bytes: bytes as u64 })
+    }
+}
+
+impl fmt::Display for MemoryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_bytes(self.bytes).fmt(f)
+    }
+}
+
+/// `bytes` in the largest binary unit that keeps it at least 1, e.g. "1.5 GiB".
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < units.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
@@ -0,0 +1,170 @@
+// Heuristic speech / music / silence classification of one-second segments
+// from zero-crossing rate, spectral flatness, spectral flux and harmonicity.
+
+use rustfft::{FftPlanner, num_complex::Complex};
+
+use crate::stft::{stft, StftConfig};
+
+const FRAME: usize = 2048;
+const HOP: usize = 1024;
+const SEGMENT_SECS: f32 = 1.0;
+
+// segments quieter than this (dBFS RMS) are silence
+const SILENCE_DB: f32 = -50.0;
+
+// harmonicity looks for a pitch period in this range
+const MIN_PITCH_HZ: f32 = 50.0;
+const MAX_PITCH_HZ: f32 = 500.0;
+
+/// What a segment of a recording sounds like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentClass {
+    Silence,
+    Speech,
+    Music,
+}
+
+impl SegmentClass {
+    pub fn name(self) -> &'static str {
+        match self {
+            SegmentClass::Silence => "silence",
+            SegmentClass::Speech => "speech",
+            SegmentClass::Music => "music",
+        }
+    }
+}
+
+/// Per-frame features the classifier votes on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameFeatures {
+    pub rms_db: f32,
+    pub zcr: f32,         // zero crossings per sample
+    pub flatness: f32,    // geometric / arithmetic mean of the power spectrum
+    pub flux: f32,        // positive magnitude change from the previous frame
+    pub harmonicity: f32, // normalized autocorrelation peak over pitch lags
+}
+
+/// A run of consecutive segments with the same label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub start: f32, // seconds
+    pub end: f32,
+    pub class: SegmentClass,
+    pub confidence: f32, // mean share of feature votes behind the label
+}
+
+/// Features of every 2048-sample frame (hop 1024) of a mono signal.
+pub fn frame_features(samples: &[f32], sample_rate: u32) -> Vec<FrameFeatures> {
+    let config = StftConfig { nfft: FRAME, hop: HOP };
+    let spectra = stft(samples, config);
+
+    // autocorrelation by FFT, padded so it isn't circular
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(2 * FRAME);
+    let inverse = planner.plan_fft_inverse(2 * FRAME);
+    let min_lag = (sample_rate as f32 / MAX_PITCH_HZ) as usize;
+    let max_lag = ((sample_rate as f32 / MIN_PITCH_HZ) as usize).min(FRAME - 1);
+    let mut buffer = vec![Complex { re: 0.0, im: 0.0 }; 2 * FRAME];
+
+    let mut previous: Option<Vec<f32>> = None;
+    spectra.iter().enumerate().map(|(t, spectrum)| {
+        let frame = &samples[t * HOP..t * HOP + FRAME];
+
+        let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / FRAME as f32;
+        let zcr = frame.windows(2).filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0)).count() as f32 / FRAME as f32;
+
+        let magnitudes: Vec<f32> = spectrum.iter().map(|c| c.norm()).collect();
+        let power: Vec<f64> = magnitudes.iter().map(|&m| m as f64 * m as f64 + 1e-20).collect();
+        let log_mean = power.iter().map(|p| p.ln()).sum::<f64>() / power.len() as f64;
+        let flatness = (log_mean.exp() / (power.iter().sum::<f64>() / power.len() as f64)) as f32;
+
+        let total = magnitudes.iter().sum::<f32>().max(1e-9);
+        let flux = previous.as_ref().map_or(0.0, |prev| {
+            let prev_total = prev.iter().sum::<f32>().max(1e-9);
+            magnitudes.iter().zip(prev).map(|(&m, &p)| (m / total - p / prev_total).max(0.0)).sum()
+        });
+        previous = Some(magnitudes);
+
+        for (b, &s) in buffer.iter_mut().zip(frame.iter().chain(std::iter::repeat(&0.0))) {
+            *b = Complex { re: s, im: 0.0 };
+        }
+        forward.process(&mut buffer);
+        for b in buffer.iter_mut() {
+            *b = Complex { re: b.norm_sqr(), im: 0.0 };
+        }
+        inverse.process(&mut buffer);
+        let r0 = buffer[0].re.max(1e-12);
+        // scale each lag for the shrinking overlap so long periods aren't penalized
+        let harmonicity = (min_lag..=max_lag)
+            .map(|lag| buffer[lag].re / r0 * FRAME as f32 / (FRAME - lag) as f32)
+            .fold(0.0f32, f32::max)
+            .min(1.0);
+
+        FrameFeatures { rms_db: 10.0 * (mean_square + 1e-12).log10(), zcr, flatness, flux, harmonicity }
+    }).collect()
+}
+
+fn mean_std(values: impl Iterator<Item = f32> + Clone) -> (f32, f32) {
+    let n = values.clone().count().max(1) as f32;
+    let mean = values.clone().sum::<f32>() / n;
+    let variance = values.map(|v| (v - mean) * (v - mean)).sum::<f32>() / n;
+    (mean, variance.sqrt())
+}
+
+// speech alternates voiced and unvoiced sounds every few tens of
+// milliseconds, so its features swing more and it is less sustained-tonal
+fn classify_segment(frames: &[FrameFeatures]) -> (SegmentClass, f32) {
+    let power = frames.iter().map(|f| 10f32.powf(f.rms_db / 10.0)).sum::<f32>() / frames.len() as f32;
+    if 10.0 * (power + 1e-12).log10() < SILENCE_DB {
+        return (SegmentClass::Silence, 1.0);
+    }
+
+    let (zcr_mean, zcr_std) = mean_std(frames.iter().map(|f| f.zcr));
+    let (_, flatness_std) = mean_std(frames.iter().map(|f| f.flatness));
+    let (flux_mean, flux_std) = mean_std(frames.iter().map(|f| f.flux));
+    let (harmonicity, _) = mean_std(frames.iter().map(|f| f.harmonicity));
+
+    let votes = [
+        zcr_std > 0.5 * zcr_mean,
+        flatness_std > 0.05,
+        flux_std > 0.75 * flux_mean,
+        harmonicity < 0.75,
+    ];
+    let speech = votes.iter().filter(|&&v| v).count() as f32 / votes.len() as f32;
+    if speech > 0.5 {
+        (SegmentClass::Speech, speech)
+    } else {
+        (SegmentClass::Music, 1.0 - speech)
+    }
+}
+
+/// Label a mono signal in one-second segments and merge runs of the same
+/// label into regions.
+pub fn classify(samples: &[f32], sample_rate: u32) -> Vec<Region> {
+    let features = frame_features(samples, sample_rate);
+    let per_segment = ((SEGMENT_SECS * sample_rate as f32) as usize / HOP).max(1);
+    let duration = samples.len() as f32 / sample_rate as f32;
+
+    let mut regions: Vec<Region> = Vec::new();
+    let mut merged = 0;
+    for (i, chunk) in features.chunks(per_segment).enumerate() {
+        let (class, confidence) = classify_segment(chunk);
+        let start = (i * per_segment * HOP) as f32 / sample_rate as f32;
+        let end = (((i + 1) * per_segment * HOP) as f32 / sample_rate as f32).min(duration);
+        match regions.last_mut() {
+            Some(last) if last.class == class => {
+                merged += 1;
+                last.confidence += (confidence - last.confidence) / merged as f32;
+                last.end = end;
+            }
+            _ => {
+                merged = 1;
+                regions.push(Region { start, end, class, confidence });
+            }
+        }
+    }
+    if let Some(last) = regions.last_mut() {
+        last.end = duration;
+    }
+    regions
+}
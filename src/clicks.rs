@@ -0,0 +1,69 @@
+// Click and pop detection for vinyl transfers: outliers in the second
+// difference of each channel, judged against a robust local noise level.
+
+// the local noise level is re-estimated over blocks of this length
+const BLOCK_SECS: f32 = 0.02;
+
+// detections closer than this belong to the same click
+const MERGE_SECS: f32 = 0.005;
+
+// noise level floor, so digital silence doesn't turn dither into clicks
+const MIN_SIGMA: f32 = 1e-4;
+
+/// One detected click.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Click {
+    pub frame: usize,   // strongest frame of the click
+    pub time: f32,      // seconds
+    pub strength: f32,  // second difference in robust standard deviations
+}
+
+/// Clicks in interleaved `samples`, strongest frame first within each; a
+/// frame is flagged when any channel's second difference exceeds
+/// `sensitivity` times that block's noise level (median absolute value
+/// scaled to a standard deviation).
+pub fn detect_clicks(samples: &[f32], channels: usize, sample_rate: u32, sensitivity: f32) -> Vec<Click> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let block = ((sample_rate as f32 * BLOCK_SECS) as usize).max(3);
+
+    // per-frame strength: the worst channel's normalized second difference
+    let mut strength = vec![0.0f32; frames];
+    for ch in 0..channels {
+        let x: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+        let diff: Vec<f32> = (0..frames)
+            .map(|n| if n < 2 { 0.0 } else { (x[n] - 2.0 * x[n - 1] + x[n - 2]).abs() })
+            .collect();
+
+        for start in (0..frames).step_by(block) {
+            let end = (start + block).min(frames);
+            let mut sorted = diff[start..end].to_vec();
+            sorted.sort_by(f32::total_cmp);
+            let sigma = (sorted[sorted.len() / 2] / 0.6745).max(MIN_SIGMA);
+            for n in start..end {
+                strength[n] = strength[n].max(diff[n] / sigma);
+            }
+        }
+    }
+
+    let merge = (sample_rate as f32 * MERGE_SECS) as usize;
+    let mut clicks: Vec<Click> = Vec::new();
+    for (n, &s) in strength.iter().enumerate() {
+        if s < sensitivity {
+            continue;
+        }
+        match clicks.last_mut() {
+            Some(last) if n - last.frame <= merge => {
+                if s > last.strength {
+                    last.frame = n;
+                    last.strength = s;
+                }
+            }
+            _ => clicks.push(Click { frame: n, time: 0.0, strength: s }),
+        }
+    }
+    for click in &mut clicks {
+        click.time = click.frame as f32 / sample_rate as f32;
+    }
+    clicks
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use fft_rs::meter::{db_to_gain, integrated_loudness};
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::convert::DitherArg;
+
+#[derive(Args)]
+pub struct AbMatchArgs {
+    /// First WAV file of the comparison
+    a: PathBuf,
+    /// Second WAV file of the comparison
+    b: PathBuf,
+    /// Where to write the louder file turned down to the quieter one's
+    /// loudness (default: <louder>_matched.wav next to it)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Dither used when quantizing back to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+}
+
+struct Measured {
+    spec: WavSpec, // to write a copy in the same format
+    samples: Vec<f32>,
+    loudness: f32,
+}
+
+fn measure(path: &Path) -> Result<Measured, Box<dyn Error>> {
+    let wav = WavFile::parse(&mut File::open(path)?)?;
+    let fmt = wav.fmt.as_ref().ok_or_else(|| format!("{}: no fmt chunk", path.display()))?;
+    let spec = WavSpec { channels: fmt.num_channels.max(1), sample_rate: fmt.sample_rate, format: SampleFormat::matching(fmt) };
+    let samples = wav.to_normalized_samples()?;
+    let loudness = integrated_loudness(&samples, spec.channels as usize, spec.sample_rate)
+        .ok_or_else(|| format!("{}: too short or too quiet to measure loudness", path.display()))?;
+    Ok(Measured { spec, samples, loudness })
+}
+
+/// Measure the integrated loudness of both files and write a copy of the
+/// louder one attenuated to match the quieter, so an A/B listening test
+/// isn't won by level alone. Turning down rather than up never clips.
+pub fn run(args: AbMatchArgs) -> Result<(), Box<dyn Error>> {
+    let a = measure(&args.a)?;
+    let b = measure(&args.b)?;
+    println!("A: {:.2} LUFS  {}", a.loudness, args.a.display());
+    println!("B: {:.2} LUFS  {}", b.loudness, args.b.display());
+
+    let (louder, louder_path, quieter) = if a.loudness >= b.loudness { (&a, &args.a, &b) } else { (&b, &args.b, &a) };
+    let gain_db = quieter.loudness - louder.loudness;
+    let name = if louder_path == &args.a { "A" } else { "B" };
+    println!("{} is louder by {:.2} LU", name, -gain_db);
+
+    let output = args.output.clone().unwrap_or_else(|| {
+        let stem = louder_path.file_stem().and_then(|s| s.to_str()).unwrap_or("louder");
+        louder_path.with_file_name(format!("{}_matched.wav", stem))
+    });
+    let gain = db_to_gain(gain_db);
+    let scaled: Vec<f32> = louder.samples.iter().map(|&s| s * gain).collect();
+    write_wav_file(&output, louder.spec, &scaled, args.dither.into())?;
+
+    info!("Applied {:+.2} dB to {}, wrote {}", gain_db, name, output.display());
+    Ok(())
+}
@@ -0,0 +1,359 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::classify::classify;
+use fft_rs::dynamics::{crest_factor_db, dr_score};
+use fft_rs::hum::{detect_mains, hum_candidates};
+use fft_rs::iso226::equal_loudness_contour;
+use fft_rs::meter::{Calibration, LevelMeter, short_term_loudness, to_db};
+use fft_rs::pass::{AudioBuffer, LevelsPass, PassRegistry, Report};
+use fft_rs::bands::{band_layout, band_levels_dbfs, energy_distribution, BandFraction};
+use fft_rs::room::find_room_modes;
+use fft_rs::sample::mix_to_mono;
+use fft_rs::spectrum::compute_spectrum;
+use fft_rs::stereo::{coherence, side_mid_ratio_db, width_over_time};
+use fft_rs::stft::StftConfig;
+use fft_rs::structure::segment;
+use fft_rs::wav::LazyWavFile;
+use plotters::style::RGBColor;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::multiband::band_label;
+use super::ssm::StructureArgs;
+use crate::plots::{plot_lines, Series};
+
+// frames decoded at a time
+const BLOCK_FRAMES: u64 = 1 << 16;
+
+// coherence needs many averaged frames, so a moderate window
+const COHERENCE_NFFT: usize = 2048;
+
+// line colors of the pass plots, in series order
+const PALETTE: [RGBColor; 4] = [
+    RGBColor(200, 60, 0),
+    RGBColor(0, 120, 160),
+    RGBColor(120, 0, 200),
+    RGBColor(0, 150, 60),
+];
+
+// equal-loudness contours drawn under the calibrated spectrum (phon)
+const CONTOUR_PHONS: [f32; 4] = [20.0, 40.0, 60.0, 80.0];
+
+// energy below SUBSONIC_HZ is inaudible but still takes headroom; warn when
+// it is more than this share of the total
+const SUBSONIC_HZ: f32 = 20.0;
+const SUBSONIC_WARNING: f32 = 0.05;
+
+// structural segmentation looks for changes on this time scale (seconds)
+const SEGMENT_KERNEL_SECS: f32 = 8.0;
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Also render the plots (crest_factor.png, and stereo_width.png and
+    /// coherence.png for stereo input) in the working directory
+    #[arg(long)]
+    plots: bool,
+    /// Look for low-frequency room modes (peaks and dips below 300 Hz), e.g.
+    /// in a recorded sweep
+    #[arg(long)]
+    room_modes: bool,
+    /// Calibrator measurement, e.g. 94dB@1kHz=0.05 (RMS of the recorded
+    /// tone, linear or in dBFS), to also report levels in dB SPL
+    #[arg(long, value_name = "SPL@FREQ=RMS", allow_hyphen_values = true)]
+    cal: Option<Calibration>,
+    /// Plot the calibrated third-octave spectrum in dB SPL on ISO 226
+    /// equal-loudness contours (spl_spectrum.png)
+    #[arg(long, requires = "cal")]
+    phon_contours: bool,
+    /// Check for 50/60 Hz mains hum and report its first N harmonics
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "5")]
+    hum: Option<usize>,
+    /// Label the recording as speech, music or silence in one-second
+    /// segments and print the timeline
+    #[arg(long)]
+    classify: bool,
+    /// Report each band's share of the total energy, split at these
+    /// frequencies in Hz, e.g. to catch subsonic content eating headroom.
+    /// A custom list needs the equals sign: --energy-bands=20,80,250
+    #[arg(
+        long,
+        value_name = "HZ,...",
+        value_delimiter = ',',
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "20,40,120,500,2000,8000"
+    )]
+    energy_bands: Option<Vec<f32>>,
+    /// Find section boundaries (verse/chorus-scale changes in harmony) and
+    /// label sections that sound alike with the same letter
+    #[arg(long)]
+    segments: bool,
+    /// Also write the report as JSON to this path
+    #[arg(long, value_name = "PATH")]
+    json: Option<PathBuf>,
+}
+
+/// Measure the file and print a level report.
+pub fn run(args: AnalyzeArgs) -> Result<(), Box<dyn Error>> {
+    // decode block by block, metering peaks, loudness and ReplayGain as the
+    // samples go by. DR, coherence, crest factor and the optional sections
+    // still need the whole signal, so unlike batch and export this holds
+    // every decoded sample (though never the raw payload as well)
+    let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+    let fmt = lazy.wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let (num_channels, sample_rate) = (fmt.num_channels.max(1) as usize, fmt.sample_rate);
+    let frames = lazy.wav.num_frames().unwrap_or(0) as usize;
+
+    let mut meter = LevelMeter::new(num_channels, sample_rate);
+    let mut decoded = Vec::with_capacity(frames * num_channels);
+    for block in lazy.blocks(BLOCK_FRAMES) {
+        let block = block?;
+        meter.push(&block);
+        decoded.extend_from_slice(&block);
+    }
+    let audio = AudioBuffer::new(decoded, num_channels, sample_rate);
+    let (samples, channels) = (audio.samples.as_slice(), audio.channels);
+
+    let peak_db = to_db(meter.sample_peak());
+    let true_peak_db = to_db(meter.true_peak());
+    let loudness = meter.integrated_loudness();
+
+    // sections that also go into the JSON report, starting with the
+    // standard passes
+    let mut report = Report::default();
+    report.insert("file", json!(args.input.display().to_string()));
+    report.insert("sample_rate", json!(sample_rate));
+    report.insert("channels", json!(channels));
+    report.insert("duration", json!(audio.duration()));
+    report.insert("levels", LevelsPass::section(meter.sample_peak(), meter.true_peak(), loudness));
+    PassRegistry::whole_signal().run(&audio, &mut report)?;
+
+    println!("Levels:");
+    println!("  Sample peak: {:.2} dBFS", peak_db);
+    println!("  True peak: {:.2} dBTP", true_peak_db);
+    match loudness {
+        Some(loudness) => println!("  Integrated loudness: {:.2} LUFS", loudness),
+        None => println!("  Integrated loudness: n/a (too short or too quiet)"),
+    }
+
+    if let Some(cal) = &args.cal {
+        let rms = (samples.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / samples.len().max(1) as f64).sqrt();
+        println!("\nCalibrated (offset {:+.2} dB):", cal.offset_db());
+        println!("  RMS level: {:.1} dB SPL", cal.to_spl(to_db(rms as f32)));
+        println!("  Sample peak: {:.1} dB SPL", cal.to_spl(peak_db));
+        let mono = mix_to_mono(samples, channels);
+        let spectrum = compute_spectrum(&mono, sample_rate);
+        println!("  Octave bands:");
+        for band in band_levels_dbfs(&spectrum, mono.len(), BandFraction::Octave) {
+            println!("    {:>6} Hz: {:5.1} dB SPL", band.nominal, cal.to_spl(band.level_db));
+        }
+
+        if args.phon_contours {
+            let levels: Vec<(f32, f32)> = band_levels_dbfs(&spectrum, mono.len(), BandFraction::Third).iter()
+                .map(|band| (band.center, cal.to_spl(band.level_db)))
+                .collect();
+            let contours: Vec<(String, Vec<(f32, f32)>)> = CONTOUR_PHONS.iter()
+                .filter_map(|&phon| Some((format!("{} phon", phon), equal_loudness_contour(phon)?)))
+                .collect();
+            let mut series: Vec<Series> = contours.iter().enumerate()
+                .map(|(i, (label, points))| {
+                    let shade = 200 - 30 * i as u8;
+                    Series { label, points, color: RGBColor(shade, shade, shade) }
+                })
+                .collect();
+            series.push(Series { label: "Third-octave bands", points: &levels, color: RGBColor(200, 60, 0) });
+            plot_lines("Spectrum on Equal-Loudness Contours (ISO 226)", "Frequency (Hz)", "Level (dB SPL)", &series, true, "spl_spectrum.png")?;
+            info!("Equal-loudness plot saved to 'spl_spectrum.png'");
+        }
+    }
+
+    if let Some(dr) = dr_score(samples, channels, sample_rate) {
+        println!("\nDynamics:");
+        println!("  DR score: DR{}", dr.track);
+        for (ch, score) in dr.channels.iter().enumerate() {
+            let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+            println!("  Channel {}: DR {:.1} dB, crest factor {:.2} dB", ch + 1, score, crest_factor_db(&channel));
+        }
+        // peak-to-loudness ratios: how far peaks stand above the average
+        // (PLR) and above the loudest 3 s stretch (PSR)
+        if let Some(loudness) = loudness {
+            println!("  PLR: {:.2} dB", true_peak_db - loudness);
+        }
+        let short_term = short_term_loudness(samples, channels, sample_rate);
+        if let Some(max) = short_term.into_iter().reduce(f32::max) {
+            println!("  PSR: {:.2} dB", true_peak_db - max);
+        }
+    }
+
+    // coherence feeds both the report and the plot
+    let config = StftConfig::with_overlap(COHERENCE_NFFT, 0.5);
+    let msc = (channels == 2 && config.validate(samples.len() / 2).is_ok())
+        .then(|| coherence(samples, sample_rate, config));
+
+    if channels == 2 {
+        if let Some(ratio) = side_mid_ratio_db(samples) {
+            println!("\nStereo:");
+            println!("  Side/mid ratio: {:.2} dB", ratio);
+        }
+
+        if let Some(msc) = &msc {
+            println!("  Coherence by octave band:");
+            for band in band_layout(BandFraction::Octave, sample_rate as f32 / 2.0) {
+                let in_band: Vec<f32> = msc.iter()
+                    .filter(|(f, _)| *f >= band.lower && *f < band.upper)
+                    .map(|&(_, c)| c)
+                    .collect();
+                if !in_band.is_empty() {
+                    let mean = in_band.iter().sum::<f32>() / in_band.len() as f32;
+                    println!("    {:>6} Hz: {:.3}", band.nominal, mean);
+                }
+            }
+        }
+    }
+
+    if let Some(rg) = meter.replay_gain() {
+        println!("\nReplayGain 2.0:");
+        println!("  REPLAYGAIN_TRACK_GAIN: {:+.2} dB", rg.gain_db);
+        println!("  REPLAYGAIN_TRACK_PEAK: {:.6}", rg.peak);
+    }
+
+    if let Some(harmonics) = args.hum {
+        let mono = mix_to_mono(samples, channels);
+        let candidates = hum_candidates(&compute_spectrum(&mono, sample_rate), mono.len(), harmonics);
+        println!("\nMains hum:");
+        match detect_mains(&candidates) {
+            Some(mains) => println!("  Detected: {} Hz", mains),
+            None => println!("  Detected: none"),
+        }
+        for candidate in &candidates {
+            println!("  {} Hz harmonics:", candidate.mains_hz);
+            for h in &candidate.harmonics {
+                println!(
+                    "    {:>2}: {:7.2} Hz {:7.1} dBFS {:+6.1} dB over floor{}",
+                    h.harmonic, h.frequency, h.level_db, h.prominence_db,
+                    if h.is_present() { "  *" } else { "" }
+                );
+            }
+        }
+    }
+
+    if let Some(edges) = &args.energy_bands {
+        if edges.iter().any(|&f| !(f > 0.0 && f < sample_rate as f32 / 2.0)) {
+            return Err(format!("energy bands must be between 0 and {} Hz", sample_rate / 2).into());
+        }
+        if edges.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("energy bands must be in ascending order".into());
+        }
+        // each channel's own energy, so out-of-phase lows aren't mixed away
+        let spectra: Vec<_> = (0..channels)
+            .map(|ch| {
+                let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+                compute_spectrum(&channel, sample_rate)
+            })
+            .collect();
+        let shares = energy_distribution(&spectra, edges);
+        let labels: Vec<String> = (0..shares.len())
+            .map(|i| band_label(i.checked_sub(1).map(|j| edges[j]), edges.get(i).copied()))
+            .collect();
+        println!("\nEnergy distribution:");
+        for (label, share) in labels.iter().zip(&shares) {
+            println!("  {:>14}: {:5.1}%", label, share * 100.0);
+        }
+        let subsonic = energy_distribution(&spectra, &[SUBSONIC_HZ])[0];
+        if subsonic > SUBSONIC_WARNING {
+            println!(
+                "  Warning: {:.1}% of the energy is below {} Hz, inaudible but taking headroom",
+                subsonic * 100.0, SUBSONIC_HZ
+            );
+        }
+        report.insert("energy_distribution", labels.iter().zip(&shares).enumerate().map(|(i, (label, share))| json!({
+            "band": label,
+            "lower_hz": i.checked_sub(1).map(|j| edges[j]),
+            "upper_hz": edges.get(i),
+            "percent": share * 100.0,
+        })).collect::<Value>());
+    }
+
+    if args.classify {
+        let regions = classify(&mix_to_mono(samples, channels), sample_rate);
+        println!("\nTimeline:");
+        for region in &regions {
+            println!(
+                "  {:8.2} - {:8.2} s  {:<7}  ({:.0}% of votes)",
+                region.start, region.end, region.class.name(), region.confidence * 100.0
+            );
+        }
+        report.insert("timeline", regions.iter().map(|region| json!({
+            "start": region.start,
+            "end": region.end,
+            "label": region.class.name(),
+            "confidence": region.confidence,
+        })).collect::<Value>());
+    }
+
+    if args.segments {
+        let features = StructureArgs::default().features(&mix_to_mono(samples, channels), sample_rate)?;
+        let kernel = (SEGMENT_KERNEL_SECS / features.step).round() as usize;
+        let sections = segment(&features.vectors, features.step, kernel);
+        println!("\nSections:");
+        for section in &sections {
+            println!("  {:8.2} - {:8.2} s  {}", section.start, section.end, section.name());
+        }
+        report.insert("segments", sections.iter().map(|section| json!({
+            "start": section.start,
+            "end": section.end,
+            "label": section.name(),
+        })).collect::<Value>());
+    }
+
+    if args.room_modes {
+        let spectrum = compute_spectrum(&mix_to_mono(samples, channels), sample_rate);
+        let modes = find_room_modes(&spectrum);
+        println!("\nRoom modes (15-300 Hz): {}", modes.len());
+        for mode in &modes {
+            println!(
+                "  {:7.1} Hz  {:>4} {:+6.1} dB  Q {:.1}",
+                mode.frequency,
+                if mode.is_peak() { "peak" } else { "dip" },
+                mode.deviation_db,
+                mode.q
+            );
+        }
+    }
+
+    if args.plots {
+        for plot in &report.plots {
+            let series: Vec<Series> = plot.series.iter().zip(PALETTE.iter().cycle())
+                .map(|(s, &color)| Series { label: &s.label, points: &s.points, color })
+                .collect();
+            let path = format!("{}.png", plot.name);
+            plot_lines(&plot.title, &plot.x_desc, &plot.y_desc, &series, plot.log_x, &path)?;
+            info!("{} plot saved to '{}'", plot.title, path);
+        }
+
+        if channels == 2 {
+            let width = width_over_time(samples, sample_rate);
+            let series = [Series { label: "Side/mid", points: &width, color: RGBColor(120, 0, 200) }];
+            plot_lines("Stereo Width Over Time", "Time (s)", "Side / mid energy (dB)", &series, false, "stereo_width.png")?;
+            info!("Stereo width plot saved to 'stereo_width.png'");
+
+            if let Some(msc) = &msc {
+                let series = [Series { label: "L/R coherence", points: &msc[1..], color: RGBColor(0, 120, 160) }];
+                plot_lines("Inter-channel Coherence", "Frequency (Hz)", "Magnitude-squared coherence", &series, true, "coherence.png")?;
+                info!("Coherence plot saved to 'coherence.png'");
+            }
+        }
+    }
+
+    if let Some(path) = &args.json {
+        std::fs::write(path, serde_json::to_string_pretty(&report.to_json())?)?;
+        info!("JSON report saved to '{}'", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::animation::{AnimationFormat, AnimationWriter};
+use fft_rs::sample::mix_to_mono;
+use fft_rs::stft::{Spectrogram, StftConfig};
+use fft_rs::wav::LazyWavFile;
+use plotters::prelude::*;
+use tracing::{debug, info};
+
+use super::{format_time, parse_size, parse_time};
+use crate::plots::{background, caption_font, themed};
+
+// lowest frequency on the log axis
+const MIN_FREQ: f32 = 20.0;
+
+const LINE: RGBColor = RGBColor(30, 120, 220);
+
+#[derive(Args)]
+pub struct AnimateArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output animation: .gif, or .png/.apng for an animated PNG
+    output: PathBuf,
+    /// Frames per second; each shows the spectrum at that moment
+    #[arg(long, default_value_t = 10)]
+    fps: u16,
+    /// FFT size of each frame
+    #[arg(long, default_value_t = 4096)]
+    nfft: usize,
+    /// Region start, as seconds, m:ss.s or h:mm:ss.s (default: beginning)
+    #[arg(long, value_parser = parse_time)]
+    start: Option<f64>,
+    /// Region end, same formats as --start (default: end of file)
+    #[arg(long, value_parser = parse_time)]
+    end: Option<f64>,
+    /// Levels shown, in dB below full scale
+    #[arg(long, default_value_t = 100.0)]
+    range: f32,
+    /// Frame size in pixels
+    #[arg(long, default_value = "960x540", value_parser = parse_size)]
+    size: (u16, u16),
+}
+
+/// Render the spectrum of successive STFT frames of the mono mix as an
+/// animated GIF or APNG, one frame per 1/fps seconds.
+pub fn run(args: AnimateArgs) -> Result<(), Box<dyn Error>> {
+    let format = AnimationFormat::from_path(&args.output).ok_or("output must end in .gif, .png or .apng")?;
+    if args.fps == 0 || args.range <= 0.0 {
+        return Err("fps and range must be positive".into());
+    }
+
+    // decode only the region
+    let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+    let fmt = lazy.wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let (rate, channels) = (fmt.sample_rate, fmt.num_channels.max(1) as usize);
+    let total = lazy.wav.num_frames().ok_or("input has no data chunk")?;
+    let start = (args.start.unwrap_or(0.0) * rate as f64).round() as u64;
+    let end = args.end.map_or(total, |t| ((t * rate as f64).round() as u64).min(total));
+    if start >= end {
+        return Err(format!("empty region: start frame {} is not before end frame {}", start, end).into());
+    }
+    let bytes = lazy.read_frames(start, end - start)?;
+    let fmt = lazy.wav.fmt.as_ref().unwrap();
+    let mono = mix_to_mono(&fmt.decode(&bytes)?.to_f32(), channels);
+
+    let hop = ((rate as f32 / args.fps as f32).round() as usize).max(1);
+    let config = StftConfig { nfft: args.nfft, hop };
+    // unlike an STFT for resynthesis, frames may skip samples between them
+    if args.nfft < 2 || args.nfft > mono.len() {
+        return Err(format!("FFT size {} must lie between 2 and the region length ({} samples)", args.nfft, mono.len()).into());
+    }
+    let spectrogram = Spectrogram::compute(&mono, rate, config);
+    let frames = spectrogram.frames.len();
+    debug!("{} frames of {} points, one every {} samples", frames, args.nfft, hop);
+
+    // full-scale sine amplitude from the Hann window's coherent gain
+    let gain = 4.0 / args.nfft as f32;
+    let (width, height) = args.size;
+    let mut writer = AnimationWriter::create(&args.output, format, width, height, frames as u32, args.fps)?;
+    let mut buffer = vec![0u8; width as usize * height as usize * 3];
+    for (t, frame) in spectrogram.frames.iter().enumerate() {
+        // each frame is labelled with the time at its window's center
+        let time = start as f64 / rate as f64 + (spectrogram.frame_time(t) + args.nfft as f32 / 2.0 / rate as f32) as f64;
+        let points = frame.iter().enumerate()
+            .skip(1)
+            .map(|(k, &m)| (spectrogram.bin_frequency(k), (20.0 * (m * gain + 1e-9).log10()).max(-args.range)))
+            .filter(|&(f, _)| f >= MIN_FREQ);
+        draw_frame(&mut buffer, (width, height), points, rate as f32 / 2.0, args.range, &format_time(time))?;
+        writer.push(&buffer)?;
+    }
+    writer.finish()?;
+
+    info!("Wrote {} frames ({:.1} s at {} fps) to {}", frames, frames as f32 / args.fps as f32, args.fps, args.output.display());
+    Ok(())
+}
+
+// one spectrum, dBFS over a log frequency axis, into an RGB buffer
+fn draw_frame(
+    buffer: &mut [u8],
+    size: (u16, u16),
+    points: impl Iterator<Item = (f32, f32)>,
+    nyquist: f32,
+    range: f32,
+    time: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::with_buffer(buffer, (size.0 as u32, size.1 as u32)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption(format!("Spectrum at {}", time), caption_font(24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d((MIN_FREQ..nyquist).log_scale(), -range..0f32)?;
+
+    themed(&mut chart.configure_mesh())
+        .x_desc("Frequency (Hz)")
+        .y_desc("Level (dBFS)")
+        .axis_desc_style(caption_font(16))
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(points, LINE.stroke_width(2)))?;
+    root_area.present()?;
+    Ok(())
+}
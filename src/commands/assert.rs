@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::baseline::Baseline;
+use tracing::info;
+
+use super::{load_mono, parse_db};
+
+#[derive(Args)]
+pub struct AssertArgs {
+    /// Rendered WAV file to check
+    input: PathBuf,
+    /// Baseline JSON of the expected band levels and peaks
+    #[arg(long, value_name = "PATH")]
+    baseline: PathBuf,
+    /// Largest deviation from a baseline level that still passes, e.g. 1dB
+    #[arg(long, value_name = "DB", default_value = "1dB", value_parser = parse_db)]
+    tolerance: f32,
+    /// Measure the input and (over)write the baseline instead of checking
+    #[arg(long)]
+    update: bool,
+}
+
+/// Compare the mono mix's third-octave band levels and spectral peaks with
+/// a stored baseline; any level off by more than the tolerance is an error,
+/// so the process exits nonzero.
+pub fn run(args: AssertArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+
+    if args.update {
+        let baseline = Baseline::measure(&samples, rate)?;
+        fs::write(&args.baseline, serde_json::to_string_pretty(&baseline)? + "\n")?;
+        info!("Baseline of {} bands and {} peaks written to '{}'", baseline.bands.len(), baseline.peaks.len(), args.baseline.display());
+        return Ok(());
+    }
+    if args.tolerance < 0.0 {
+        return Err("tolerance must not be negative".into());
+    }
+
+    let text = fs::read_to_string(&args.baseline)
+        .map_err(|e| format!("{}: {} (create it with --update)", args.baseline.display(), e))?;
+    let baseline: Baseline = serde_json::from_str(&text).map_err(|e| format!("{}: {}", args.baseline.display(), e))?;
+    let checks = baseline.compare(&samples, rate)?;
+
+    let failed: Vec<_> = checks.iter().filter(|c| !c.passes(args.tolerance)).collect();
+    for check in &failed {
+        println!(
+            "FAIL {:<16} {:7.1} dB, baseline {:7.1} dB ({:+.1} dB)",
+            check.name,
+            check.actual_db,
+            check.expected_db,
+            check.deviation_db()
+        );
+    }
+    println!("{} of {} checks within {} dB of the baseline", checks.len() - failed.len(), checks.len(), args.tolerance);
+
+    if !failed.is_empty() {
+        return Err(format!("{} levels deviate from '{}'", failed.len(), args.baseline.display()).into());
+    }
+    Ok(())
+}
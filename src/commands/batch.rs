@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use fft_rs::analyzer::SpectrumAnalyzer;
+use fft_rs::key::detect_key;
+use fft_rs::meter::{to_db, LevelMeter};
+use fft_rs::rhythm::{log_compress, novelty_from_flux, spectral_flux, tempo_config, tempo_of_novelty};
+use fft_rs::sample::mix_to_mono;
+use fft_rs::stft::StftConfig;
+use fft_rs::structure::chroma_vector;
+use fft_rs::wav::{LazyWavFile, WAVE_FORMAT_IEEE_FLOAT};
+use fft_rs::window::Window;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use rusqlite::{params, Connection};
+use tracing::{debug, error, info};
+
+// chroma framing for key detection
+const KEY_NFFT: usize = 4096;
+const KEY_HOP: usize = 2048;
+
+// frames decoded at a time
+const BLOCK_FRAMES: u64 = 1 << 16;
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// WAV files, or directories searched recursively for .wav files
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+    /// SQLite database that receives one row per file (replacing earlier
+    /// rows for the same path)
+    #[arg(long, value_name = "PATH")]
+    db: Option<PathBuf>,
+    /// Files measured at once (default: one per CPU core)
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+}
+
+/// Everything the batch run measures for one file.
+struct Summary {
+    path: PathBuf,
+    format: String,
+    sample_rate: u32,
+    channels: u16,
+    bits: u16,
+    duration: f64,
+    loudness: Option<f32>,
+    sample_peak: f32,
+    true_peak: f32,
+    key: Option<String>,
+    bpm: Option<f32>,
+}
+
+fn collect_wavs(path: &Path, found: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    if !path.is_dir() {
+        found.push(path.to_path_buf());
+        return Ok(());
+    }
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)?.map(|e| e.map(|e| e.path())).collect::<Result<_, _>>()?;
+    entries.sort();
+    for entry in entries {
+        let is_wav = entry.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav"));
+        if entry.is_dir() || is_wav {
+            collect_wavs(&entry, found)?;
+        }
+    }
+    Ok(())
+}
+
+// Decode block by block, feeding every measurement as the samples go by,
+// so memory stays flat however long the file is
+fn summarize(path: &Path, analyzer: &mut SpectrumAnalyzer) -> Result<Summary, Box<dyn Error>> {
+    let mut lazy = LazyWavFile::open(File::open(path)?)?;
+    let duration = lazy.wav.duration().unwrap_or(0.0);
+    let fmt = lazy.wav.fmt.as_ref().ok_or("no fmt chunk")?;
+    let format = if fmt.format_code() == WAVE_FORMAT_IEEE_FLOAT { "float" } else { "pcm" };
+    let format = format!("{}{}", format, fmt.bits_per_sample);
+    let (sample_rate, num_channels, bits) = (fmt.sample_rate, fmt.num_channels, fmt.bits_per_sample);
+    let channels = num_channels.max(1) as usize;
+
+    let mut meter = LevelMeter::new(channels, sample_rate);
+    let mut key_stft = analyzer.stream(StftConfig { nfft: KEY_NFFT, hop: KEY_HOP }, Window::Hann);
+    let mut tempo_stft = analyzer.stream(tempo_config(), Window::Hann);
+    let mut chroma_total = vec![0.0f32; 12];
+    let mut flux = Vec::new();
+    let mut previous: Option<Vec<f32>> = None;
+
+    for block in lazy.blocks(BLOCK_FRAMES) {
+        let block = block?;
+        meter.push(&block);
+        let mono = mix_to_mono(&block, channels);
+        key_stft.push(&mono, |frame| {
+            for (total, c) in chroma_total.iter_mut().zip(chroma_vector(frame, sample_rate, KEY_NFFT)) {
+                *total += c;
+            }
+        });
+        tempo_stft.push(&mono, |frame| {
+            let compressed = log_compress(frame);
+            if let Some(previous) = &previous {
+                flux.push(spectral_flux(previous, &compressed));
+            }
+            previous = Some(compressed);
+        });
+    }
+
+    let novelty = novelty_from_flux(&flux, sample_rate as f32 / tempo_config().hop as f32);
+    Ok(Summary {
+        path: path.to_path_buf(),
+        format,
+        sample_rate,
+        channels: num_channels,
+        bits,
+        duration,
+        loudness: meter.integrated_loudness(),
+        sample_peak: to_db(meter.sample_peak()),
+        true_peak: to_db(meter.true_peak()),
+        key: detect_key(&[chroma_total]).map(|key| key.name()),
+        bpm: tempo_of_novelty(&novelty, sample_rate),
+    })
+}
+
+// `summarize` on a worker thread: errors become strings to cross threads,
+// and a panic on one malformed file fails only that file. Each worker keeps
+// one analyzer, so plans and windows carry over from file to file
+fn measure(path: &Path, analyzer: &mut SpectrumAnalyzer) -> Result<Summary, String> {
+    debug!("measuring {}", path.display());
+    match panic::catch_unwind(AssertUnwindSafe(|| summarize(path, analyzer))) {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(payload) => {
+            let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".into());
+            Err(format!("analysis panicked: {}", message))
+        }
+    }
+}
+
+fn write_db(path: &Path, summaries: &[Summary]) -> Result<(), Box<dyn Error>> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS results (
+            path TEXT PRIMARY KEY,
+            format TEXT NOT NULL,
+            sample_rate INTEGER NOT NULL,
+            channels INTEGER NOT NULL,
+            bits INTEGER NOT NULL,
+            duration REAL NOT NULL,
+            integrated_lufs REAL,
+            sample_peak_dbfs REAL,
+            true_peak_dbtp REAL,
+            key TEXT,
+            bpm REAL
+        )",
+    )?;
+
+    // silence has -inf peaks, which SQLite stores as NULL
+    let finite = |db: f32| db.is_finite().then_some(db as f64);
+
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT OR REPLACE INTO results VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )?;
+        for s in summaries {
+            insert.execute(params![
+                s.path.to_string_lossy(),
+                s.format,
+                s.sample_rate,
+                s.channels,
+                s.bits,
+                s.duration,
+                s.loudness.map(f64::from),
+                finite(s.sample_peak),
+                finite(s.true_peak),
+                s.key,
+                s.bpm.map(f64::from),
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Measure every input in parallel, print one line per file (in input
+/// order) and optionally record the results in SQLite. Files that fail to
+/// parse, or whose analysis panics, are reported and skipped, and the run
+/// then ends with an error so scripts see the failure.
+pub fn run(args: BatchArgs) -> Result<(), Box<dyn Error>> {
+    let mut paths = Vec::new();
+    for input in &args.inputs {
+        collect_wavs(input, &mut paths)?;
+    }
+    if paths.is_empty() {
+        return Err("no .wav files found".into());
+    }
+
+    let pool = ThreadPoolBuilder::new().num_threads(args.jobs.unwrap_or(0)).build()?;
+    let results: Vec<Result<Summary, String>> = pool.install(|| {
+        paths.par_iter().map_init(SpectrumAnalyzer::new, |analyzer, path| measure(path, analyzer)).collect()
+    });
+
+    println!("{:<32} {:>8} {:>9} {:>8} {:>8} {:>9} {:>6}", "File", "Duration", "LUFS", "Peak", "TP", "Key", "BPM");
+    let mut summaries = Vec::new();
+    let mut failed = 0;
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok(s) => {
+                let optional = |value: Option<f32>| value.map_or("-".to_string(), |v| format!("{:.1}", v));
+                println!(
+                    "{:<32} {:>7.2}s {:>9} {:>8.2} {:>8.2} {:>9} {:>6}",
+                    path.display(),
+                    s.duration,
+                    optional(s.loudness),
+                    s.sample_peak,
+                    s.true_peak,
+                    s.key.as_deref().unwrap_or("-"),
+                    optional(s.bpm),
+                );
+                summaries.push(s);
+            }
+            Err(e) => {
+                error!("{}: {}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+    println!("\n{} file(s) measured, {} failed", summaries.len(), failed);
+
+    if let Some(db) = &args.db {
+        write_db(db, &summaries)?;
+        info!("Wrote {} row(s) to {}", summaries.len(), db.display());
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} file(s) failed", failed, paths.len()).into());
+    }
+    Ok(())
+}
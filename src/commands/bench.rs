@@ -0,0 +1,150 @@
+use std::error::Error;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::generate::white_noise;
+use fft_rs::kernels::{amplitude_db, magnitudes, window_frame};
+use fft_rs::stft::{Spectrogram, StftConfig};
+use fft_rs::wav::WavFile;
+use fft_rs::window::Window;
+use fft_rs::writer::{write_wav, SampleFormat, WavSpec};
+use rustfft::{num_complex::Complex, FftPlanner};
+use tracing::info;
+
+use crate::plots::plot_spectrogram;
+
+const SAMPLE_RATE: u32 = 48000;
+const CHANNELS: u16 = 2;
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Seconds of synthetic stereo audio each stage works through
+    #[arg(long, default_value_t = 30.0)]
+    duration: f32,
+    /// FFT sizes to time
+    #[arg(long, value_delimiter = ',', default_value = "256,1024,4096,16384,65536")]
+    sizes: Vec<usize>,
+    /// Runs of each stage; the fastest counts
+    #[arg(long, default_value_t = 5)]
+    iterations: usize,
+}
+
+// one line of the report: `samples` processed and `bytes` touched per run
+struct Stage {
+    name: String,
+    time: Duration,
+    samples: usize,
+    bytes: usize,
+}
+
+/// Time each processing stage on generated noise and print its throughput.
+pub fn run(args: BenchArgs) -> Result<(), Box<dyn Error>> {
+    if args.duration <= 0.0 || args.iterations == 0 {
+        return Err("duration and iterations must be positive".into());
+    }
+    let frames = (args.duration * SAMPLE_RATE as f32) as usize;
+    let samples = white_noise(frames * CHANNELS as usize, 0.5, 0);
+    info!("Benchmarking on {:.1} s of {}-channel noise, best of {} runs", args.duration, CHANNELS, args.iterations);
+
+    let mut stages = Vec::new();
+
+    // decode: parse and convert an in-memory 24-bit file
+    let spec = WavSpec { channels: CHANNELS, sample_rate: SAMPLE_RATE, format: SampleFormat::I24 };
+    let mut bytes = Vec::new();
+    write_wav(&mut bytes, spec, &samples, Dither::Off)?;
+    let time = best_of(args.iterations, || {
+        let wav = WavFile::parse_bytes(&bytes)?;
+        black_box(wav.to_normalized_samples()?);
+        Ok(())
+    })?;
+    stages.push(Stage { name: "decode (24-bit WAV)".into(), time, samples: samples.len(), bytes: bytes.len() });
+
+    // windowing: multiply 4096-sample frames by a Hann window
+    let window = Window::Hann.coefficients(4096);
+    let mut frame = vec![Complex::default(); window.len()];
+    let time = best_of(args.iterations, || {
+        for chunk in samples.chunks_exact(window.len()) {
+            window_frame(chunk, &window, &mut frame);
+            black_box(&frame);
+        }
+        Ok(())
+    })?;
+    let windowed = samples.len() / window.len() * window.len();
+    stages.push(Stage { name: "window (Hann 4096)".into(), time, samples: windowed, bytes: windowed * 4 });
+
+    // magnitude and dB of a spectrogram's worth of bins
+    let bins: Vec<Complex<f32>> = samples.chunks_exact(2).map(|pair| Complex { re: pair[0], im: pair[1] }).collect();
+    let mut levels = vec![0.0f32; bins.len()];
+    let time = best_of(args.iterations, || {
+        magnitudes(&bins, &mut levels);
+        black_box(&levels);
+        Ok(())
+    })?;
+    stages.push(Stage { name: "magnitude".into(), time, samples: bins.len(), bytes: bins.len() * 8 });
+    let mut db = vec![0.0f32; levels.len()];
+    let time = best_of(args.iterations, || {
+        amplitude_db(&levels, 1e-9, &mut db);
+        black_box(&db);
+        Ok(())
+    })?;
+    stages.push(Stage { name: "dB".into(), time, samples: levels.len(), bytes: levels.len() * 4 });
+
+    // FFT: planned once, then run back to back over the signal
+    let mut planner = FftPlanner::<f32>::new();
+    for &size in &args.sizes {
+        if size < 2 || size > samples.len() {
+            return Err(format!("FFT size {} must lie between 2 and the signal length ({})", size, samples.len()).into());
+        }
+        let fft = planner.plan_fft_forward(size);
+        let mut buffer: Vec<Complex<f32>> = samples.iter().map(|&s| Complex { re: s, im: 0.0 }).collect();
+        buffer.truncate(samples.len() / size * size);
+        let mut scratch = vec![Complex::default(); fft.get_inplace_scratch_len()];
+        let time = best_of(args.iterations, || {
+            fft.process_with_scratch(&mut buffer, &mut scratch);
+            black_box(&buffer);
+            Ok(())
+        })?;
+        let points = buffer.len();
+        stages.push(Stage { name: format!("FFT {}", size), time, samples: points, bytes: points * 8 });
+    }
+
+    // rendering: spectrogram of the left channel, drawn to a temporary PNG
+    let left: Vec<f32> = samples.iter().step_by(CHANNELS as usize).copied().collect();
+    let spectrogram = Spectrogram::compute(&left, SAMPLE_RATE, StftConfig::with_overlap(2048, 0.75));
+    let path = std::env::temp_dir().join(format!("fft-rs-bench-{}.png", std::process::id()));
+    let path = path.to_str().ok_or("temporary directory is not valid UTF-8")?;
+    let result = best_of(args.iterations, || plot_spectrogram(&spectrogram, "Benchmark", &[], &[], path));
+    let _ = std::fs::remove_file(path);
+    let cells = spectrogram.frames.len() * spectrogram.num_bins();
+    stages.push(Stage { name: "render spectrogram".into(), time: result?, samples: left.len(), bytes: cells * 4 });
+
+    println!("{:<22} {:>10} {:>10} {:>10}", "Stage", "Time", "MS/s", "MiB/s");
+    for stage in &stages {
+        let secs = stage.time.as_secs_f64().max(1e-9);
+        println!(
+            "{:<22} {:>7.2} ms {:>10.1} {:>10.1}",
+            stage.name,
+            secs * 1000.0,
+            stage.samples as f64 / secs / 1e6,
+            stage.bytes as f64 / secs / (1 << 20) as f64,
+        );
+    }
+
+    Ok(())
+}
+
+// shortest wall-clock time of `iterations` runs of `f`
+fn best_of<F>(iterations: usize, mut f: F) -> Result<Duration, Box<dyn Error>>
+where
+    F: FnMut() -> Result<(), Box<dyn Error>>,
+{
+    let mut best = Duration::MAX;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        f()?;
+        best = best.min(start.elapsed());
+    }
+    Ok(best)
+}
@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use tracing::info;
+
+use super::gammatone::{frame_hop, FilterbankArgs};
+use super::load_mono;
+use crate::plots::plot_cochleagram;
+
+#[derive(Args)]
+pub struct CochleagramArgs {
+    /// Input WAV file
+    input: PathBuf,
+    #[command(flatten)]
+    filterbank: FilterbankArgs,
+    /// Envelope frames per second
+    #[arg(long, default_value_t = 100.0)]
+    frame_rate: f32,
+    /// Dynamic range shown, in dB below full scale
+    #[arg(long, default_value_t = 80.0)]
+    range: f32,
+    /// Where to write the cochleagram
+    #[arg(long, default_value = "cochleagram.png")]
+    plot: PathBuf,
+}
+
+/// Render the gammatone filterbank envelopes of the mono mix as a
+/// cochleagram: the auditory counterpart of the spectrogram.
+pub fn run(args: CochleagramArgs) -> Result<(), Box<dyn Error>> {
+    if args.range <= 0.0 {
+        return Err("range must be positive".into());
+    }
+    let (samples, rate) = load_mono(&args.input)?;
+    if samples.is_empty() {
+        return Err("input has no samples".into());
+    }
+    let bank = args.filterbank.bank(rate)?;
+    let hop = frame_hop(rate, args.frame_rate)?;
+    let envelopes = bank.envelopes(&samples, hop);
+
+    let duration = samples.len() as f32 / rate as f32;
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_cochleagram(&envelopes, &bank.centers, duration, -args.range, 0.0, "Cochleagram", path)?;
+    info!("Cochleagram of {} channels saved to '{}'", bank.centers.len(), path);
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::resample::resample;
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_encoded_file, write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::FadeArgs;
+
+#[derive(Args)]
+pub struct ConcatArgs {
+    /// Input WAV files followed by the output file
+    #[arg(required = true, num_args = 3.., value_name = "FILES")]
+    files: Vec<PathBuf>,
+    /// Resample / requantize inputs that don't match the first file's
+    /// rate and format instead of refusing them
+    #[arg(long)]
+    convert: bool,
+    #[command(flatten)]
+    fade: FadeArgs,
+}
+
+/// Join files end to end. Inputs that all share the first file's layout are
+/// copied byte for byte; otherwise they're converted (with --convert).
+pub fn run(args: ConcatArgs) -> Result<(), Box<dyn Error>> {
+    let (output, inputs) = args.files.split_last().unwrap();
+
+    let mut wavs = Vec::with_capacity(inputs.len());
+    for path in inputs {
+        let wav = WavFile::parse(&mut File::open(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        if wav.fmt.is_none() || wav.data.is_none() {
+            return Err(format!("{}: missing fmt or data chunk", path.display()).into());
+        }
+        wavs.push(wav);
+    }
+
+    let first = wavs[0].fmt.as_ref().unwrap();
+    let channels = first.num_channels.max(1);
+    let sample_rate = first.sample_rate;
+    let format = SampleFormat::matching(first);
+    let spec = WavSpec { channels, sample_rate, format };
+
+    let mut identical = format.bits() == first.bits_per_sample;
+    for (path, wav) in inputs.iter().zip(&wavs).skip(1) {
+        let fmt = wav.fmt.as_ref().unwrap();
+        if fmt.num_channels != first.num_channels {
+            return Err(format!(
+                "{}: {} channels, but {} has {}",
+                path.display(), fmt.num_channels, inputs[0].display(), first.num_channels
+            ).into());
+        }
+
+        let same = fmt.sample_rate == first.sample_rate
+            && fmt.format_code() == first.format_code()
+            && fmt.bits_per_sample == first.bits_per_sample;
+        if !same && !args.convert {
+            return Err(format!(
+                "{}: {} Hz / {}-bit doesn't match {} ({} Hz / {}-bit); pass --convert to convert it",
+                path.display(), fmt.sample_rate, fmt.bits_per_sample,
+                inputs[0].display(), first.sample_rate, first.bits_per_sample
+            ).into());
+        }
+        identical &= same;
+    }
+
+    if identical && !args.fade.is_active() {
+        // same layout throughout: splice the payloads, whole frames only
+        let block = first.block_align.max(1) as usize;
+        let mut payload = Vec::new();
+        for wav in &wavs {
+            let bytes = wav.data.as_ref().unwrap().bytes().unwrap_or(&[]);
+            payload.extend_from_slice(&bytes[..bytes.len() / block * block]);
+        }
+        write_encoded_file(output, spec, &payload)?;
+    } else {
+        let mut samples = Vec::new();
+        for wav in &wavs {
+            let fmt = wav.fmt.as_ref().unwrap();
+            let decoded = wav.to_normalized_samples()?;
+            samples.extend(resample(&decoded, channels as usize, fmt.sample_rate, sample_rate));
+        }
+        args.fade.apply(&mut samples, channels as usize, sample_rate);
+        write_wav_file(output, spec, &samples, Dither::Tpdf)?;
+    }
+
+    info!("Joined {} files into {}", inputs.len(), output.display());
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use fft_rs::dither::Dither;
+use fft_rs::resample::resample;
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::FadeArgs;
+
+#[derive(Args)]
+pub struct ConvertArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file
+    output: PathBuf,
+    /// Target sample rate in Hz (default: keep the input's)
+    #[arg(long)]
+    rate: Option<u32>,
+    /// Target bit depth: 8, 16, 24 or 32 (default: keep the input's)
+    #[arg(long)]
+    bits: Option<u16>,
+    /// Write 32-bit IEEE float samples
+    #[arg(long)]
+    float: bool,
+    /// Dither used when quantizing to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+    #[command(flatten)]
+    fade: FadeArgs,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DitherArg {
+    Off,
+    Tpdf,
+    Shaped,
+}
+
+impl From<DitherArg> for Dither {
+    fn from(arg: DitherArg) -> Self {
+        match arg {
+            DitherArg::Off => Dither::Off,
+            DitherArg::Tpdf => Dither::Tpdf,
+            DitherArg::Shaped => Dither::Shaped,
+        }
+    }
+}
+
+/// Decode, resample, requantize and write: the whole transcode in one pass.
+pub fn run(args: ConvertArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+
+    let samples = wav.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1);
+
+    let rate = args.rate.unwrap_or(fmt.sample_rate);
+    if rate == 0 {
+        return Err("sample rate must be positive".into());
+    }
+
+    let format = match (args.bits, args.float) {
+        (None, false) => SampleFormat::matching(fmt),
+        (bits, float) => SampleFormat::from_bits(bits.unwrap_or(32), float)
+            .ok_or("unsupported output format: use --bits 8/16/24/32, or --float for 32-bit float")?,
+    };
+
+    let mut resampled = resample(&samples, channels as usize, fmt.sample_rate, rate);
+
+    args.fade.apply(&mut resampled, channels as usize, rate);
+
+    let spec = WavSpec { channels, sample_rate: rate, format };
+    write_wav_file(&args.output, spec, &resampled, args.dither.into())?;
+
+    info!(
+        "Converted {} ({} Hz, {}-bit) -> {} ({} Hz, {}-bit{})",
+        args.input.display(), fmt.sample_rate, fmt.bits_per_sample,
+        args.output.display(), rate, format.bits(),
+        if format == SampleFormat::F32 { " float" } else { "" },
+    );
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::wav::LazyWavFile;
+use fft_rs::writer::{write_encoded_file, write_wav_file, SampleFormat, WavSpec};
+
+use super::{parse_time, FadeArgs};
+
+#[derive(Args)]
+pub struct CutArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file
+    output: PathBuf,
+    /// Region start, as seconds, m:ss.s or h:mm:ss.s (default: beginning)
+    #[arg(long, value_parser = parse_time)]
+    start: Option<f64>,
+    /// Region end, same formats as --start (default: end of file)
+    #[arg(long, value_parser = parse_time)]
+    end: Option<f64>,
+    #[command(flatten)]
+    fade: FadeArgs,
+}
+
+/// Copy a time range out of a file. Only the requested frames are read, and
+/// without fades the bytes are copied verbatim, so the cut is bit-exact.
+pub fn run(args: CutArgs) -> Result<(), Box<dyn Error>> {
+    let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+    let fmt = lazy.wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let total = lazy.wav.num_frames().ok_or("input has no data chunk")?;
+
+    // round to whole frames so the cut lands on a block boundary
+    let rate = fmt.sample_rate as f64;
+    let start = (args.start.unwrap_or(0.0) * rate).round() as u64;
+    let end = args.end.map_or(total, |t| ((t * rate).round() as u64).min(total));
+    if start >= end {
+        return Err(format!("empty region: start frame {} is not before end frame {}", start, end).into());
+    }
+
+    let channels = fmt.num_channels.max(1);
+    let sample_rate = fmt.sample_rate;
+    let format = SampleFormat::matching(fmt);
+    let verbatim = format.bits() == fmt.bits_per_sample && !args.fade.is_active();
+
+    let bytes = lazy.read_frames(start, end - start)?;
+    let spec = WavSpec { channels, sample_rate, format };
+
+    if verbatim {
+        write_encoded_file(&args.output, spec, &bytes)?;
+    } else {
+        // fades need decoded samples; 64-bit float input is also narrowed here
+        let fmt = lazy.wav.fmt.as_ref().unwrap();
+        let mut samples = fmt.decode(&bytes)?.to_f32();
+        args.fade.apply(&mut samples, channels as usize, sample_rate);
+        write_wav_file(&args.output, spec, &samples, Dither::Tpdf)?;
+    }
+
+    println!(
+        "Cut frames {}..{} ({:.3} s - {:.3} s) to {}",
+        start, end, start as f64 / rate, end as f64 / rate, args.output.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::transfer::deconvolve;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::load_mono;
+
+#[derive(Args)]
+pub struct DeconvolveArgs {
+    /// The sweep (or other stimulus) that was played
+    reference: PathBuf,
+    /// The recording of that stimulus
+    recording: PathBuf,
+    /// Output impulse response WAV (32-bit float)
+    output: PathBuf,
+    /// Regularization relative to the stimulus' peak power; raise it if the
+    /// response is noisy outside the swept range
+    #[arg(long, default_value_t = 1e-4)]
+    regularization: f32,
+    /// Keep only the first SECS seconds of the response
+    #[arg(long, value_name = "SECS")]
+    length: Option<f32>,
+}
+
+/// Recover the impulse response of whatever the stimulus was played through.
+pub fn run(args: DeconvolveArgs) -> Result<(), Box<dyn Error>> {
+    let (reference, rate) = load_mono(&args.reference)?;
+    let (recording, recording_rate) = load_mono(&args.recording)?;
+    if rate != recording_rate {
+        return Err(format!(
+            "sample rates differ: reference {} Hz, recording {} Hz", rate, recording_rate
+        ).into());
+    }
+    if args.regularization < 0.0 {
+        return Err("regularization must not be negative".into());
+    }
+
+    let mut ir = deconvolve(&reference, &recording, args.regularization);
+    if let Some(secs) = args.length {
+        ir.truncate((secs * rate as f32).round() as usize);
+    }
+
+    let spec = WavSpec { channels: 1, sample_rate: rate, format: SampleFormat::F32 };
+    write_wav_file(&args.output, spec, &ir, Dither::Off)?;
+
+    let peak = ir.iter().enumerate().fold((0, 0.0f32), |best, (i, &s)| if s.abs() > best.1 { (i, s.abs()) } else { best });
+    info!(
+        "Wrote {} ({} samples, peak {:.4} at {:.4} s)",
+        args.output.display(), ir.len(), peak.1, peak.0 as f32 / rate as f32
+    );
+
+    Ok(())
+}
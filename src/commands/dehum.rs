@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::hum::{detect_mains, hum_candidates, remove_hum};
+use fft_rs::sample::mix_to_mono;
+use fft_rs::spectrum::compute_spectrum;
+use fft_rs::stft::{Averaging, Spectrogram, StftConfig};
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use plotters::style::RGBColor;
+use tracing::info;
+
+use super::convert::DitherArg;
+use crate::plots::{plot_lines, Series};
+
+// window for the before/after spectra; fine enough to resolve 50 vs 60 Hz
+const SPECTRUM_NFFT: usize = 8192;
+
+#[derive(Args)]
+pub struct DehumArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file
+    output: PathBuf,
+    /// Mains frequency in Hz; detected from the input when omitted
+    #[arg(long)]
+    freq: Option<f32>,
+    /// Number of harmonics to notch, counting the fundamental
+    #[arg(long, default_value_t = 5)]
+    harmonics: usize,
+    /// Notch quality factor; higher is narrower
+    #[arg(long, default_value_t = 30.0)]
+    q: f32,
+    /// Dither used when quantizing back to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+    /// Where to write the before/after spectra
+    #[arg(long, default_value = "dehum.png")]
+    plot: PathBuf,
+}
+
+/// Notch out mains hum, write the cleaned copy and plot both spectra.
+pub fn run(args: DehumArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1) as usize;
+    let rate = fmt.sample_rate;
+
+    if args.harmonics == 0 {
+        return Err("need at least one harmonic".into());
+    }
+    if args.q <= 0.0 {
+        return Err("q must be positive".into());
+    }
+
+    let before = mix_to_mono(&samples, channels);
+    let mains = match args.freq {
+        Some(freq) if freq > 0.0 && freq < rate as f32 / 2.0 => freq,
+        Some(_) => return Err(format!("mains frequency must be between 0 and {} Hz", rate / 2).into()),
+        None => {
+            let candidates = hum_candidates(&compute_spectrum(&before, rate), before.len(), args.harmonics);
+            let mains = detect_mains(&candidates).ok_or("no mains hum detected; pass --freq to notch anyway")?;
+            println!("Detected {} Hz mains hum", mains);
+            mains
+        }
+    };
+
+    let mut cleaned = samples.clone();
+    remove_hum(&mut cleaned, channels, rate, mains, args.harmonics, args.q);
+
+    let spec = WavSpec { channels: channels as u16, sample_rate: rate, format: SampleFormat::matching(fmt) };
+    write_wav_file(&args.output, spec, &cleaned, args.dither.into())?;
+    info!("Notched {} Hz x{} (Q {}), wrote {}", mains, args.harmonics, args.q, args.output.display());
+
+    let config = StftConfig::with_overlap(SPECTRUM_NFFT, 0.5);
+    config.validate(before.len())?;
+    let spectrum_db = |mono: &[f32]| -> Vec<(f32, f32)> {
+        let spectrum = Spectrogram::compute(mono, rate, config).average(Averaging::Linear);
+        spectrum.frequencies.iter().zip(&spectrum.magnitudes)
+            .skip(1)
+            .map(|(&f, &m)| (f, 20.0 * (m + 1e-9).log10()))
+            .collect()
+    };
+    let before_db = spectrum_db(&before);
+    let after_db = spectrum_db(&mix_to_mono(&cleaned, channels));
+
+    let series = [
+        Series { label: "Before", points: &before_db, color: RGBColor(200, 0, 80) },
+        Series { label: "After", points: &after_db, color: RGBColor(0, 90, 200) },
+    ];
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("Hum Removal", "Frequency (Hz)", "Magnitude (dB)", &series, true, path)?;
+    info!("Spectra saved to '{}'", path);
+
+    Ok(())
+}
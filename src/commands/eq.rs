@@ -0,0 +1,103 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::bands::{band_levels_dbfs, Band, BandFraction};
+use fft_rs::eq::{pink_reference, suggest_eq};
+use fft_rs::spectrum::compute_spectrum;
+use plotters::style::RGBColor;
+use tracing::info;
+
+use super::{load_mono, parse_db, BandsArg};
+use crate::plots::{plot_lines, Series};
+
+#[derive(Args)]
+pub struct EqArgs {
+    /// Input WAV file (a mix or track)
+    input: PathBuf,
+    /// Balance to aim for: "pink" (equal power per band, the usual
+    /// mix-down slope of -3 dB/octave) or a reference WAV file
+    #[arg(long, default_value = "pink", value_parser = parse_reference)]
+    reference: Reference,
+    /// Band resolution of the suggestion
+    #[arg(long, value_enum, default_value_t = BandsArg::Third)]
+    bands: BandsArg,
+    /// Largest boost or cut suggested, e.g. 6dB
+    #[arg(long, value_name = "DB", default_value = "6dB", value_parser = parse_db)]
+    max_gain: f32,
+    /// Where to write the track, reference and suggested EQ curves
+    #[arg(long, default_value = "eq.png")]
+    plot: PathBuf,
+}
+
+#[derive(Clone)]
+enum Reference {
+    Pink,
+    File(PathBuf),
+}
+
+fn parse_reference(s: &str) -> Result<Reference, String> {
+    match s {
+        "pink" => Ok(Reference::Pink),
+        _ => Ok(Reference::File(s.into())),
+    }
+}
+
+// band levels of the whole-file spectrum, which by Parseval is the
+// long-term average
+fn long_term_bands(samples: &[f32], rate: u32, fraction: BandFraction) -> Vec<Band> {
+    band_levels_dbfs(&compute_spectrum(samples, rate), samples.len(), fraction)
+}
+
+/// Compare the long-term band balance of the mono mix with a reference
+/// and suggest the band gains that would match it.
+pub fn run(args: EqArgs) -> Result<(), Box<dyn Error>> {
+    if args.max_gain <= 0.0 {
+        return Err("max gain must be positive".into());
+    }
+    let (samples, rate) = load_mono(&args.input)?;
+    let fraction = BandFraction::from(args.bands);
+    let track = long_term_bands(&samples, rate, fraction);
+
+    let (reference, label) = match &args.reference {
+        Reference::Pink => (pink_reference(&track), "Pink noise".to_string()),
+        Reference::File(path) => {
+            let (reference, reference_rate) = load_mono(path)?;
+            let bands = long_term_bands(&reference, reference_rate, fraction);
+            // match bands by label; ones past the reference's Nyquist are left out
+            let levels = track.iter()
+                .map(|band| bands.iter().find(|b| b.nominal == band.nominal).map_or(f32::NAN, |b| b.level_db))
+                .collect();
+            let name = path.file_name().map_or(path.display().to_string(), |n| n.to_string_lossy().into_owned());
+            (levels, name)
+        }
+    };
+    let suggestion = suggest_eq(&track, &reference, args.max_gain);
+
+    println!("Suggested EQ toward {}:", label);
+    println!("{:>8} {:>10} {:>10} {:>10}", "Band Hz", "Track dB", "Target dB", "Gain dB");
+    for band in &suggestion {
+        match band.gain_db {
+            Some(gain) => println!("{:>8} {:>10.1} {:>10.1} {:>+10.1}", band.nominal, band.track_db, band.reference_db, gain),
+            None => println!("{:>8} {:>10} {:>10} {:>10}", band.nominal, "-", "-", "-"),
+        }
+    }
+
+    let used = || suggestion.iter().filter(|b| b.gain_db.is_some());
+    let track_curve: Vec<(f32, f32)> = used().map(|b| (b.center, b.track_db)).collect();
+    let reference_curve: Vec<(f32, f32)> = used().map(|b| (b.center, b.reference_db)).collect();
+    let gain_curve: Vec<(f32, f32)> = used().filter_map(|b| Some((b.center, b.gain_db?))).collect();
+    if gain_curve.is_empty() {
+        return Err("no band has enough content to compare".into());
+    }
+    let series = [
+        Series { label: "Track", points: &track_curve, color: RGBColor(0, 90, 200) },
+        Series { label: &label, points: &reference_curve, color: RGBColor(120, 120, 120) },
+        Series { label: "Suggested EQ", points: &gain_curve, color: RGBColor(200, 0, 80) },
+    ];
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("EQ Suggestion", "Frequency (Hz)", "Level re mean / gain (dB)", &series, true, path)?;
+    info!("EQ plot saved to '{}'", path);
+
+    Ok(())
+}
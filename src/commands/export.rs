@@ -0,0 +1,348 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
+use fft_rs::budget::{format_bytes, MemoryBudget};
+use fft_rs::export::{write_npy_file, write_npy_member_file, write_npz_file, write_npz_members_file, Member, NamedArray};
+use fft_rs::mat::write_mat_file;
+use fft_rs::sample::mix_to_mono;
+use fft_rs::spill::SpillWriter;
+use fft_rs::stft::{Averaging, Spectrogram, StftStream};
+use fft_rs::structure::{chroma, chroma_vector, mfcc, mfcc_vector};
+use fft_rs::wav::{LazyWavFile, WavFile};
+use tracing::{debug, info, warn};
+
+use super::{load_mono, BackendArg, StftArgs};
+
+const MFCC_COUNT: usize = 13;
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output file; the extension picks the format: .npz (every array),
+    /// .npy (the one chosen with --array), .mat (every array plus
+    /// metadata variables, MATLAB level 5) or .h5 (metadata, spectrum,
+    /// stft and features groups; needs the hdf5 feature) or .parquet (one
+    /// row per frame of times, chroma and MFCCs; needs the parquet feature)
+    output: PathBuf,
+    /// Write each of these formats to the output path with its extension
+    /// (e.g. --format npz,mat), instead of going by the output's extension
+    #[arg(long, value_enum, value_delimiter = ',')]
+    format: Vec<FormatArg>,
+    /// Array written to a .npy file
+    #[arg(long, value_enum, default_value_t = ArrayArg::Spectrogram)]
+    array: ArrayArg,
+    #[command(flatten)]
+    stft: StftArgs,
+    /// Memory budget, e.g. 2G. An input whose analysis would need more is
+    /// decoded in blocks, with the spectrogram spilled to a temporary file
+    /// (.npz, .npy and .parquet only)
+    #[arg(long, value_name = "SIZE")]
+    max_mem: Option<MemoryBudget>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ArrayArg {
+    Spectrogram,
+    Spectrum,
+    Frequencies,
+    Times,
+    Chroma,
+    Mfcc,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FormatArg {
+    Npz,
+    Npy,
+    Mat,
+    H5,
+    Parquet,
+}
+
+impl FormatArg {
+    fn extension(self) -> &'static str {
+        match self {
+            FormatArg::Npz => "npz",
+            FormatArg::Npy => "npy",
+            FormatArg::Mat => "mat",
+            FormatArg::H5 => "h5",
+            FormatArg::Parquet => "parquet",
+        }
+    }
+}
+
+impl ArrayArg {
+    fn name(self) -> &'static str {
+        match self {
+            ArrayArg::Spectrogram => "spectrogram",
+            ArrayArg::Spectrum => "spectrum",
+            ArrayArg::Frequencies => "frequencies",
+            ArrayArg::Times => "times",
+            ArrayArg::Chroma => "chroma",
+            ArrayArg::Mfcc => "mfcc",
+        }
+    }
+}
+
+/// The analysis matrices of a mono signal: STFT magnitudes (frames x bins)
+/// and their mean over frames, with the bin frequencies and frame center
+/// times, and per-frame chroma and MFCCs.
+fn arrays(spectrogram: &Spectrogram) -> Vec<NamedArray> {
+    let center = spectrogram.config.nfft as f32 / 2.0 / spectrogram.sample_rate as f32;
+    vec![
+        NamedArray::matrix("spectrogram", &spectrogram.frames),
+        NamedArray::vector("spectrum", spectrogram.average(Averaging::Linear).magnitudes),
+        NamedArray::vector("frequencies", (0..spectrogram.num_bins()).map(|k| spectrogram.bin_frequency(k)).collect()),
+        NamedArray::vector("times", (0..spectrogram.frames.len()).map(|t| spectrogram.frame_time(t) + center).collect()),
+        NamedArray::matrix("chroma", &chroma(spectrogram)),
+        NamedArray::matrix("mfcc", &mfcc(spectrogram, MFCC_COUNT)),
+    ]
+}
+
+/// Analyze the mono mix and write its matrices for other tools.
+pub fn run(args: ExportArgs) -> Result<(), Box<dyn Error>> {
+    let targets = targets(&args)?;
+    if let Some(budget) = args.max_mem {
+        let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+        let needed = in_memory_bytes(&lazy.wav, &args.stft)?;
+        if !budget.fits(needed) {
+            return run_spilled(&args, &mut lazy, budget, needed, &targets);
+        }
+        debug!("Analysis needs about {}, within the {} budget", format_bytes(needed), budget);
+    }
+
+    let (samples, rate) = load_mono(&args.input)?;
+    let config = args.stft.config(samples.len())?;
+    let spectrogram = match args.stft.multi_res() {
+        Some(sizes) => Spectrogram::multi_resolution(&samples, rate, sizes, config.hop),
+        None => args.stft.spectrogram(&samples, rate, config),
+    };
+    let arrays = arrays(&spectrogram);
+
+    for (format, path) in targets {
+        let written = write(&args, format, &path, &spectrogram, &arrays)?;
+        info!("Wrote {}: {}", path.display(), describe(written.iter().map(|a| (a.name.as_str(), a.shape.clone()))));
+    }
+
+    Ok(())
+}
+
+// either every --format next to the output path, or the one its extension names
+fn targets(args: &ExportArgs) -> Result<Vec<(FormatArg, PathBuf)>, Box<dyn Error>> {
+    if !args.format.is_empty() {
+        return Ok(args.format.iter().map(|&format| (format, args.output.with_extension(format.extension()))).collect());
+    }
+    let extension = args.output.extension().and_then(|ext| ext.to_str()).unwrap_or("").to_ascii_lowercase();
+    let format = match extension.as_str() {
+        "npz" => FormatArg::Npz,
+        "npy" => FormatArg::Npy,
+        "mat" => FormatArg::Mat,
+        "h5" | "hdf5" => FormatArg::H5,
+        "parquet" => FormatArg::Parquet,
+        _ => return Err(format!("unknown export format '{}' (use .npz, .npy, .mat, .h5 or .parquet)", extension).into()),
+    };
+    Ok(vec![(format, args.output.clone())])
+}
+
+// "spectrogram 431x513, spectrum 513, ..."
+fn describe<'a>(arrays: impl Iterator<Item = (&'a str, Vec<usize>)>) -> String {
+    let contents: Vec<String> = arrays.map(|(name, shape)| {
+        let shape: Vec<String> = shape.iter().map(|d| d.to_string()).collect();
+        format!("{} {}", name, shape.join("x"))
+    }).collect();
+    contents.join(", ")
+}
+
+// Rough peak memory of the in-memory export: the raw data chunk, the
+// decoded samples and their mono mix, and three copies of the magnitudes
+// (the frames, the flattened array and its encoding)
+fn in_memory_bytes(wav: &WavFile, stft: &StftArgs) -> Result<u64, Box<dyn Error>> {
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let frames = wav.num_frames().unwrap_or(0);
+    let config = stft.config(frames as usize)?;
+    let nfft = stft.multi_res().and_then(|sizes| sizes.iter().max().copied()).unwrap_or(config.nfft);
+    let cells = config.num_frames(frames as usize) as u64 * (nfft / 2 + 1) as u64;
+    let channels = fmt.num_channels.max(1) as u64;
+    Ok(frames * (fmt.block_align as u64 + channels * 4 + 4) + 3 * cells * 4)
+}
+
+// Over budget: decode a block at a time, spill the spectrogram rows to a
+// temporary file and keep only the per-frame features (a few dozen values
+// each) in memory. Only the formats that can copy the spill piecewise work.
+fn run_spilled<R: Read + Seek>(
+    args: &ExportArgs,
+    lazy: &mut LazyWavFile<R>,
+    budget: MemoryBudget,
+    needed: u64,
+    targets: &[(FormatArg, PathBuf)],
+) -> Result<(), Box<dyn Error>> {
+    if args.stft.multi_res().is_some() {
+        return Err("--multi-res needs the whole signal in memory; raise --max-mem or drop it".into());
+    }
+    if let Some((format, _)) = targets.iter().find(|(format, _)| matches!(format, FormatArg::Mat | FormatArg::H5)) {
+        return Err(format!(
+            ".{} export needs the whole spectrogram in memory; write .npz, .npy or .parquet, or raise --max-mem",
+            format.extension()
+        ).into());
+    }
+    info!("Analysis needs about {} in memory, over the {} budget; streaming it", format_bytes(needed), budget);
+    if args.stft.backend == BackendArg::Gpu {
+        warn!("the GPU backend transforms the whole signal at once; streaming on the CPU instead");
+    }
+
+    let fmt = lazy.wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let (rate, channels) = (fmt.sample_rate, fmt.num_channels.max(1) as usize);
+    let block_frames = budget.block_frames(fmt.block_align as u64 + channels as u64 * 4 + 4);
+    let config = args.stft.config(lazy.wav.num_frames().unwrap_or(0) as usize)?;
+    let bins = config.nfft / 2 + 1;
+    debug!("Decoding {} frames per block", block_frames);
+
+    let mut stream = StftStream::new(config, args.stft.window());
+    let mut spill = SpillWriter::create(bins)?;
+    let mut written = Ok(());
+    let mut sums = vec![0.0f32; bins];
+    let (mut chroma, mut mfcc) = (Vec::new(), Vec::new());
+    for block in lazy.blocks(block_frames) {
+        stream.push(&mix_to_mono(&block?, channels), |frame| {
+            if written.is_ok() {
+                written = spill.push_row(frame);
+            }
+            for (sum, &m) in sums.iter_mut().zip(frame) {
+                *sum += m;
+            }
+            chroma.push(chroma_vector(frame, rate, config.nfft));
+            mfcc.push(mfcc_vector(frame, rate, config.nfft, MFCC_COUNT));
+        });
+        if written.is_err() {
+            break;
+        }
+    }
+    written?;
+    let spectrogram = spill.finish()?;
+    debug!("Spilled {} of magnitudes", format_bytes(spectrogram.byte_len()));
+
+    // the same arrays, in the same order, as `arrays` builds in memory
+    let count = spectrogram.rows().max(1) as f32;
+    let center = config.nfft as f32 / 2.0 / rate as f32;
+    let arrays = [
+        NamedArray::vector("spectrum", sums.iter().map(|sum| sum / count).collect()),
+        NamedArray::vector("frequencies", (0..bins).map(|k| k as f32 * rate as f32 / config.nfft as f32).collect()),
+        NamedArray::vector("times", (0..spectrogram.rows()).map(|t| (t * config.hop) as f32 / rate as f32 + center).collect()),
+        NamedArray::matrix("chroma", &chroma),
+        NamedArray::matrix("mfcc", &mfcc),
+    ];
+    let mut members = vec![Member::Spilled("spectrogram", &spectrogram)];
+    members.extend(arrays.iter().map(Member::Array));
+
+    for (format, path) in targets {
+        let written: Vec<&Member> = match format {
+            FormatArg::Npz => {
+                write_npz_members_file(path, &members)?;
+                members.iter().collect()
+            }
+            FormatArg::Npy => {
+                let member = members.iter().find(|m| m.name() == args.array.name()).expect("every array is built");
+                write_npy_member_file(path, member)?;
+                vec![member]
+            }
+            FormatArg::Parquet => {
+                let table: Vec<NamedArray> = arrays.iter()
+                    .filter(|a| ["times", "chroma", "mfcc"].contains(&a.name.as_str()))
+                    .cloned()
+                    .collect();
+                write_parquet(path, &table)?;
+                members.iter().filter(|m| ["times", "chroma", "mfcc"].contains(&m.name())).collect()
+            }
+            FormatArg::Mat | FormatArg::H5 => unreachable!("rejected before streaming"),
+        };
+        info!("Wrote {}: {}", path.display(), describe(written.iter().map(|m| (m.name(), m.shape()))));
+    }
+
+    Ok(())
+}
+
+/// Write one format to `path`, returning the arrays it holds.
+fn write<'a>(
+    args: &ExportArgs,
+    format: FormatArg,
+    path: &Path,
+    spectrogram: &Spectrogram,
+    arrays: &'a [NamedArray],
+) -> Result<Vec<&'a NamedArray>, Box<dyn Error>> {
+    let written = match format {
+        FormatArg::Npz => {
+            write_npz_file(path, arrays)?;
+            arrays.iter().collect()
+        }
+        FormatArg::Npy => {
+            let array = arrays.iter().find(|a| a.name == args.array.name()).expect("every array is built");
+            write_npy_file(path, array)?;
+            vec![array]
+        }
+        FormatArg::Mat => {
+            let metadata = [
+                NamedArray::vector("sample_rate", vec![spectrogram.sample_rate as f32]),
+                NamedArray::vector("nfft", vec![spectrogram.config.nfft as f32]),
+                NamedArray::vector("hop", vec![spectrogram.config.hop as f32]),
+            ];
+            let file = args.input.display().to_string();
+            let variables: Vec<NamedArray> = arrays.iter().chain(&metadata).cloned().collect();
+            write_mat_file(path, &variables, &[("file", &file)])?;
+            arrays.iter().collect()
+        }
+        FormatArg::H5 => {
+            write_h5(args, path, spectrogram, arrays)?;
+            arrays.iter().collect()
+        }
+        FormatArg::Parquet => {
+            let features: Vec<&NamedArray> = arrays.iter()
+                .filter(|a| ["times", "chroma", "mfcc"].contains(&a.name.as_str()))
+                .collect();
+            let table: Vec<NamedArray> = features.iter().map(|&a| a.clone()).collect();
+            write_parquet(path, &table)?;
+            features
+        }
+    };
+    Ok(written)
+}
+
+#[cfg(feature = "hdf5")]
+fn write_h5(args: &ExportArgs, path: &Path, spectrogram: &Spectrogram, arrays: &[NamedArray]) -> Result<(), Box<dyn Error>> {
+    let pick = |names: &[&str]| -> Vec<NamedArray> {
+        arrays.iter().filter(|a| names.contains(&a.name.as_str())).cloned().collect()
+    };
+    let numbers = [
+        ("sample_rate", spectrogram.sample_rate as f64),
+        ("nfft", spectrogram.config.nfft as f64),
+        ("hop", spectrogram.config.hop as f64),
+        ("duration", spectrogram.duration() as f64),
+    ];
+    let file = args.input.display().to_string();
+    let groups = [
+        ("spectrum", pick(&["spectrum", "frequencies"])),
+        ("stft", pick(&["spectrogram", "frequencies", "times"])),
+        ("features", pick(&["chroma", "mfcc", "times"])),
+    ];
+    fft_rs::h5::write_h5_file(path, &numbers, &[("file", &file)], &groups)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "hdf5"))]
+fn write_h5(_: &ExportArgs, _: &Path, _: &Spectrogram, _: &[NamedArray]) -> Result<(), Box<dyn Error>> {
+    Err("HDF5 export needs a build with the hdf5 feature (cargo build --features hdf5)".into())
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(path: &Path, features: &[NamedArray]) -> Result<(), Box<dyn Error>> {
+    fft_rs::pq::write_parquet_file(path, features)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_: &Path, _: &[NamedArray]) -> Result<(), Box<dyn Error>> {
+    Err("Parquet export needs a build with the parquet feature (cargo build --features parquet)".into())
+}
@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::flutter::{measure_flutter, TEST_TONE_HZ};
+use plotters::style::RGBColor;
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::{plot_lines, Series};
+
+// the deviation plot keeps at most this many points
+const PLOT_POINTS: usize = 4000;
+
+#[derive(Args)]
+pub struct FlutterArgs {
+    /// Recording of a steady test tone
+    input: PathBuf,
+    /// Nominal test tone frequency in Hz
+    #[arg(long, default_value_t = TEST_TONE_HZ)]
+    freq: f32,
+}
+
+/// Report wow and flutter and plot the speed deviation over time.
+pub fn run(args: FlutterArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    if !(args.freq > 0.0 && args.freq < rate as f32 / 2.0) {
+        return Err(format!("tone frequency must be between 0 and {} Hz", rate / 2).into());
+    }
+    let flutter = measure_flutter(&samples, rate, args.freq)
+        .ok_or("no steady test tone found (need at least a second of it)")?;
+
+    let offset = (flutter.mean_frequency / args.freq - 1.0) * 100.0;
+    println!("Mean frequency: {:.2} Hz ({:+.3}% speed)", flutter.mean_frequency, offset);
+    println!("Wow & flutter, unweighted RMS: {:.4}%", flutter.unweighted_rms);
+    println!("Wow & flutter, weighted RMS: {:.4}%", flutter.weighted_rms);
+    println!("Wow & flutter, weighted peak (2-sigma): {:.4}%", flutter.weighted_peak);
+
+    let step = (flutter.deviation.len() / PLOT_POINTS).max(1);
+    let points: Vec<(f32, f32)> = flutter.deviation.iter().step_by(step).copied().collect();
+    let series = [Series { label: "Deviation", points: &points, color: RGBColor(200, 0, 80) }];
+    plot_lines("Speed Deviation", "Time (s)", "Deviation (%)", &series, false, "flutter.png")?;
+    info!("Deviation plot saved to 'flutter.png'");
+
+    Ok(())
+}
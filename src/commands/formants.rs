@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::lpc::{formants, lpc};
+use fft_rs::resample::resample;
+use plotters::style::RGBColor;
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::{plot_lines, Series};
+
+// formants live below 5 kHz, so the analysis runs at 10 kHz where a low
+// order covers them
+const ANALYSIS_RATE: u32 = 10_000;
+const FRAME_SECS: f32 = 0.025;
+const HOP_SECS: f32 = 0.01;
+
+// frames quieter than this (dBFS RMS) are skipped
+const FLOOR_DB: f32 = -50.0;
+
+const TRACKED: usize = 3;
+const COLORS: [RGBColor; TRACKED] = [RGBColor(200, 0, 80), RGBColor(0, 90, 200), RGBColor(0, 150, 60)];
+
+#[derive(Args)]
+pub struct FormantsArgs {
+    /// Input WAV file (speech or singing)
+    input: PathBuf,
+    /// LPC order; 2 + kHz of the 10 kHz analysis rate is the usual choice
+    #[arg(long, default_value_t = 12)]
+    order: usize,
+    /// Where to write the formant track plot
+    #[arg(long, default_value = "formants.png")]
+    plot: PathBuf,
+}
+
+/// Track F1-F3 frame by frame and report their means.
+pub fn run(args: FormantsArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    if args.order < 2 {
+        return Err("LPC order must be at least 2".into());
+    }
+    let samples = resample(&samples, 1, rate, ANALYSIS_RATE);
+    let frame = (ANALYSIS_RATE as f32 * FRAME_SECS) as usize;
+    let hop = (ANALYSIS_RATE as f32 * HOP_SECS) as usize;
+    if samples.len() < frame || args.order >= frame {
+        return Err("input is too short for formant analysis".into());
+    }
+
+    let mut tracks: Vec<Vec<(f32, f32)>> = vec![Vec::new(); TRACKED];
+    for start in (0..=samples.len() - frame).step_by(hop) {
+        let block = &samples[start..start + frame];
+        let mean_square = block.iter().map(|s| s * s).sum::<f32>() / frame as f32;
+        if 10.0 * mean_square.max(1e-12).log10() < FLOOR_DB {
+            continue;
+        }
+        let Some(model) = lpc(block, args.order) else { continue };
+        let time = (start + frame / 2) as f32 / ANALYSIS_RATE as f32;
+        for (track, formant) in tracks.iter_mut().zip(formants(&model, ANALYSIS_RATE)) {
+            track.push((time, formant.frequency));
+        }
+    }
+    if tracks[0].is_empty() {
+        return Err("no frames loud enough to analyze".into());
+    }
+
+    let labels: Vec<String> = (1..=TRACKED).map(|n| format!("F{}", n)).collect();
+    println!("Mean formants over {} frames:", tracks[0].len());
+    for (label, track) in labels.iter().zip(&tracks) {
+        if !track.is_empty() {
+            let mean = track.iter().map(|p| p.1).sum::<f32>() / track.len() as f32;
+            println!("  {}: {:7.0} Hz", label, mean);
+        }
+    }
+
+    let series: Vec<Series> = labels.iter().zip(&tracks).zip(COLORS)
+        .filter(|((_, track), _)| !track.is_empty())
+        .map(|((label, track), color)| Series { label, points: track, color })
+        .collect();
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("Formant Tracks", "Time (s)", "Frequency (Hz)", &series, false, path)?;
+    info!("Formant plot saved to '{}'", path);
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::gammatone::GammatoneBank;
+use tracing::info;
+
+use super::load_mono;
+
+// highest center frequency unless --high says otherwise
+const DEFAULT_HIGH: f32 = 8000.0;
+
+#[derive(Args)]
+pub struct GammatoneArgs {
+    /// Input WAV file
+    input: PathBuf,
+    #[command(flatten)]
+    filterbank: FilterbankArgs,
+    /// Envelope frames per second
+    #[arg(long, default_value_t = 100.0)]
+    frame_rate: f32,
+    /// Where to write the envelopes as CSV (time, then one column per
+    /// channel, headed by its center frequency)
+    #[arg(short, long, default_value = "gammatone.csv")]
+    output: PathBuf,
+}
+
+/// Gammatone filterbank options shared by the auditory analyses.
+#[derive(Args)]
+pub struct FilterbankArgs {
+    /// Number of filters, spaced evenly on the ERB-rate scale
+    #[arg(long, default_value_t = 64)]
+    channels: usize,
+    /// Center frequency of the lowest filter in Hz
+    #[arg(long, default_value_t = 50.0)]
+    low: f32,
+    /// Center frequency of the highest filter in Hz [default: 8000, or
+    /// 90% of Nyquist for lower sample rates]
+    #[arg(long)]
+    high: Option<f32>,
+}
+
+impl FilterbankArgs {
+    /// The filterbank for a signal at `rate`.
+    pub fn bank(&self, rate: u32) -> Result<GammatoneBank, String> {
+        let high = self.high.unwrap_or(DEFAULT_HIGH.min(0.45 * rate as f32));
+        GammatoneBank::new(rate, self.low, high, self.channels)
+    }
+}
+
+/// Samples per envelope frame for `frame_rate` frames per second.
+pub fn frame_hop(rate: u32, frame_rate: f32) -> Result<usize, String> {
+    if !(frame_rate > 0.0 && frame_rate <= rate as f32) {
+        return Err(format!("frame rate must be between 0 and {} per second", rate));
+    }
+    Ok((rate as f32 / frame_rate).round() as usize)
+}
+
+/// Run the mono mix through the gammatone filterbank and export every
+/// channel's envelope.
+pub fn run(args: GammatoneArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let bank = args.filterbank.bank(rate)?;
+    let hop = frame_hop(rate, args.frame_rate)?;
+    let envelopes = bank.envelopes(&samples, hop);
+
+    println!("{:>9} {:>11}", "Center Hz", "Mean dBFS");
+    for (center, envelope) in bank.centers.iter().zip(&envelopes) {
+        let mean = envelope.iter().sum::<f32>() / envelope.len().max(1) as f32;
+        println!("{:>9.1} {:>11.1}", center, 20.0 * (mean + 1e-12).log10());
+    }
+
+    // each frame is stamped with the middle of the samples it averages
+    let mut out = BufWriter::new(File::create(&args.output)?);
+    write!(out, "time")?;
+    for center in &bank.centers {
+        write!(out, ",{:.1}", center)?;
+    }
+    writeln!(out)?;
+    for t in 0..envelopes.first().map_or(0, |e| e.len()) {
+        let middle = (t * hop) as f32 + hop.min(samples.len() - t * hop) as f32 / 2.0;
+        write!(out, "{:.6}", middle / rate as f32)?;
+        for envelope in &envelopes {
+            write!(out, ",{:e}", envelope[t])?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()?;
+    info!("{} channel envelopes written to '{}'", bank.centers.len(), args.output.display());
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::gate::{spectral_gate, GateThreshold};
+use fft_rs::stft::StftConfig;
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::convert::DitherArg;
+use super::parse_db;
+
+#[derive(Args)]
+pub struct GateArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file
+    output: PathBuf,
+    /// Let bins through above this level, e.g. -60dBFS
+    #[arg(long, allow_hyphen_values = true, value_parser = parse_db,
+          conflicts_with = "above_noise", required_unless_present = "above_noise")]
+    threshold: Option<f32>,
+    /// Let bins through this many dB above their own noise estimate
+    #[arg(long, value_name = "DB", value_parser = parse_db)]
+    above_noise: Option<f32>,
+    /// STFT window size
+    #[arg(long, default_value_t = 2048)]
+    nfft: usize,
+    /// Dither used when quantizing back to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+}
+
+/// Gate every channel in the STFT domain and write the result.
+pub fn run(args: GateArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1) as usize;
+
+    let threshold = match (args.threshold, args.above_noise) {
+        (Some(db), _) => GateThreshold::Absolute(db),
+        (None, Some(db)) => GateThreshold::AboveNoise(db),
+        (None, None) => unreachable!("clap requires --threshold or --above-noise"),
+    };
+    let config = StftConfig::for_resynthesis(args.nfft);
+    config.validate(samples.len() / channels)?;
+
+    let mut gated = vec![0.0f32; samples.len()];
+    for ch in 0..channels {
+        let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+        let processed = spectral_gate(&channel, config, threshold);
+        for (out, s) in gated.iter_mut().skip(ch).step_by(channels).zip(processed) {
+            *out = s;
+        }
+    }
+
+    let spec = WavSpec { channels: channels as u16, sample_rate: fmt.sample_rate, format: SampleFormat::matching(fmt) };
+    write_wav_file(&args.output, spec, &gated, args.dither.into())?;
+    info!("Gated {} channel(s), wrote {}", channels, args.output.display());
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use fft_rs::generate::{impulse, log_sweep, pink_noise, sine, white_noise};
+use fft_rs::meter::db_to_gain;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::convert::DitherArg;
+use super::{parse_time, FadeArgs};
+
+#[derive(Args)]
+pub struct GenArgs {
+    /// Output WAV file
+    output: PathBuf,
+    /// Signal to generate
+    #[arg(long, value_enum, default_value_t = SignalArg::Sine)]
+    signal: SignalArg,
+    /// Tone frequency, or the start of a sweep, in Hz
+    #[arg(long, default_value_t = 1000.0)]
+    freq: f64,
+    /// End frequency of a sweep in Hz
+    #[arg(long, default_value_t = 20000.0)]
+    to: f64,
+    /// Length as SECS, M:SS or H:MM:SS
+    #[arg(long, value_parser = parse_time, default_value = "5")]
+    duration: f64,
+    /// Peak level in dBFS
+    #[arg(long, allow_hyphen_values = true, default_value_t = -6.0)]
+    level: f32,
+    /// Sample rate in Hz
+    #[arg(long, default_value_t = 48000)]
+    rate: u32,
+    /// Number of channels, each carrying the same signal
+    #[arg(long, default_value_t = 1)]
+    channels: u16,
+    /// Bit depth: 8, 16, 24 or 32
+    #[arg(long, default_value_t = 24)]
+    bits: u16,
+    /// Write 32-bit IEEE float samples
+    #[arg(long)]
+    float: bool,
+    /// Seed of the noise generator; the same seed gives the same file
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Dither used when quantizing to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+    #[command(flatten)]
+    fade: FadeArgs,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SignalArg {
+    Sine,
+    Sweep,
+    White,
+    Pink,
+    Impulse,
+    Silence,
+}
+
+/// Synthesize the test signal and write it.
+pub fn run(args: GenArgs) -> Result<(), Box<dyn Error>> {
+    if args.rate == 0 || args.channels == 0 {
+        return Err("sample rate and channel count must be positive".into());
+    }
+    let nyquist = args.rate as f64 / 2.0;
+    let tonal = matches!(args.signal, SignalArg::Sine | SignalArg::Sweep);
+    if tonal && !(args.freq > 0.0 && args.freq < nyquist && args.to > 0.0 && args.to <= nyquist) {
+        return Err(format!("frequencies must lie between 0 and {} Hz", nyquist).into());
+    }
+    let format = SampleFormat::from_bits(if args.float { 32 } else { args.bits }, args.float)
+        .ok_or("unsupported output format: use --bits 8/16/24/32, or --float for 32-bit float")?;
+
+    let len = (args.duration * args.rate as f64).round() as usize;
+    let amplitude = db_to_gain(args.level);
+    let mono = match args.signal {
+        SignalArg::Sine => sine(args.freq, args.rate, len, amplitude),
+        SignalArg::Sweep => log_sweep(args.freq, args.to, args.rate, len, amplitude),
+        SignalArg::White => white_noise(len, amplitude, args.seed),
+        SignalArg::Pink => pink_noise(len, amplitude, args.seed),
+        SignalArg::Impulse => impulse(len, amplitude),
+        SignalArg::Silence => vec![0.0; len],
+    };
+
+    let channels = args.channels as usize;
+    let mut samples: Vec<f32> = mono.iter().flat_map(|&s| std::iter::repeat_n(s, channels)).collect();
+    args.fade.apply(&mut samples, channels, args.rate);
+
+    let spec = WavSpec { channels: args.channels, sample_rate: args.rate, format };
+    write_wav_file(&args.output, spec, &samples, args.dither.into())?;
+
+    info!(
+        "Wrote {} ({:.3} s, {} Hz, {} channel(s), {}-bit{})",
+        args.output.display(), args.duration, args.rate, args.channels, format.bits(),
+        if args.float { " float" } else { "" }
+    );
+
+    Ok(())
+}
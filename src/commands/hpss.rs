@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::analyzer::SpectrumAnalyzer;
+use fft_rs::hpss::hpss;
+use fft_rs::sample::mix_to_mono;
+use fft_rs::stft::StftConfig;
+use fft_rs::wav::WavFile;
+use fft_rs::window::Window;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::convert::DitherArg;
+use crate::plots::plot_spectrogram;
+
+#[derive(Args)]
+pub struct HpssArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file for the harmonic part
+    harmonic: PathBuf,
+    /// Output WAV file for the percussive part
+    percussive: PathBuf,
+    /// STFT window size
+    #[arg(long, default_value_t = 2048)]
+    nfft: usize,
+    /// Median filter length along time, in frames
+    #[arg(long, default_value_t = 17)]
+    time_kernel: usize,
+    /// Median filter length along frequency, in bins
+    #[arg(long, default_value_t = 17)]
+    freq_kernel: usize,
+    /// Dither used when quantizing back to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+}
+
+/// Separate every channel, write both parts and plot their spectrograms.
+pub fn run(args: HpssArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1) as usize;
+    if args.time_kernel == 0 || args.freq_kernel == 0 {
+        return Err("median filter kernels must be at least 1".into());
+    }
+
+    let config = StftConfig::for_resynthesis(args.nfft);
+    config.validate(samples.len() / channels)?;
+
+    let mut harmonic = vec![0.0f32; samples.len()];
+    let mut percussive = vec![0.0f32; samples.len()];
+    for ch in 0..channels {
+        let channel: Vec<f32> = samples.iter().skip(ch).step_by(channels).copied().collect();
+        let parts = hpss(&channel, config, args.time_kernel, args.freq_kernel);
+        for (out, s) in harmonic.iter_mut().skip(ch).step_by(channels).zip(parts.harmonic) {
+            *out = s;
+        }
+        for (out, s) in percussive.iter_mut().skip(ch).step_by(channels).zip(parts.percussive) {
+            *out = s;
+        }
+    }
+
+    let spec = WavSpec { channels: channels as u16, sample_rate: fmt.sample_rate, format: SampleFormat::matching(fmt) };
+    let mut analyzer = SpectrumAnalyzer::new();
+    for (part, path, caption, plot) in [
+        (&harmonic, &args.harmonic, "Harmonic Part", "hpss_harmonic.png"),
+        (&percussive, &args.percussive, "Percussive Part", "hpss_percussive.png"),
+    ] {
+        write_wav_file(path, spec, part, args.dither.into())?;
+        let spectrogram = analyzer.spectrogram(&mix_to_mono(part, channels), fmt.sample_rate, config, Window::Hann);
+        plot_spectrogram(&spectrogram, caption, &[], &[], plot)?;
+        info!("Wrote {}, spectrogram saved to '{}'", path.display(), plot);
+    }
+
+    Ok(())
+}
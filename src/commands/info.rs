@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::bitdepth::BitDepthMeter;
+use fft_rs::wav::{fourcc_to_string, LazyWavFile, WAVE_FORMAT_IEEE_FLOAT};
+
+// frames decoded at a time for the bit depth scan
+const BLOCK_FRAMES: u64 = 1 << 16;
+
+#[derive(Args)]
+pub struct InfoArgs {
+    /// Input WAV file
+    input: PathBuf,
+}
+
+/// Print the format, chunk layout and effective bit depth. The header
+/// fields come from a lazy parse; the bit depth scan then reads the data
+/// chunk a block at a time.
+pub fn run(args: InfoArgs) -> Result<(), Box<dyn Error>> {
+    let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+    let wav = &lazy.wav;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let is_float = fmt.format_code() == WAVE_FORMAT_IEEE_FLOAT;
+
+    println!("{}", args.input.display());
+    println!("  Container: {} / {}", fourcc_to_string(wav.header.chunk_id), fourcc_to_string(wav.header.format));
+    println!("  Format code: {}", fmt.format_code());
+    println!("  Channels: {}", fmt.num_channels);
+    println!("  Sample rate: {} Hz", fmt.sample_rate);
+    println!("  Bits per sample: {}", fmt.bits_per_sample);
+    if let (Some(frames), Some(duration)) = (wav.num_frames(), wav.duration()) {
+        println!("  Frames: {}", frames);
+        println!("  Duration: {:.3} s", duration);
+    }
+    if let Some(bitrate) = wav.effective_bitrate() {
+        println!("  Bitrate: {} kbps", bitrate / 1000);
+    }
+    let markers = wav.markers();
+    if !markers.is_empty() {
+        println!("  Cue markers: {}", markers.len());
+    }
+
+    let mut meter = BitDepthMeter::new();
+    for start in (0..).step_by(BLOCK_FRAMES as usize) {
+        let block = lazy.read_range(start, BLOCK_FRAMES)?;
+        if block.is_empty() {
+            break;
+        }
+        meter.add(&block);
+    }
+    let depth = meter.finish();
+    println!("\nBit depth:");
+    match depth.used_bits {
+        Some(bits) if is_float => println!(
+            "  Used bits: float data on a {}-bit integer grid", bits
+        ),
+        Some(bits) if bits < depth.container => println!(
+            "  Used bits: {} of {} (the low {} are never set)", bits, depth.container, depth.container - bits
+        ),
+        Some(bits) => println!("  Used bits: {} of {}", bits, depth.container),
+        None if depth.distinct_values <= 1 => println!("  Used bits: none (digital silence)"),
+        None => println!("  Used bits: full float resolution (not on an integer grid)"),
+    }
+    if let Some(bits) = depth.histogram_bits {
+        println!("  Histogram resolution: {:.1} bits ({} distinct values)", bits, depth.distinct_values);
+    } else {
+        println!("  Distinct values: {}", depth.distinct_values);
+    }
+    if let Some(bits) = depth.effective() {
+        println!("  Effective bit depth: ~{:.0} bits", bits);
+    }
+
+    Ok(())
+}
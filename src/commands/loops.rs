@@ -0,0 +1,82 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::wav::LazyWavFile;
+use fft_rs::writer::{write_encoded_file, write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+#[derive(Args)]
+pub struct LoopsArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Write the looped region to this file
+    #[arg(long, value_name = "WAV")]
+    export: Option<PathBuf>,
+    /// Which loop to export (1-based)
+    #[arg(long = "loop", default_value_t = 1)]
+    index: usize,
+}
+
+/// List the smpl chunk's loop points and optionally export one loop body.
+pub fn run(args: LoopsArgs) -> Result<(), Box<dyn Error>> {
+    let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+    let fmt = lazy.wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let smpl = lazy.wav.smpl.as_ref().ok_or("input has no smpl chunk")?;
+    let rate = fmt.sample_rate as f64;
+
+    println!("MIDI unity note: {}", smpl.midi_unity_note);
+    println!("Loops: {}", smpl.loops.len());
+    for (i, l) in smpl.loops.iter().enumerate() {
+        let kind = match l.loop_type {
+            0 => "forward",
+            1 => "ping-pong",
+            2 => "reverse",
+            _ => "custom",
+        };
+        // the end point is inclusive; a malformed chunk may put it first
+        let length = match (l.end as u64 + 1).checked_sub(l.start as u64) {
+            Some(length) => format!("{} samples", length),
+            None => "invalid (ends before it starts)".to_string(),
+        };
+        println!(
+            "  {}: {} start {} ({:.4} s) end {} ({:.4} s) length {}, plays {}",
+            i + 1, kind,
+            l.start, l.start as f64 / rate,
+            l.end, l.end as f64 / rate,
+            length,
+            if l.play_count == 0 { "forever".to_string() } else { format!("{}x", l.play_count) },
+        );
+    }
+
+    let Some(output) = &args.export else {
+        return Ok(());
+    };
+
+    let l = *smpl.loops.get(args.index.wrapping_sub(1))
+        .ok_or(format!("no loop {} (file has {})", args.index, smpl.loops.len()))?;
+    if l.end < l.start {
+        return Err(format!("loop {} ends before it starts", args.index).into());
+    }
+
+    let spec = WavSpec {
+        channels: fmt.num_channels.max(1),
+        sample_rate: fmt.sample_rate,
+        format: SampleFormat::matching(fmt),
+    };
+    let verbatim = spec.format.bits() == fmt.bits_per_sample;
+
+    // the end point is inclusive
+    let bytes = lazy.read_frames(l.start as u64, l.end as u64 - l.start as u64 + 1)?;
+    if verbatim {
+        write_encoded_file(output, spec, &bytes)?;
+    } else {
+        let samples = lazy.wav.fmt.as_ref().unwrap().decode(&bytes)?.to_f32();
+        write_wav_file(output, spec, &samples, Dither::Off)?;
+    }
+    info!("Exported loop {} to {}", args.index, output.display());
+
+    Ok(())
+}
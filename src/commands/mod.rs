@@ -0,0 +1,407 @@
+pub mod ab_match;
+pub mod analyze;
+pub mod animate;
+pub mod assert;
+pub mod batch;
+pub mod bench;
+pub mod cochleagram;
+pub mod concat;
+pub mod convert;
+pub mod cut;
+pub mod deconvolve;
+pub mod dehum;
+pub mod eq;
+pub mod export;
+pub mod flutter;
+pub mod formants;
+pub mod gammatone;
+pub mod gen;
+pub mod gate;
+pub mod hpss;
+pub mod info;
+pub mod loops;
+pub mod modulation;
+pub mod multiband;
+pub mod nmf;
+pub mod normalize;
+pub mod novelty;
+pub mod overs;
+pub mod partials;
+pub mod pitch;
+pub mod plot;
+pub mod response;
+pub mod rt60;
+pub mod spectrogram;
+pub mod split;
+pub mod ssm;
+pub mod tempogram;
+pub mod thumbnail;
+pub mod transfer;
+pub mod tremolo;
+pub mod tune;
+pub mod vectorscope;
+pub mod vibrato;
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::Path;
+
+use clap::{Args, ValueEnum};
+use fft_rs::bands::BandFraction;
+use fft_rs::eq::TargetCurve;
+use fft_rs::fade::{apply_fades, FadeCurve};
+#[cfg(feature = "gpu")]
+use fft_rs::gpu::GpuFft;
+use fft_rs::sample::mix_to_mono;
+use fft_rs::spectrum::SpectrumScale;
+use fft_rs::stft::{Averaging, Spectrogram, StftConfig};
+use fft_rs::wav::WavFile;
+use fft_rs::window::Window;
+use tracing::warn;
+
+/// Fade options shared by every subcommand that writes audio.
+#[derive(Args)]
+pub struct FadeArgs {
+    /// Fade in over the first SECS seconds
+    #[arg(long, value_name = "SECS")]
+    fade_in: Option<f32>,
+    /// Fade out over the last SECS seconds
+    #[arg(long, value_name = "SECS")]
+    fade_out: Option<f32>,
+    /// Fade curve
+    #[arg(long, value_enum, default_value_t = CurveArg::Linear)]
+    fade_curve: CurveArg,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CurveArg {
+    Linear,
+    Log,
+}
+
+impl FadeArgs {
+    pub fn is_active(&self) -> bool {
+        self.fade_in.is_some() || self.fade_out.is_some()
+    }
+
+    /// Apply the requested fades (if any) to interleaved samples.
+    pub fn apply(&self, samples: &mut [f32], channels: usize, sample_rate: u32) {
+        if !self.is_active() {
+            return;
+        }
+        let curve = match self.fade_curve {
+            CurveArg::Linear => FadeCurve::Linear,
+            CurveArg::Log => FadeCurve::Log,
+        };
+        apply_fades(
+            samples,
+            channels,
+            sample_rate,
+            self.fade_in.unwrap_or(0.0),
+            self.fade_out.unwrap_or(0.0),
+            curve,
+        );
+    }
+}
+
+/// FFT options for the whole-file spectrum.
+#[derive(Args)]
+pub struct SpectrumArgs {
+    /// Zero-padding factor applied on top of the next power of two; larger
+    /// values interpolate a denser spectrum
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=64))]
+    zero_pad: u32,
+    /// Transform this many samples from the start of the signal (any size,
+    /// not only powers of two) instead of the whole signal
+    #[arg(long)]
+    fft_size: Option<usize>,
+    /// Average the magnitude spectra of overlapping STFT windows (framed by
+    /// --nfft/--hop/--overlap) instead of taking one transform
+    #[arg(long, value_enum, conflicts_with_all = ["fft_size", "zero_pad"])]
+    average: Option<AverageArg>,
+    /// Keep the per-bin maximum over all STFT windows (peak hold)
+    #[arg(long, conflicts_with_all = ["fft_size", "zero_pad", "average"])]
+    max_hold: bool,
+    /// Also sum the spectrum into octave or 1/3-octave bands (IEC 61260),
+    /// critical bands (one Bark wide) or ERB-wide bands and plot them as bars
+    #[arg(long, value_enum)]
+    bands: Option<BandsArg>,
+    /// Overlay the LPC spectral envelope of this order on the spectrum plot
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..=256))]
+    lpc_order: Option<u32>,
+    /// Units of the spectrum plot: raw FFT magnitudes, or true sine
+    /// amplitude (re full scale)
+    #[arg(long, value_enum, default_value_t = ScaleArg::Raw)]
+    scale: ScaleArg,
+    /// Quantity to plot: sine amplitude, power (FS^2) or power spectral
+    /// density (FS^2/Hz, normalized by the window's ENBW)
+    #[arg(long, value_enum, conflicts_with = "scale")]
+    spectrum_type: Option<SpectrumTypeArg>,
+    /// Overlay a reference curve on the spectrum plot, fitted to its level:
+    /// "pink" (-3 dB/octave) or a CSV of frequency,level_db points
+    #[arg(long, value_name = "pink|FILE.csv")]
+    reference: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ScaleArg {
+    Raw,
+    Amplitude,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SpectrumTypeArg {
+    Amplitude,
+    Power,
+    Psd,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BandsArg {
+    Octave,
+    Third,
+    Bark,
+    Erb,
+}
+
+impl From<BandsArg> for BandFraction {
+    fn from(arg: BandsArg) -> Self {
+        match arg {
+            BandsArg::Octave => BandFraction::Octave,
+            BandsArg::Third => BandFraction::Third,
+            BandsArg::Bark => BandFraction::Bark,
+            BandsArg::Erb => BandFraction::Erb,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AverageArg {
+    Linear,
+    Db,
+}
+
+impl SpectrumArgs {
+    /// How many input samples to transform and the padded FFT length,
+    /// validated against the signal length.
+    pub fn sizes(&self, signal_len: usize) -> Result<(usize, usize), String> {
+        let zero_pad = self.zero_pad as usize;
+        match self.fft_size {
+            Some(n) if n < 2 => Err(format!("FFT size must be at least 2 (got {})", n)),
+            Some(n) if n > signal_len => Err(format!(
+                "FFT size {} exceeds the signal length ({} samples)", n, signal_len
+            )),
+            Some(n) => Ok((n, n * zero_pad)),
+            None => Ok((signal_len, signal_len.next_power_of_two() * zero_pad)),
+        }
+    }
+
+    pub fn max_hold(&self) -> bool {
+        self.max_hold
+    }
+
+    /// Fractional-octave band analysis to run, if requested.
+    pub fn bands(&self) -> Option<BandFraction> {
+        self.bands.map(BandFraction::from)
+    }
+
+    /// Units to convert the spectrum to.
+    pub fn spectrum_scale(&self) -> SpectrumScale {
+        match (self.spectrum_type, self.scale) {
+            (Some(SpectrumTypeArg::Amplitude), _) | (None, ScaleArg::Amplitude) => SpectrumScale::Amplitude,
+            (Some(SpectrumTypeArg::Power), _) => SpectrumScale::Power,
+            (Some(SpectrumTypeArg::Psd), _) => SpectrumScale::Psd,
+            (None, ScaleArg::Raw) => SpectrumScale::Raw,
+        }
+    }
+
+    /// Order of the LPC envelope to overlay, if requested.
+    pub fn lpc_order(&self) -> Option<usize> {
+        self.lpc_order.map(|order| order as usize)
+    }
+
+    /// Reference curve to overlay and its legend label, if requested; a
+    /// target file is read here so a bad one fails before any plot.
+    pub fn reference(&self) -> Result<Option<(String, TargetCurve)>, String> {
+        match self.reference.as_deref() {
+            None => Ok(None),
+            Some("pink") => Ok(Some(("Pink noise (-3 dB/oct)".to_string(), TargetCurve::Pink))),
+            Some(path) => {
+                let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+                let curve = TargetCurve::parse_csv(&text).map_err(|e| format!("{}: {}", path, e))?;
+                let label = Path::new(path).file_name().map_or(path.into(), |n| n.to_string_lossy().into_owned());
+                Ok(Some((label, curve)))
+            }
+        }
+    }
+
+    /// Frame averaging to use instead of a single transform, if requested.
+    pub fn averaging(&self) -> Option<Averaging> {
+        self.average.map(|a| match a {
+            AverageArg::Linear => Averaging::Linear,
+            AverageArg::Db => Averaging::Db,
+        })
+    }
+}
+
+/// STFT framing options shared by the time-frequency analyses.
+#[derive(Args)]
+pub struct StftArgs {
+    /// STFT window (FFT) size in samples
+    #[arg(long, default_value_t = 1024)]
+    nfft: usize,
+    /// Samples between successive windows
+    #[arg(long, conflicts_with = "overlap")]
+    hop: Option<usize>,
+    /// Fraction of each window shared with the next, in [0, 1)
+    #[arg(long)]
+    overlap: Option<f32>,
+    /// Merge several window sizes (e.g. 512,2048,8192): long windows for
+    /// low frequencies, short ones for highs; hop/overlap follow the shortest
+    #[arg(long, value_delimiter = ',', num_args = 1.., conflicts_with = "nfft")]
+    multi_res: Vec<usize>,
+    /// STFT analysis window; levels are corrected for its coherent gain
+    #[arg(long, value_enum, default_value_t = WindowArg::Hann, conflicts_with = "multi_res")]
+    window: WindowArg,
+    /// Where the STFT frames are transformed; gpu needs a build with the
+    /// gpu feature and a power-of-two window, and falls back to the CPU
+    #[arg(long, value_enum, default_value_t = BackendArg::Cpu)]
+    backend: BackendArg,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BackendArg {
+    Cpu,
+    Gpu,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WindowArg {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+    Flattop,
+}
+
+impl StftArgs {
+    pub fn window(&self) -> Window {
+        match self.window {
+            WindowArg::Rectangular => Window::Rectangular,
+            WindowArg::Hann => Window::Hann,
+            WindowArg::Hamming => Window::Hamming,
+            WindowArg::Blackman => Window::Blackman,
+            WindowArg::Flattop => Window::FlatTop,
+        }
+    }
+
+    /// The framing to use, validated against the signal length.
+    /// With `--multi-res` this is the shortest window's framing.
+    pub fn config(&self, signal_len: usize) -> Result<StftConfig, String> {
+        let nfft = self.multi_res.iter().copied().min().unwrap_or(self.nfft);
+        // --overlap wins over a hop that only came from fft-rs.toml
+        let config = match (self.overlap, self.hop) {
+            (Some(overlap), _) if !(0.0..1.0).contains(&overlap) => {
+                return Err(format!("overlap must be in [0, 1) (got {})", overlap));
+            }
+            (Some(overlap), _) => StftConfig::with_overlap(nfft, overlap),
+            (None, Some(hop)) => StftConfig { nfft, hop },
+            (None, None) => StftConfig::with_overlap(nfft, 0.5),
+        };
+        config.validate(signal_len)?;
+        for &size in &self.multi_res {
+            StftConfig { nfft: size, hop: config.hop }.validate(signal_len)?;
+        }
+        Ok(config)
+    }
+
+    /// Spectrogram of `samples` with the chosen window, on the chosen backend.
+    pub fn spectrogram(&self, samples: &[f32], sample_rate: u32, config: StftConfig) -> Spectrogram {
+        if self.backend == BackendArg::Gpu {
+            match gpu_spectrogram(samples, sample_rate, config, self.window()) {
+                Ok(spectrogram) => return spectrogram,
+                Err(e) => warn!("GPU backend unavailable ({}), using the CPU", e),
+            }
+        }
+        Spectrogram::compute_windowed(samples, sample_rate, config, self.window())
+    }
+
+    /// Window sizes to merge, if `--multi-res` was given.
+    pub fn multi_res(&self) -> Option<&[usize]> {
+        (!self.multi_res.is_empty()).then_some(&self.multi_res[..])
+    }
+}
+
+#[cfg(feature = "gpu")]
+fn gpu_spectrogram(samples: &[f32], sample_rate: u32, config: StftConfig, window: Window) -> Result<Spectrogram, String> {
+    let frames = GpuFft::new()?.stft_magnitudes(samples, config, &window.coefficients(config.nfft))?;
+    Ok(Spectrogram { config, sample_rate, frames, window })
+}
+
+#[cfg(not(feature = "gpu"))]
+fn gpu_spectrogram(_: &[f32], _: u32, _: StftConfig, _: Window) -> Result<Spectrogram, String> {
+    Err("built without the gpu feature".into())
+}
+
+/// Mono mix and sample rate of a file.
+pub fn load_mono(path: &Path) -> Result<(Vec<f32>, u32), Box<dyn Error>> {
+    let mut file = File::open(path)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or_else(|| format!("{}: no fmt chunk", path.display()))?;
+    let samples = mix_to_mono(&wav.to_normalized_samples()?, fmt.num_channels.max(1) as usize);
+    Ok((samples, fmt.sample_rate))
+}
+
+/// Parse a timestamp given as seconds ("83.5"), m:ss ("1:23.5") or
+/// h:mm:ss ("0:01:23.5").
+pub fn parse_time(s: &str) -> Result<f64, String> {
+    let err = || format!("not a time: {} (use SECS, M:SS or H:MM:SS)", s);
+
+    let parts: Vec<&str> = s.trim().split(':').collect();
+    if parts.len() > 3 {
+        return Err(err());
+    }
+
+    let mut seconds = 0.0;
+    for part in &parts {
+        let value: f64 = part.parse().map_err(|_| err())?;
+        if value < 0.0 {
+            return Err(err());
+        }
+        seconds = seconds * 60.0 + value;
+    }
+    Ok(seconds)
+}
+
+/// Parse a level or gain given as "-60", "-60dB", "-60dBFS", "6dB" or
+/// "-16 LUFS".
+pub fn parse_db(s: &str) -> Result<f32, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    let number = ["dbfs", "lufs", "db"].iter()
+        .find_map(|unit| lower.strip_suffix(unit))
+        .unwrap_or(&lower)
+        .trim();
+    number.parse().map_err(|_| format!("not a level: {}", s))
+}
+
+/// Parse a frame size given as WIDTHxHEIGHT ("960x540").
+pub fn parse_size(s: &str) -> Result<(u16, u16), String> {
+    let err = || format!("not a size: {} (use WIDTHxHEIGHT, e.g. 960x540)", s);
+    let (width, height) = s.trim().split_once(['x', 'X']).ok_or_else(err)?;
+    let width: u16 = width.trim().parse().map_err(|_| err())?;
+    let height: u16 = height.trim().parse().map_err(|_| err())?;
+    if width < 200 || height < 150 {
+        return Err(format!("frames of {}x{} are too small to draw a chart on", width, height));
+    }
+    Ok((width, height))
+}
+
+/// Format seconds the way `parse_time` reads them: m:ss.s, or h:mm:ss.s
+/// from an hour on.
+pub fn format_time(secs: f64) -> String {
+    let (minutes, secs) = ((secs / 60.0).floor() as u64, secs % 60.0);
+    match minutes / 60 {
+        0 => format!("{}:{:04.1}", minutes, secs),
+        hours => format!("{}:{:02}:{:04.1}", hours, minutes % 60, secs),
+    }
+}
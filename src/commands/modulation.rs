@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::analyzer::SpectrumAnalyzer;
+use fft_rs::modulation::{modulation_bands, modulation_depths, MODULATION_HIGH, MODULATION_LOW};
+use tracing::info;
+
+use super::gammatone::{frame_hop, FilterbankArgs};
+use super::load_mono;
+use crate::plots::plot_modulation_spectrum;
+
+// envelopes are sampled well above twice the fastest modulation, so the
+// averaging over each frame barely dulls it
+const ENVELOPE_RATE: f32 = 8.0 * MODULATION_HIGH;
+
+#[derive(Args)]
+pub struct ModulationArgs {
+    /// Input WAV file
+    input: PathBuf,
+    #[command(flatten)]
+    filterbank: FilterbankArgs,
+    /// Depth range shown, in dB below full modulation
+    #[arg(long, default_value_t = 40.0)]
+    range: f32,
+    /// Write every channel's depths as CSV (center frequency, then one
+    /// column per modulation band)
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    /// Where to write the modulation spectrogram
+    #[arg(long, default_value = "modulation.png")]
+    plot: PathBuf,
+}
+
+/// Modulation spectrum of every gammatone channel of the mono mix: list
+/// the depth per modulation band over all channels, and plot them channel
+/// by channel.
+pub fn run(args: ModulationArgs) -> Result<(), Box<dyn Error>> {
+    if args.range <= 0.0 {
+        return Err("range must be positive".into());
+    }
+    let (samples, rate) = load_mono(&args.input)?;
+    let seconds = samples.len() as f32 / rate as f32;
+    if seconds < 1.0 / MODULATION_LOW {
+        return Err(format!(
+            "need at least {} s to resolve {} Hz modulation (got {:.2} s)",
+            1.0 / MODULATION_LOW, MODULATION_LOW, seconds
+        ).into());
+    }
+    let bank = args.filterbank.bank(rate)?;
+    let hop = frame_hop(rate, ENVELOPE_RATE)?;
+    let envelopes = bank.envelopes(&samples, hop);
+
+    let envelope_rate = rate as f32 / hop as f32;
+    let mut analyzer = SpectrumAnalyzer::new();
+    let depths: Vec<Vec<f32>> = envelopes.iter()
+        .map(|envelope| modulation_depths(&mut analyzer, envelope, envelope_rate))
+        .collect();
+
+    // over all channels, each weighted by its mean power, so quiet channels
+    // (whose noise floor fluctuates a lot) don't dominate
+    let bands = modulation_bands();
+    let weights: Vec<f32> = envelopes.iter()
+        .map(|envelope| {
+            let mean = envelope.iter().sum::<f32>() / envelope.len().max(1) as f32;
+            mean * mean
+        })
+        .collect();
+    let total: f32 = weights.iter().sum::<f32>().max(f32::MIN_POSITIVE);
+    let overall: Vec<f32> = (0..bands.len())
+        .map(|b| {
+            let power: f32 = depths.iter().zip(&weights).map(|(row, w)| row[b] * row[b] * w).sum();
+            (power / total).sqrt()
+        })
+        .collect();
+
+    println!("{:>7} {:>9}", "Mod Hz", "Depth %");
+    for (band, depth) in bands.iter().zip(&overall) {
+        println!("{:>7.2} {:>9.1}", band.center, depth * 100.0);
+    }
+    if let Some((band, depth)) = bands.iter().zip(&overall).max_by(|a, b| a.1.total_cmp(b.1)) {
+        println!("Strongest modulation: {:.2} Hz ({:.1}% depth)", band.center, depth * 100.0);
+    }
+
+    if let Some(path) = &args.csv {
+        let mut out = BufWriter::new(File::create(path)?);
+        write!(out, "center")?;
+        for band in &bands {
+            write!(out, ",{:.2}", band.center)?;
+        }
+        writeln!(out)?;
+        for (center, row) in bank.centers.iter().zip(&depths) {
+            write!(out, "{:.1}", center)?;
+            for depth in row {
+                write!(out, ",{:.6}", depth)?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()?;
+        info!("Modulation depths written to '{}'", path.display());
+    }
+
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_modulation_spectrum(&depths, &bands, &bank.centers, -args.range, "Modulation Spectrum", path)?;
+    info!("Modulation spectrogram saved to '{}'", path);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::crossover::split_bands;
+use fft_rs::dynamics::rms_over_time;
+use plotters::style::{Color, Palette, Palette99, RGBColor};
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::{plot_lines, Series};
+
+#[derive(Args)]
+pub struct MultibandArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Crossover frequencies in Hz, e.g. 250,4000 for lows, mids and highs
+    #[arg(long, value_delimiter = ',', default_value = "250,4000")]
+    crossovers: Vec<f32>,
+    /// Where to write the RMS plot
+    #[arg(long, default_value = "multiband.png")]
+    plot: PathBuf,
+}
+
+/// "< 250 Hz", "250-4000 Hz", "> 4000 Hz"
+pub fn band_label(lower: Option<f32>, upper: Option<f32>) -> String {
+    match (lower, upper) {
+        (None, Some(hi)) => format!("< {} Hz", hi),
+        (Some(lo), Some(hi)) => format!("{}-{} Hz", lo, hi),
+        (Some(lo), None) => format!("> {} Hz", lo),
+        (None, None) => "Full band".to_string(),
+    }
+}
+
+/// Split the mono mix into bands and plot each band's RMS over time.
+pub fn run(args: MultibandArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let nyquist = rate as f32 / 2.0;
+    if args.crossovers.iter().any(|&f| !(f > 0.0 && f < nyquist)) {
+        return Err(format!("crossovers must be between 0 and {} Hz", nyquist).into());
+    }
+    if args.crossovers.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err("crossovers must be in ascending order".into());
+    }
+
+    let bands = split_bands(&samples, rate, &args.crossovers);
+    let curves: Vec<(String, Vec<(f32, f32)>)> = bands.iter().enumerate().map(|(i, band)| {
+        let lower = i.checked_sub(1).map(|j| args.crossovers[j]);
+        let upper = args.crossovers.get(i).copied();
+        (band_label(lower, upper), rms_over_time(band, rate))
+    }).collect();
+    if curves.iter().all(|(_, points)| points.is_empty()) {
+        return Err("input is too short (need at least 400 ms)".into());
+    }
+
+    println!("Mean RMS by band:");
+    for (label, points) in &curves {
+        let mean = points.iter().map(|p| p.1).sum::<f32>() / points.len().max(1) as f32;
+        println!("  {:>14}: {:7.1} dBFS", label, mean);
+    }
+
+    let series: Vec<Series> = curves.iter().enumerate().map(|(i, (label, points))| {
+        let c = Palette99::pick(i).to_rgba();
+        Series { label, points, color: RGBColor(c.0, c.1, c.2) }
+    }).collect();
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("Band RMS Over Time", "Time (s)", "RMS (dBFS)", &series, false, path)?;
+    info!("Band RMS plot saved to '{}'", path);
+
+    Ok(())
+}
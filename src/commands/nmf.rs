@@ -0,0 +1,101 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::nmf::separate;
+use fft_rs::stft::StftConfig;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::load_mono;
+
+#[derive(Args)]
+pub struct NmfArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Number of components K
+    #[arg(long, short = 'k', default_value_t = 4)]
+    components: usize,
+    /// Multiplicative update rounds
+    #[arg(long, default_value_t = 200)]
+    iterations: usize,
+    /// STFT window size
+    #[arg(long, default_value_t = 2048)]
+    nfft: usize,
+    /// Where to write the basis spectra as CSV (frequency, then one column
+    /// per component)
+    #[arg(long, default_value = "nmf_basis.csv")]
+    basis: PathBuf,
+    /// Where to write the activations as CSV (time, then one column per
+    /// component)
+    #[arg(long, default_value = "nmf_activations.csv")]
+    activations: PathBuf,
+    /// Also resynthesize each component by soft masking into
+    /// PREFIX1.wav, PREFIX2.wav, ... (mono, 32-bit float)
+    #[arg(long, value_name = "PREFIX")]
+    separate: Option<PathBuf>,
+}
+
+// header line "<first>,component1,component2,..."
+fn write_header(out: &mut impl Write, first: &str, components: usize) -> std::io::Result<()> {
+    write!(out, "{}", first)?;
+    for c in 1..=components {
+        write!(out, ",component{}", c)?;
+    }
+    writeln!(out)
+}
+
+/// Factorize the mono mix's spectrogram and export the factors.
+pub fn run(args: NmfArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    if args.components == 0 {
+        return Err("need at least one component".into());
+    }
+    let config = StftConfig::for_resynthesis(args.nfft);
+    config.validate(samples.len())?;
+
+    let (model, parts) = separate(&samples, config, args.components, args.iterations);
+
+    let mut out = BufWriter::new(File::create(&args.basis)?);
+    write_header(&mut out, "frequency", args.components)?;
+    for k in 0..config.nfft / 2 + 1 {
+        write!(out, "{:.3}", k as f32 * rate as f32 / config.nfft as f32)?;
+        for basis in &model.basis {
+            write!(out, ",{:e}", basis[k])?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()?;
+
+    // frames are centered nfft/2 into the padded signal, which starts nfft early
+    let mut out = BufWriter::new(File::create(&args.activations)?);
+    write_header(&mut out, "time", args.components)?;
+    for t in 0..model.activations.first().map_or(0, |a| a.len()) {
+        let center = (t * config.hop + config.nfft / 2) as f32 - config.nfft as f32;
+        write!(out, "{:.6}", center / rate as f32)?;
+        for activations in &model.activations {
+            write!(out, ",{:e}", activations[t])?;
+        }
+        writeln!(out)?;
+    }
+    out.flush()?;
+    info!(
+        "{} components: basis written to '{}', activations to '{}'",
+        args.components, args.basis.display(), args.activations.display()
+    );
+
+    if let Some(prefix) = &args.separate {
+        let spec = WavSpec { channels: 1, sample_rate: rate, format: SampleFormat::F32 };
+        for (c, part) in parts.iter().enumerate() {
+            let path = PathBuf::from(format!("{}{}.wav", prefix.display(), c + 1));
+            write_wav_file(&path, spec, part, Dither::Off)?;
+            let rms = (part.iter().map(|s| s * s).sum::<f32>() / part.len().max(1) as f32).sqrt();
+            println!("  component {}: {:6.1} dBFS RMS -> {}", c + 1, 20.0 * (rms + 1e-12).log10(), path.display());
+        }
+    }
+
+    Ok(())
+}
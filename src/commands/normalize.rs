@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::meter::{db_to_gain, integrated_loudness, sample_peak, to_db, true_peak};
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::{info, warn};
+
+use super::convert::DitherArg;
+use super::{parse_db, FadeArgs};
+
+#[derive(Args)]
+pub struct NormalizeArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file
+    output: PathBuf,
+    /// Target sample peak, e.g. -1dBFS
+    #[arg(long, allow_hyphen_values = true, value_parser = parse_db,
+          conflicts_with = "lufs", required_unless_present = "lufs")]
+    peak: Option<f32>,
+    /// Target integrated loudness, e.g. -16LUFS
+    #[arg(long, allow_hyphen_values = true, value_parser = parse_db)]
+    lufs: Option<f32>,
+    /// Dither used when quantizing back to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+    #[command(flatten)]
+    fade: FadeArgs,
+}
+
+/// Measure, compute the gain that hits the target, and write the scaled copy.
+pub fn run(args: NormalizeArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav.to_normalized_samples()?;
+
+    let peak_db = to_db(sample_peak(&samples));
+    if !peak_db.is_finite() {
+        return Err("input is silent, nothing to normalize".into());
+    }
+    let channels = fmt.num_channels as usize;
+    println!("Sample peak: {:.2} dBFS", peak_db);
+    println!("True peak: {:.2} dBTP", to_db(true_peak(&samples, channels)));
+
+    let gain_db = match (args.peak, args.lufs) {
+        (Some(target), _) => target - peak_db,
+        (None, Some(target)) => {
+            let loudness = integrated_loudness(&samples, channels, fmt.sample_rate)
+                .ok_or("input is too short or too quiet to measure loudness")?;
+            println!("Integrated loudness: {:.2} LUFS", loudness);
+            target - loudness
+        }
+        (None, None) => unreachable!("clap requires --peak or --lufs"),
+    };
+
+    let format = SampleFormat::matching(fmt);
+    if peak_db + gain_db > 0.0 && format != SampleFormat::F32 {
+        warn!("gain pushes the peak to {:+.2} dBFS; output will clip", peak_db + gain_db);
+    }
+
+    let gain = db_to_gain(gain_db);
+    let mut scaled: Vec<f32> = samples.iter().map(|&s| s * gain).collect();
+
+    args.fade.apply(&mut scaled, channels, fmt.sample_rate);
+
+    let spec = WavSpec { channels: fmt.num_channels.max(1), sample_rate: fmt.sample_rate, format };
+    write_wav_file(&args.output, spec, &scaled, args.dither.into())?;
+
+    info!("Applied {:+.2} dB gain, wrote {}", gain_db, args.output.display());
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use fft_rs::rhythm::onset_novelty;
+use fft_rs::stft::{Spectrogram, StftConfig};
+use fft_rs::structure::{self_similarity, structural_novelty};
+use serde_json::json;
+use tracing::info;
+
+use super::load_mono;
+use super::ssm::StructureArgs;
+
+// onset analysis framing, as for the tempogram
+const ONSET_NFFT: usize = 2048;
+const ONSET_HOP: usize = 512;
+
+#[derive(Args)]
+pub struct NoveltyArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output file: JSON if it ends in .json, CSV otherwise
+    output: PathBuf,
+    /// Onset novelty (spectral flux, one value per 512 samples) or
+    /// structural novelty (checkerboard kernel on the self-similarity matrix)
+    #[arg(long, value_enum, default_value_t = KindArg::Onset)]
+    kind: KindArg,
+    #[command(flatten)]
+    structure: StructureArgs,
+    /// Structural novelty kernel width in seconds
+    #[arg(long, default_value_t = 8.0)]
+    kernel: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum KindArg {
+    Onset,
+    Structure,
+}
+
+/// Compute a novelty curve of the mono mix and write it with timestamps.
+pub fn run(args: NoveltyArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+
+    // (time of the frame or block center, value)
+    let curve: Vec<(f32, f32)> = match args.kind {
+        KindArg::Onset => {
+            let config = StftConfig { nfft: ONSET_NFFT, hop: ONSET_HOP };
+            config.validate(samples.len())?;
+            let spectrogram = Spectrogram::compute(&samples, rate, config);
+            let center = ONSET_NFFT as f32 / 2.0 / rate as f32;
+            // value i compares frame i + 1 with frame i
+            onset_novelty(&spectrogram).into_iter().enumerate()
+                .map(|(i, value)| (spectrogram.frame_time(i + 1) + center, value))
+                .collect()
+        }
+        KindArg::Structure => {
+            let features = args.structure.features(&samples, rate)?;
+            let step = features.step;
+            let kernel = (args.kernel / step).round() as usize;
+            if kernel < 2 {
+                return Err("kernel must span at least two feature blocks".into());
+            }
+            structural_novelty(&self_similarity(&features.vectors), kernel).into_iter().enumerate()
+                .map(|(i, value)| ((i as f32 + 0.5) * step, value))
+                .collect()
+        }
+    };
+
+    let is_json = args.output.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let mut out = BufWriter::new(File::create(&args.output)?);
+    if is_json {
+        let kind = if args.kind == KindArg::Onset { "onset" } else { "structure" };
+        let points: Vec<_> = curve.iter().map(|&(time, value)| json!({ "time": time, "value": value })).collect();
+        let document = json!({ "file": args.input.display().to_string(), "kind": kind, "novelty": points });
+        serde_json::to_writer_pretty(&mut out, &document)?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "time,novelty")?;
+        for (time, value) in &curve {
+            writeln!(out, "{:.6},{:.6}", time, value)?;
+        }
+    }
+    out.flush()?;
+    info!("Wrote {} novelty values to '{}'", curve.len(), args.output.display());
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::meter::{to_db, true_peak};
+use fft_rs::resample::resample;
+use fft_rs::wav::WavFile;
+use plotters::prelude::*;
+use tracing::{info, instrument};
+
+use super::parse_time;
+use crate::plots::{background, caption_font, foreground, themed};
+
+const OVERSAMPLING: u32 = 8;
+
+// extra input frames on each side so the reconstruction settles
+const CONTEXT_FRAMES: usize = 32;
+
+#[derive(Args)]
+pub struct OversArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Center of the zoomed segment, as seconds, m:ss.s or h:mm:ss.s
+    /// (default: the sample peak)
+    #[arg(long, value_parser = parse_time)]
+    at: Option<f64>,
+    /// Length of the zoomed segment in milliseconds
+    #[arg(long, default_value_t = 2.0)]
+    span: f64,
+    /// Channel to show, counting from 1 (default: the one with the sample peak)
+    #[arg(long)]
+    channel: Option<usize>,
+    /// Where to write the plot
+    #[arg(long, default_value = "overs.png")]
+    plot: PathBuf,
+}
+
+/// Zoom in on a short segment and overlay its 8x oversampled reconstruction,
+/// so overs between the samples show up.
+pub fn run(args: OversArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1) as usize;
+    let rate = fmt.sample_rate;
+    let frames = samples.len() / channels;
+    if frames == 0 {
+        return Err("input has no samples".into());
+    }
+
+    // the sample peak is where overs are most likely
+    let peak_index = samples.iter().enumerate()
+        .fold(0, |best, (i, s)| if s.abs() > samples[best].abs() { i } else { best });
+    let channel = match args.channel {
+        Some(ch) if ch == 0 || ch > channels => {
+            return Err(format!("channel must be between 1 and {}", channels).into());
+        }
+        Some(ch) => ch - 1,
+        None => peak_index % channels,
+    };
+    let center = match args.at {
+        Some(t) => ((t * rate as f64).round() as usize).min(frames - 1),
+        None => peak_index / channels,
+    };
+    if args.span <= 0.0 {
+        return Err("span must be positive".into());
+    }
+
+    let half = ((args.span / 1000.0 * rate as f64 / 2.0).ceil() as usize).max(1);
+    let (start, end) = (center.saturating_sub(half), (center + half).min(frames));
+    let (padded_start, padded_end) = (start.saturating_sub(CONTEXT_FRAMES), (end + CONTEXT_FRAMES).min(frames));
+    let segment: Vec<f32> = samples[padded_start * channels..padded_end * channels]
+        .iter().skip(channel).step_by(channels).copied().collect();
+    let upsampled = resample(&segment, 1, rate, rate * OVERSAMPLING);
+
+    // both curves on a time axis in milliseconds
+    let ms = |frame: f64| (frame / rate as f64 * 1000.0) as f32;
+    let raw: Vec<(f32, f32)> = (start..end)
+        .map(|n| (ms(n as f64), samples[n * channels + channel]))
+        .collect();
+    let first = (start - padded_start) * OVERSAMPLING as usize;
+    let last = ((end - padded_start) * OVERSAMPLING as usize).min(upsampled.len());
+    let reconstructed: Vec<(f32, f32)> = (first..last)
+        .map(|i| (ms(padded_start as f64 + i as f64 / OVERSAMPLING as f64), upsampled[i]))
+        .collect();
+
+    let segment_peak = raw.iter().map(|p| p.1.abs()).fold(0.0, f32::max);
+    let reconstructed_peak = reconstructed.iter().map(|p| p.1.abs()).fold(0.0, f32::max);
+    let overs = reconstructed.iter().filter(|p| p.1.abs() > 1.0).count();
+    println!("Channel {}, {:.4}-{:.4} s", channel + 1, start as f64 / rate as f64, end as f64 / rate as f64);
+    println!("  Sample peak: {:.2} dBFS", to_db(segment_peak));
+    println!("  Reconstructed peak ({}x): {:.2} dBFS", OVERSAMPLING, to_db(reconstructed_peak));
+    println!("  Oversampled points over full scale: {}", overs);
+    println!("File true peak (4x, BS.1770): {:.2} dBTP", to_db(true_peak(&samples, channels)));
+
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_overs(&raw, &reconstructed, path)?;
+    info!("Overs plot saved to '{}'", path);
+
+    Ok(())
+}
+
+/// Plots raw samples as stems over their oversampled reconstruction, with
+/// the full-scale limits drawn and any reconstruction above them in red.
+///
+/// # Arguments
+///
+/// * `raw` - The stored samples as (ms, amplitude).
+/// * `reconstructed` - The oversampled signal on the same axes.
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+fn plot_overs(raw: &[(f32, f32)], reconstructed: &[(f32, f32)], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, (1280, 720)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    let x_min = reconstructed.first().or(raw.first()).map_or(0.0, |p| p.0);
+    let x_max = reconstructed.last().or(raw.last()).map_or(1.0, |p| p.0).max(x_min + 1e-3);
+    let y_max = reconstructed.iter().chain(raw).map(|p| p.1.abs()).fold(1.0, f32::max) * 1.05;
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Inter-sample Peaks", caption_font(40))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, -y_max..y_max)?;
+
+    themed(&mut chart.configure_mesh())
+        .x_label_formatter(&|x| format!("{:.3}", x))
+        .x_desc("Time (ms)")
+        .y_desc("Amplitude")
+        .draw()?;
+
+    let fg = foreground();
+    // full-scale limits
+    for level in [-1.0f32, 1.0] {
+        chart.draw_series(LineSeries::new(vec![(x_min, level), (x_max, level)], fg.mix(0.5).stroke_width(1)))?;
+    }
+
+    let curve = RGBColor(0, 90, 200);
+    chart.draw_series(LineSeries::new(reconstructed.iter().copied(), curve.stroke_width(2)))?
+        .label(format!("{}x reconstruction", OVERSAMPLING))
+        .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], curve));
+
+    let over = RGBColor(220, 0, 0);
+    chart.draw_series(reconstructed.iter().filter(|p| p.1.abs() > 1.0).map(|&p| Circle::new(p, 3, over.filled())))?
+        .label("Over full scale")
+        .legend(move |(x, y)| Circle::new((x + 10, y), 3, over.filled()));
+
+    chart.draw_series(raw.iter().map(|&(x, y)| PathElement::new(vec![(x, 0.0), (x, y)], fg.mix(0.6))))?;
+    chart.draw_series(raw.iter().map(|&p| Circle::new(p, 4, fg.filled())))?
+        .label("Samples")
+        .legend(move |(x, y)| Circle::new((x + 10, y), 4, fg.filled()));
+
+    chart
+        .configure_series_labels()
+        .label_font(caption_font(15))
+        .background_style(background().mix(0.8))
+        .border_style(foreground())
+        .draw()?;
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::partials::{synthesize, track_partials, TrackingConfig};
+use fft_rs::stft::{Spectrogram, StftConfig};
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::plot_spectrogram;
+
+#[derive(Args)]
+pub struct PartialsArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// STFT window size; long windows resolve close partials
+    #[arg(long, default_value_t = 4096)]
+    nfft: usize,
+    /// Ignore peaks below this sine amplitude, e.g. -60dBFS
+    #[arg(long, allow_hyphen_values = true, default_value_t = -60.0)]
+    threshold: f32,
+    /// Strongest peaks kept per frame
+    #[arg(long, default_value_t = 40)]
+    max_peaks: usize,
+    /// Drop tracks shorter than this many frames
+    #[arg(long, default_value_t = 5)]
+    min_frames: usize,
+    /// Write every track point as CSV (partial, time, frequency, amplitude)
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    /// Resynthesize the tracked partials (additive synthesis) into this
+    /// mono WAV file (32-bit float)
+    #[arg(long, value_name = "WAV")]
+    resynth: Option<PathBuf>,
+    /// Where to write the spectrogram with the tracks drawn over it
+    #[arg(long, default_value = "partials.png")]
+    plot: PathBuf,
+}
+
+/// Track partials through the mono mix, list the strongest, and plot them.
+pub fn run(args: PartialsArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let config = StftConfig::with_overlap(args.nfft, 0.75);
+    config.validate(samples.len())?;
+
+    let spectrogram = Spectrogram::compute(&samples, rate, config);
+    let tracking = TrackingConfig {
+        threshold_db: args.threshold,
+        max_peaks: args.max_peaks,
+        min_frames: args.min_frames,
+        ..TrackingConfig::default()
+    };
+    let partials = track_partials(&spectrogram, tracking);
+    println!("{} partials over {} frames", partials.len(), spectrogram.frames.len());
+
+    let mut strongest: Vec<_> = partials.iter().collect();
+    strongest.sort_by(|a, b| b.peak_amplitude().total_cmp(&a.peak_amplitude()));
+    println!("{:>10} {:>10} {:>10} {:>10}", "Start (s)", "Length (s)", "Mean (Hz)", "Peak dBFS");
+    for partial in strongest.iter().take(10) {
+        let (first, last) = (partial.points[0], partial.points[partial.points.len() - 1]);
+        println!(
+            "{:>10.3} {:>10.3} {:>10.1} {:>10.1}",
+            first.time,
+            last.time - first.time,
+            partial.mean_frequency(),
+            20.0 * partial.peak_amplitude().log10()
+        );
+    }
+
+    if let Some(path) = &args.csv {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "partial,time,frequency,amplitude")?;
+        for (i, partial) in partials.iter().enumerate() {
+            for p in &partial.points {
+                writeln!(out, "{},{:.6},{:.3},{:.6}", i, p.time, p.frequency, p.amplitude)?;
+            }
+        }
+        out.flush()?;
+        info!("Track points written to '{}'", path.display());
+    }
+
+    if let Some(path) = &args.resynth {
+        let resynthesized = synthesize(&partials, rate, samples.len(), config.hop);
+        let spec = WavSpec { channels: 1, sample_rate: rate, format: SampleFormat::F32 };
+        write_wav_file(path, spec, &resynthesized, Dither::Off)?;
+        info!("Resynthesis written to '{}'", path.display());
+    }
+
+    let tracks: Vec<Vec<(f32, f32)>> = partials.iter()
+        .map(|partial| partial.points.iter().map(|p| (p.time, p.frequency)).collect())
+        .collect();
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_spectrogram(&spectrogram, "Partial Tracks", &tracks, &[], path)?;
+    info!("Partial plot saved to '{}'", path);
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use fft_rs::midi::{MidiEvent, MidiTrack};
+use fft_rs::pitch::{midi_pitch, note_name, segment_notes, track_pitch, Note, PitchConfig, PitchPoint, SegmentConfig};
+use plotters::style::RGBColor;
+use serde_json::json;
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::{plot_lines, Series};
+
+// note velocities span this range of frame levels below full scale
+const VELOCITY_RANGE_DB: f32 = 60.0;
+
+// bends closer than this to the last one sent are skipped, semitones
+const BEND_STEP: f32 = 0.02;
+
+#[derive(Args)]
+pub struct PitchArgs {
+    /// Input WAV file (one voice or instrument at a time)
+    input: PathBuf,
+    #[command(flatten)]
+    tracking: PitchTrackingArgs,
+    /// Write the f0 of every frame as CSV (time, frequency, clarity);
+    /// unvoiced frames have an empty frequency
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    /// Write the note list (start, duration, pitch, confidence): JSON if
+    /// it ends in .json, CSV otherwise
+    #[arg(long, value_name = "PATH")]
+    notes: Option<PathBuf>,
+    /// Write the notes as a standard MIDI file
+    #[arg(long, value_name = "PATH")]
+    midi: Option<PathBuf>,
+    /// Follow the contour within each note with pitch bend messages
+    #[arg(long, requires = "midi")]
+    bend: bool,
+    /// Pitch bend range in semitones, which the receiving synth must match
+    #[arg(long, default_value_t = 2, requires = "bend")]
+    bend_range: u8,
+    /// Where to write the pitch track plot
+    #[arg(long, default_value = "pitch.png")]
+    plot: PathBuf,
+}
+
+/// YIN pitch tracking options shared by the pitch analyses.
+#[derive(Args)]
+pub struct PitchTrackingArgs {
+    /// Lowest fundamental searched, Hz
+    #[arg(long, default_value_t = 50.0)]
+    min: f32,
+    /// Highest fundamental searched, Hz
+    #[arg(long, default_value_t = 2000.0)]
+    max: f32,
+    /// YIN threshold; lower values call fewer frames voiced
+    #[arg(long, default_value_t = 0.15)]
+    threshold: f32,
+}
+
+impl PitchTrackingArgs {
+    /// The f0 of every frame of `samples`.
+    pub fn track(&self, samples: &[f32], rate: u32) -> Result<Vec<PitchPoint>, String> {
+        if self.min <= 0.0 || self.max <= self.min || self.max >= rate as f32 / 2.0 {
+            return Err(format!("need 0 < min < max < {} Hz (half the sample rate)", rate / 2));
+        }
+        let config = PitchConfig { min_freq: self.min, max_freq: self.max, threshold: self.threshold, ..PitchConfig::new(rate) };
+        let points = track_pitch(samples, rate, config);
+        if points.is_empty() {
+            return Err(format!("input is shorter than one analysis frame ({} samples)", config.frame_len(rate)));
+        }
+        Ok(points)
+    }
+}
+
+/// Track the fundamental of the mono mix with YIN, transcribe it into
+/// notes, and export the contour and the notes.
+pub fn run(args: PitchArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let points = args.tracking.track(&samples, rate)?;
+
+    let mut voiced: Vec<f32> = points.iter().filter_map(|p| p.frequency).collect();
+    println!("{} of {} frames voiced", voiced.len(), points.len());
+    if !voiced.is_empty() {
+        voiced.sort_by(f32::total_cmp);
+        let median = voiced[voiced.len() / 2];
+        let key = midi_pitch(median);
+        let cents = (key - key.round()) * 100.0;
+        println!("Median f0: {:.1} Hz ({} {:+.0} cents)", median, note_name(key.round() as u8), cents);
+    }
+    let notes = segment_notes(&points, SegmentConfig::default());
+    println!("{} notes", notes.len());
+    if !notes.is_empty() {
+        println!("{:>10} {:>10} {:>6} {:>10}", "Start (s)", "Length (s)", "Pitch", "Confidence");
+        for note in &notes {
+            println!("{:>10.3} {:>10.3} {:>6} {:>10.2}", note.start, note.end - note.start, note_name(note.key), note.confidence);
+        }
+    }
+
+    if let Some(path) = &args.csv {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "time,frequency,clarity")?;
+        for p in &points {
+            let frequency = p.frequency.map(|f| format!("{:.3}", f)).unwrap_or_default();
+            writeln!(out, "{:.6},{},{:.4}", p.time, frequency, p.clarity)?;
+        }
+        out.flush()?;
+        info!("Pitch contour written to '{}'", path.display());
+    }
+
+    if let Some(path) = &args.notes {
+        write_notes(path, &args.input, &notes)?;
+        info!("{} notes written to '{}'", notes.len(), path.display());
+    }
+
+    if let Some(path) = &args.midi {
+        midi_track(&notes, args.bend.then_some(args.bend_range)).write(path)?;
+        info!("{} notes written to '{}'", notes.len(), path.display());
+    }
+
+    let track: Vec<(f32, f32)> = points.iter().filter_map(|p| Some((p.time, p.frequency?))).collect();
+    if track.is_empty() {
+        info!("No voiced frames; skipping the plot");
+        return Ok(());
+    }
+    let series = [Series { label: "f0", points: &track, color: RGBColor(200, 0, 80) }];
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("Pitch Track", "Time (s)", "Frequency (Hz)", &series, false, path)?;
+    info!("Pitch plot saved to '{}'", path);
+
+    Ok(())
+}
+
+fn write_notes(path: &Path, input: &Path, notes: &[Note]) -> Result<(), Box<dyn Error>> {
+    let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let mut out = BufWriter::new(File::create(path)?);
+    if is_json {
+        let list: Vec<_> = notes.iter().map(|note| json!({
+            "start": note.start,
+            "duration": note.end - note.start,
+            "pitch": note_name(note.key),
+            "midi": note.key,
+            "confidence": note.confidence,
+        })).collect();
+        let document = json!({ "file": input.display().to_string(), "notes": list });
+        serde_json::to_writer_pretty(&mut out, &document)?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "start,duration,pitch,midi,confidence")?;
+        for note in notes {
+            writeln!(out, "{:.4},{:.4},{},{},{:.4}", note.start, note.end - note.start, note_name(note.key), note.key, note.confidence)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+// one MIDI note per detected note, velocity from its level; with a bend
+// range, the contour's deviation from each note's key as pitch bend
+fn midi_track(notes: &[Note], bend_range: Option<u8>) -> MidiTrack {
+    let mut track = MidiTrack::new();
+    if let Some(range) = bend_range {
+        track.set_bend_range(range);
+    }
+    for note in notes {
+        let loudness = ((note.level_db + VELOCITY_RANGE_DB) / VELOCITY_RANGE_DB).clamp(0.0, 1.0);
+        let velocity = (1.0 + loudness * 126.0).round() as u8;
+        if bend_range.is_some() {
+            // the first bend lands before the note on, the rest follow the frames
+            let mut last = f32::NAN;
+            for &(time, pitch) in &note.contour {
+                let bend = pitch - note.key as f32;
+                if last.is_nan() || (bend - last).abs() >= BEND_STEP {
+                    let time = if last.is_nan() { note.start } else { time };
+                    track.push(time as f64, MidiEvent::PitchBend(bend));
+                    last = bend;
+                }
+            }
+        }
+        track.note(note.start as f64, note.end as f64, note.key, velocity);
+        if bend_range.is_some() {
+            track.push(note.end as f64, MidiEvent::PitchBend(0.0));
+        }
+    }
+    track
+}
@@ -0,0 +1,406 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::auditory::FreqScale;
+use fft_rs::bands::{band_levels, Band};
+use fft_rs::clicks::detect_clicks;
+use fft_rs::eq::TargetCurve;
+use fft_rs::lpc::{envelope, lpc};
+use fft_rs::resample::{decimate, decimation_aliasing_db};
+use fft_rs::sample::mix_to_mono;
+use fft_rs::spectrum::{compute_spectrum, compute_spectrum_sized, Spectrum, SpectrumScale};
+use fft_rs::stft::Spectrogram;
+use fft_rs::wav::WavFile;
+use plotters::prelude::*;
+use tracing::{debug, info, instrument, warn};
+
+use super::{SpectrumArgs, StftArgs};
+use crate::plots::{background, caption_font, foreground, freq_desc, freq_scale, plot_spectrogram, plot_waveform, themed};
+
+const GRAY: RGBColor = RGBColor(128, 128, 128);
+
+// the waveform and spectrum plots keep every 16th interleaved sample
+const DECIMATION: usize = 16;
+
+// warn when decimation aliases more than this (dB re the filtered signal)
+const ALIASING_WARN_DB: f32 = -60.0;
+
+// the reference curve is fitted to bins from here up, within this range of
+// the spectrum's peak
+const REFERENCE_MIN_HZ: f32 = 20.0;
+const REFERENCE_RANGE_DB: f32 = 60.0;
+
+#[derive(Args)]
+pub struct PlotArgs {
+    /// Input WAV file
+    #[arg(default_value = "440hz.wav")]
+    input: PathBuf,
+    #[command(flatten)]
+    spectrum: SpectrumArgs,
+    #[command(flatten)]
+    stft: StftArgs,
+    /// Plot the synchrosqueezed STFT instead of the plain spectrogram
+    #[arg(long, conflicts_with_all = ["multi_res", "window"])]
+    synchrosqueeze: bool,
+    /// Detect clicks and pops (at SIGMA robust standard deviations) and
+    /// mark them on the waveform
+    #[arg(long, value_name = "SIGMA", num_args = 0..=1, default_missing_value = "10")]
+    clicks: Option<f32>,
+    /// Decimate the waveform and spectrum plots by plain sample skipping,
+    /// without the anti-aliasing low-pass (shows what folds down)
+    #[arg(long)]
+    no_antialias: bool,
+}
+
+/// Plot the waveform, spectrum and spectrogram (the bundled test tone by
+/// default) to waveform.png, fft_spectrum.png and spectrogram.png.
+pub fn run(args: PlotArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav_file = WavFile::parse(&mut file)?;
+    let fmt = wav_file.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav_file.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1) as usize;
+    let downsampled_samples: Vec<f32> = if args.no_antialias {
+        samples.iter().step_by(DECIMATION).cloned().collect()
+    } else {
+        decimate(&samples, channels, DECIMATION)
+    };
+
+    // the spectrum comes from the mono mix decimated as far as each
+    // waveform channel is, at the correspondingly lower rate
+    let mono = mix_to_mono(&samples, channels);
+    let factor = (DECIMATION / channels).max(1);
+    let decimated_mono: Vec<f32> = if args.no_antialias {
+        mono.iter().step_by(factor).copied().collect()
+    } else {
+        decimate(&mono, 1, factor)
+    };
+
+    // validate the FFT and STFT flags up front, before any plot is written
+    let config = args.stft.config(mono.len())?;
+    let (fft_input, fft_size) = args.spectrum.sizes(decimated_mono.len())?;
+    let reference = args.spectrum.reference()?;
+
+    // every DECIMATION-th interleaved sample is one channel decimated by
+    // DECIMATION / channels; without the low-pass, check what that folds down
+    if args.no_antialias && DECIMATION.is_multiple_of(channels) {
+        let first: Vec<f32> = samples.iter().step_by(channels).copied().collect();
+        if let Some(aliasing) = decimation_aliasing_db(&first, DECIMATION / channels) {
+            info!("Decimation by {}: energy above the new Nyquist {:.1} dB re the energy below it", DECIMATION / channels, aliasing);
+            if aliasing > ALIASING_WARN_DB {
+                warn!(
+                    "content above {:.0} Hz folds into the decimated waveform and spectrum",
+                    fmt.sample_rate as f32 / (2 * DECIMATION / channels) as f32
+                );
+            }
+        }
+    }
+
+    // cue markers, mapped from frames onto the decimated sample index for
+    // the waveform and onto seconds for the spectrogram
+    let cues: Vec<(u32, String)> = wav_file.markers().into_iter()
+        .enumerate()
+        .map(|(i, (frame, label))| (frame, label.map_or_else(|| format!("Cue {}", i + 1), str::to_string)))
+        .collect();
+    let markers: Vec<(usize, String)> = cues.iter()
+        .map(|(frame, label)| (*frame as usize * channels / DECIMATION, label.clone()))
+        .collect();
+    let cue_times: Vec<(f32, String)> = cues.iter()
+        .map(|(frame, label)| (*frame as f32 / fmt.sample_rate as f32, label.clone()))
+        .collect();
+
+    // clicks, found at full rate and mapped like the cue markers
+    let click_marks: Vec<usize> = match args.clicks {
+        Some(sensitivity) => {
+            if sensitivity <= 0.0 {
+                return Err("click sensitivity must be positive".into());
+            }
+            let found = detect_clicks(&samples, channels, fmt.sample_rate, sensitivity);
+            println!("Clicks: {}", found.len());
+            for click in &found {
+                println!("  {:9.3} s  ({:.0} sigma)", click.time, click.strength);
+            }
+            found.iter().map(|click| click.frame * channels / DECIMATION).collect()
+        }
+        None => Vec::new(),
+    };
+
+    plot_waveform(&downsampled_samples, &markers, &click_marks, None, "waveform.png")?;
+    info!("Waveform plot saved to 'waveform.png'");
+    let full_rate = fmt.sample_rate as f32;
+    let (fft_spectrum, fft_source, source_rate) = if args.spectrum.max_hold() {
+        (args.stft.spectrogram(&mono, fmt.sample_rate, config).max_hold(), &mono[..], full_rate)
+    } else if let Some(averaging) = args.spectrum.averaging() {
+        (args.stft.spectrogram(&mono, fmt.sample_rate, config).average(averaging), &mono[..], full_rate)
+    } else {
+        let source = &decimated_mono[..fft_input];
+        let mut spectrum = compute_spectrum_sized(source, fmt.sample_rate, fft_size);
+        // the bins come labelled at the full rate; each decimated sample
+        // spans `factor` of those
+        spectrum.frequencies.iter_mut().for_each(|f| *f /= factor as f32);
+        (spectrum, source, full_rate / factor as f32)
+    };
+
+    let fft_spectrum = fft_spectrum.to_scale(args.spectrum.spectrum_scale());
+
+    // the LPC envelope is fitted to the same samples the spectrum came from
+    let lpc_envelope = match args.spectrum.lpc_order() {
+        Some(order) if order >= fft_source.len() => {
+            return Err(format!("LPC order {} needs more than {} samples", order, fft_source.len()).into());
+        }
+        Some(order) => lpc(fft_source, order).map(|model| {
+            debug!("LPC envelope: order {}", order);
+            envelope(&model, &fft_spectrum.frequencies, source_rate)
+        }),
+        None => None,
+    };
+    let reference_curve = reference.as_ref().map(|(label, curve)| (label.as_str(), fit_reference(&fft_spectrum, curve)));
+    let reference_curve = reference_curve.as_ref().map(|(label, points)| (*label, &points[..]));
+    plot_fft(&fft_spectrum, lpc_envelope.as_deref(), reference_curve, "fft_spectrum.png")?;
+    info!("FFT spectrum plot saved to 'fft_spectrum.png'");
+
+    if let Some(fraction) = args.spectrum.bands() {
+        // bands need true frequencies, so they use the full-rate mono mix
+        let bands = band_levels(&compute_spectrum(&mono, fmt.sample_rate), fraction);
+        println!("Band levels (dB re loudest band):");
+        for band in &bands {
+            println!("  {:>7} Hz: {:6.1} dB", format_hz(band.nominal), band.level_db);
+        }
+        plot_bands(&bands, "bands.png")?;
+        info!("Band plot saved to 'bands.png'");
+    }
+
+    // the spectrogram runs on the full-rate mono mix
+    let (spectrogram, caption) = if let Some(sizes) = args.stft.multi_res() {
+        debug!("STFT: windows {:?}, hop {}", sizes, config.hop);
+        (Spectrogram::multi_resolution(&mono, fmt.sample_rate, sizes, config.hop), "Multi-resolution Spectrogram")
+    } else {
+        debug!("STFT: window {}, hop {} ({:.0}% overlap)",
+            config.nfft, config.hop, config.overlap() * 100.0);
+        if args.synchrosqueeze {
+            (Spectrogram::synchrosqueezed(&mono, fmt.sample_rate, config), "Synchrosqueezed STFT")
+        } else {
+            (args.stft.spectrogram(&mono, fmt.sample_rate, config), "Spectrogram")
+        }
+    };
+    plot_spectrogram(&spectrogram, caption, &[], &cue_times, "spectrogram.png")?;
+    info!("Spectrogram plot saved to 'spectrogram.png'");
+
+    Ok(())
+}
+
+// The reference curve on the spectrum's bins, in its units, shifted so its
+// mean level in dB matches the spectrum's over the bins with content.
+fn fit_reference(spectrum: &Spectrum, curve: &TargetCurve) -> Vec<(f32, f32)> {
+    let per_decade = match spectrum.scale {
+        SpectrumScale::Raw | SpectrumScale::Amplitude => 20.0,
+        SpectrumScale::Power | SpectrumScale::Psd => 10.0,
+    };
+    let peak = spectrum.magnitudes.iter().cloned().fold(0f32, f32::max);
+    let floor = peak * 10f32.powf(-REFERENCE_RANGE_DB / per_decade);
+    let bins = || spectrum.frequencies.iter().zip(&spectrum.magnitudes).filter(|(&f, _)| f >= REFERENCE_MIN_HZ);
+
+    let (mut offset, mut count) = (0.0, 0);
+    for (&f, &m) in bins().filter(|(_, &m)| m > floor && m > 0.0) {
+        offset += per_decade * m.log10() - curve.level_db(f);
+        count += 1;
+    }
+    if count == 0 {
+        return Vec::new();
+    }
+    let offset = offset / count as f32;
+    bins().map(|(&f, _)| (f, 10f32.powf((curve.level_db(f) + offset) / per_decade))).collect()
+}
+
+fn plot_fft(spectrum: &Spectrum, envelope: Option<&[f32]>, reference: Option<(&str, &[(f32, f32)])>, output_path: &str) -> Result<(), Box<dyn Error>> {
+    debug!("FFT size: {}", spectrum.fft_size);
+
+    // Identify top 5 frequencies
+    let top_five = spectrum.top_peaks(5);
+
+    println!("Top 5 Frequencies:");
+    for (freq, mag) in &top_five {
+        println!(
+            "Frequency: {:.2} Hz, Magnitude: {:.4}, Level: {:.2} dBFS",
+            freq, mag, 20.0 * spectrum.sine_amplitude(*mag).log10()
+        );
+    }
+
+    // Plot the FFT magnitude spectrum
+    let y_desc = match spectrum.scale {
+        SpectrumScale::Raw => "Magnitude",
+        SpectrumScale::Amplitude => "Amplitude (re full scale)",
+        SpectrumScale::Power => "Power (FS^2)",
+        SpectrumScale::Psd => "PSD (FS^2/Hz)",
+    };
+    plot_fft_spectrum(&spectrum.frequencies, &spectrum.magnitudes, &top_five, envelope, reference, y_desc, output_path)?;
+
+    Ok(())
+}
+
+/// Plots the FFT magnitude spectrum, highlights the top 5 frequencies, and labels them.
+///
+/// # Arguments
+///
+/// * `frequencies` - A slice of frequencies corresponding to FFT bins.
+/// * `magnitudes` - A slice of magnitudes corresponding to FFT bins.
+/// * `top_five` - A slice of tuples containing the top 5 frequencies and their magnitudes.
+/// * `envelope` - An optional LPC envelope on the same bins, drawn scaled to the spectrum's peak.
+/// * `reference` - An optional labelled reference curve, already in the spectrum's units.
+/// * `y_desc` - The y axis description, naming the magnitudes' units.
+/// * `output_path` - The file path where the FFT plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+fn plot_fft_spectrum(frequencies: &[f32], magnitudes: &[f32], top_five: &[(f32, f32)], envelope: Option<&[f32]>, reference: Option<(&str, &[(f32, f32)])>, y_desc: &str, output_path: &str) -> Result<(), Box<dyn Error>> {
+    // Define the dimensions of the plot (High resolution for better quality)
+    let root_area = BitMapBackend::new(output_path, (1920, 1080)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    // Frequencies are placed on the chosen frequency axis
+    let scale = freq_scale();
+    let x = |f: f32| scale.from_hz(f);
+
+    // Determine the maximum magnitude for y-axis scaling
+    let max_magnitude = magnitudes.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    // Alternatively, use logarithmic scaling if desired
+
+    // Create a chart context
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("FFT Magnitude Spectrum", caption_font(40))
+        .margin(20)
+        .x_label_area_size(80)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x(0.0)..x(frequencies.last().cloned().unwrap_or(0.0)), 0f32..max_magnitude)?;
+
+    // Configure the mesh (axes) to eliminate extra padding; a warped
+    // frequency axis is still labelled in Hz
+    let label_x = |value: &f32| format!("{:.0}", scale.to_hz(*value));
+    let mut mesh = chart.configure_mesh();
+    if scale != FreqScale::Linear {
+        mesh.x_label_formatter(&label_x);
+    }
+    themed(&mut mesh)
+        .disable_mesh() // Disable grid lines for cleaner look
+        .x_desc(freq_desc(scale))
+        .y_desc(y_desc)
+        .light_line_style(GRAY.mix(0.3))
+        .draw()?;
+
+    // Prepare the data as plot points
+    let plot_points: Vec<(f32, f32)> = frequencies.iter()
+        .map(|&f| x(f))
+        .zip(magnitudes.iter().cloned())
+        .collect();
+
+    // Draw the FFT magnitude line
+    chart.draw_series(LineSeries::new(
+        plot_points,
+        RGBColor(255, 0, 0).stroke_width(2), // Red color with stroke width 2
+    ))?
+    .label("Magnitude")
+    .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RGBColor(255, 0, 0)));
+
+    // Overlay the LPC envelope, scaled so its peak meets the spectrum's
+    if let Some(envelope) = envelope {
+        let envelope_max = envelope.iter().cloned().fold(f32::MIN_POSITIVE, f32::max);
+        let gain = max_magnitude / envelope_max;
+        chart.draw_series(LineSeries::new(
+            frequencies.iter().map(|&f| x(f)).zip(envelope.iter().map(|&e| e * gain)),
+            RGBColor(0, 150, 60).stroke_width(3),
+        ))?
+        .label("LPC envelope")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RGBColor(0, 150, 60)));
+    }
+
+    // Overlay the reference curve where it fits under the spectrum's peak
+    if let Some((label, points)) = reference {
+        chart.draw_series(LineSeries::new(
+            points.iter().filter(|&&(_, m)| m <= max_magnitude).map(|&(f, m)| (x(f), m)),
+            GRAY.stroke_width(3),
+        ))?
+        .label(label)
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], GRAY));
+    }
+
+    // Highlight and label the top 5 frequencies
+    for &(freq, mag) in top_five {
+        // Draw a blue vertical line at the top frequency
+        chart.draw_series(LineSeries::new(
+            vec![(x(freq), 0.0), (x(freq), mag)],
+            RGBColor(0, 0, 255).stroke_width(2), // Blue color with stroke width 2
+        ))?
+        .label(format!("{:.1} Hz", freq))
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RGBColor(0, 0, 255)));
+
+        // Add text labels for top frequencies at the bottom
+        chart.draw_series(vec![
+            Text::new(
+                format!("{:.1} Hz", freq),
+                (x(freq), 0.0), // Position at the bottom of the plot
+                caption_font(20),
+            )
+        ])?;
+    }
+
+    // Draw the legend
+    chart
+        .configure_series_labels()
+        .label_font(caption_font(15))
+        .background_style(background().mix(0.8))
+        .border_style(foreground())
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()?;
+
+    Ok(())
+}
+/// Plots fractional-octave band levels as bars.
+///
+/// # Arguments
+///
+/// * `bands` - The bands to draw, lowest first.
+/// * `output_path` - The file path where the band plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+fn plot_bands(bands: &[Band], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, (1920, 1080)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    // bars rise from a fixed floor so quiet bands remain visible
+    let floor = bands.iter().map(|b| b.level_db).fold(0.0, f32::min).max(-90.0) - 10.0;
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Band Levels", caption_font(40))
+        .margin(20)
+        .x_label_area_size(60)
+        .y_label_area_size(80)
+        .build_cartesian_2d((0..bands.len()).into_segmented(), floor..0f32)?;
+
+    themed(&mut chart.configure_mesh())
+        .disable_x_mesh()
+        .x_labels(bands.len())
+        .x_label_formatter(&|x| match x {
+            SegmentValue::CenterOf(i) => bands.get(*i).map_or(String::new(), |b| format_hz(b.nominal)),
+            _ => String::new(),
+        })
+        .x_desc("Band center (Hz)")
+        .y_desc("Level (dB re loudest band)")
+        .draw()?;
+
+    chart.draw_series(bands.iter().enumerate().map(|(i, band)| {
+        Rectangle::new(
+            [(SegmentValue::Exact(i), floor), (SegmentValue::Exact(i + 1), band.level_db.max(floor))],
+            BLUE.mix(0.6).filled(),
+        )
+    }))?;
+
+    Ok(())
+}
+
+// band label in the usual short form: 31.5, 250, 1k, 12.5k
+fn format_hz(hz: f32) -> String {
+    if hz >= 1000.0 {
+        format!("{}k", hz / 1000.0)
+    } else {
+        format!("{}", hz)
+    }
+}
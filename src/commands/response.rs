@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::bands::{band_layout, BandFraction};
+use fft_rs::stft::StftConfig;
+use fft_rs::transfer::relative_response;
+use plotters::style::RGBColor;
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::{plot_lines, Series};
+
+#[derive(Args)]
+pub struct ResponseArgs {
+    /// The original (dry) signal
+    reference: PathBuf,
+    /// The same signal after the device or plugin under test
+    processed: PathBuf,
+    /// STFT window size for the averaged spectra
+    #[arg(long, default_value_t = 4096)]
+    nfft: usize,
+    /// Where to write the response plot
+    #[arg(long, default_value = "response.png")]
+    plot: PathBuf,
+}
+
+/// Compare two files' spectra and plot the transfer magnitude.
+pub fn run(args: ResponseArgs) -> Result<(), Box<dyn Error>> {
+    let (reference, rate) = load_mono(&args.reference)?;
+    let (processed, processed_rate) = load_mono(&args.processed)?;
+    if rate != processed_rate {
+        return Err(format!(
+            "sample rates differ: reference {} Hz, processed {} Hz", rate, processed_rate
+        ).into());
+    }
+
+    let config = StftConfig::with_overlap(args.nfft, 0.5);
+    config.validate(reference.len().min(processed.len()))?;
+    let response = relative_response(&reference, &processed, rate, config);
+    if response.is_empty() {
+        return Err("reference has no usable energy".into());
+    }
+
+    println!("Response by octave band:");
+    for band in band_layout(BandFraction::Octave, rate as f32 / 2.0) {
+        let gains: Vec<f32> = response.iter()
+            .filter(|(f, _)| *f >= band.lower && *f < band.upper)
+            .map(|&(_, g)| g)
+            .collect();
+        if !gains.is_empty() {
+            println!("  {:>6} Hz: {:+6.2} dB", band.nominal, gains.iter().sum::<f32>() / gains.len() as f32);
+        }
+    }
+
+    let series = [Series { label: "Processed / reference", points: &response, color: RGBColor(0, 90, 200) }];
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("Frequency Response", "Frequency (Hz)", "Gain (dB)", &series, true, path)?;
+    info!("Response plot saved to '{}'", path);
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::reverb::{octave_band_responses, reverb_times, schroeder_decay};
+use fft_rs::sample::mix_to_mono;
+use fft_rs::wav::WavFile;
+use plotters::style::{Color, Palette, Palette99, RGBColor};
+use tracing::info;
+
+use crate::plots::{plot_lines, Series};
+
+// the decay plot keeps at most this many points per curve
+const PLOT_POINTS: usize = 2000;
+
+#[derive(Args)]
+pub struct Rt60Args {
+    /// Impulse response WAV file
+    input: PathBuf,
+    /// Also plot the decay curves to decay.png in the working directory
+    #[arg(long)]
+    plot: bool,
+}
+
+/// Report EDT/T20/T30 for the broadband response and each octave band.
+pub fn run(args: Rt60Args) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let ir = mix_to_mono(&wav.to_normalized_samples()?, fmt.num_channels.max(1) as usize);
+    let rate = fmt.sample_rate;
+
+    let mut curves = vec![("Broadband".to_string(), schroeder_decay(&ir))];
+    for (nominal, band) in octave_band_responses(&ir, rate) {
+        curves.push((format!("{} Hz", nominal), schroeder_decay(&band)));
+    }
+
+    let secs = |t: Option<f32>| t.map_or("   -   ".to_string(), |t| format!("{:6.3}s", t));
+    println!("{:>10}  {:>7}  {:>7}  {:>7}", "Band", "EDT", "T20", "T30");
+    for (label, decay) in &curves {
+        let times = reverb_times(decay, rate);
+        println!("{:>10}  {}  {}  {}", label, secs(times.edt), secs(times.t20), secs(times.t30));
+    }
+
+    if args.plot {
+        let points: Vec<Vec<(f32, f32)>> = curves.iter().map(|(_, decay)| {
+            let step = (decay.len() / PLOT_POINTS).max(1);
+            decay.iter().enumerate().step_by(step)
+                .map(|(i, &d)| (i as f32 / rate as f32, d.max(-80.0)))
+                .collect()
+        }).collect();
+        let series: Vec<Series> = curves.iter().zip(&points).enumerate().map(|(i, ((label, _), points))| {
+            let c = Palette99::pick(i).to_rgba();
+            Series { label, points, color: RGBColor(c.0, c.1, c.2) }
+        }).collect();
+        plot_lines("Schroeder Decay", "Time (s)", "Level (dB)", &series, false, "decay.png")?;
+        info!("Decay plot saved to 'decay.png'");
+    }
+
+    Ok(())
+}
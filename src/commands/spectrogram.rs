@@ -0,0 +1,409 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use fft_rs::animation::VideoWriter;
+use fft_rs::sample::mix_to_mono;
+use fft_rs::spill::{SpillMatrix, SpillWriter};
+use fft_rs::stft::{StftConfig, StftStream};
+use fft_rs::wav::LazyWavFile;
+use plotters::prelude::*;
+use tracing::{debug, info, warn};
+
+use super::{format_time, parse_size, parse_time, BackendArg, StftArgs};
+use crate::plots::{freq_scale, heat_color, plot_spectrogram_tile, spectrogram_area, spectrogram_rows, SpectrogramTile};
+
+// frames decoded at a time
+const BLOCK_FRAMES: u64 = 1 << 16;
+
+#[derive(Args)]
+pub struct SpectrogramArgs {
+    /// Input WAV file
+    input: PathBuf,
+    #[command(flatten)]
+    stft: StftArgs,
+    /// Split the time axis into tiles of this length (SECS, M:SS or
+    /// H:MM:SS), each written as soon as its frames are in
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    tile: Option<f64>,
+    /// Also join the tiles' heat maps side by side into one wide PNG,
+    /// without axes
+    #[arg(long, value_name = "PATH", requires = "tile")]
+    stitch: Option<PathBuf>,
+    /// Levels shown, in dB below full scale; all tiles share this scale
+    #[arg(long, default_value_t = 100.0)]
+    range: f32,
+    /// Also render a video of the spectrogram scrolling past a playhead,
+    /// with the input's audio; needs ffmpeg on PATH
+    #[arg(long, value_name = "PATH")]
+    video: Option<PathBuf>,
+    /// Video frames per second
+    #[arg(long, default_value_t = 30, requires = "video")]
+    fps: u16,
+    /// Time visible across a video frame, centered on the playhead
+    #[arg(long, value_name = "TIME", default_value = "10", value_parser = parse_time, requires = "video")]
+    span: f64,
+    /// Video frame size in pixels; both must be even
+    #[arg(long, default_value = "1280x720", value_parser = parse_size, requires = "video")]
+    video_size: (u16, u16),
+    /// Where to write the spectrogram; with --tile, the tile number is
+    /// appended to the name (spectrogram_0001.png, ...)
+    #[arg(long, default_value = "spectrogram.png")]
+    plot: PathBuf,
+}
+
+// Pools frames into the cells of the current tile and renders each tile
+// once it is complete, so only one tile's cells are ever held
+struct Tiler<'a> {
+    args: &'a SpectrogramArgs,
+    tiles: usize,
+    tile_frames: usize, // STFT frames per tile
+    frame_secs: f32,    // seconds from one frame to the next
+    row_bins: Vec<Range<usize>>, // bins pooled into each cell row
+    gain: f32,                   // magnitude to full-scale amplitude
+    tile: SpectrogramTile,
+    grid: Vec<f32>,
+    index: usize, // number of the current tile
+    frame: usize, // frames pooled into it so far
+    stitch: Option<SpillWriter>,
+}
+
+impl Tiler<'_> {
+    fn push(&mut self, frame: &[f32]) -> Result<(), Box<dyn Error>> {
+        let (cols, rows) = (self.tile.cols, self.tile.rows);
+        let col = self.frame * cols / self.tile_frames;
+        for (cell, range) in self.grid[col * rows..(col + 1) * rows].iter_mut().zip(&self.row_bins) {
+            *cell = frame[range.clone()].iter().fold(*cell, |a, &b| a.max(b));
+        }
+        self.frame += 1;
+        if self.frame == self.tile_frames {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    // render the frames pooled so far as the current tile and start the next
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.frame == 0 {
+            return Ok(());
+        }
+        let (cols, rows) = (self.tile.cols, self.tile.rows);
+        // columns past the last frame of a short final tile stay blank
+        let filled = (self.frame * cols).div_ceil(self.tile_frames);
+        for (level, &mag) in self.tile.levels.iter_mut().zip(&self.grid) {
+            *level = 20.0 * (mag * self.gain + 1e-9).log10();
+        }
+        self.tile.levels[filled * rows..].fill(f32::NAN);
+        self.tile.start = (self.index * self.tile_frames) as f32 * self.frame_secs;
+        self.tile.end = self.tile.start + self.tile_frames as f32 * self.frame_secs;
+
+        let path = self.tile_path();
+        let caption = match self.args.tile {
+            Some(_) => format!("Spectrogram {} - {}", format_time(self.tile.start as f64), format_time(self.tile.end as f64)),
+            None => "Spectrogram".to_string(),
+        };
+        let path_str = path.to_str().ok_or("plot path is not valid UTF-8")?;
+        plot_spectrogram_tile(&self.tile, -self.args.range, 0.0, &caption, path_str)?;
+        match self.args.tile {
+            Some(_) => info!("Spectrogram tile {}/{} saved to '{}'", self.index + 1, self.tiles, path.display()),
+            None => info!("Spectrogram saved to '{}'", path.display()),
+        }
+
+        // keep the cells, top row first, for stitching
+        if let Some(stitch) = &mut self.stitch {
+            let mut row = vec![f32::NAN; cols];
+            for r in (0..rows).rev() {
+                for (c, value) in row.iter_mut().enumerate() {
+                    *value = self.tile.levels[c * rows + r];
+                }
+                stitch.push_row(&row)?;
+            }
+        }
+
+        self.grid.fill(0.0);
+        self.index += 1;
+        self.frame = 0;
+        Ok(())
+    }
+
+    fn tile_path(&self) -> PathBuf {
+        if self.args.tile.is_none() {
+            return self.args.plot.clone();
+        }
+        let stem = self.args.plot.file_stem().and_then(|s| s.to_str()).unwrap_or("spectrogram");
+        let extension = self.args.plot.extension().and_then(|e| e.to_str()).unwrap_or("png");
+        self.args.plot.with_file_name(format!("{}_{:04}.{}", stem, self.index + 1, extension))
+    }
+}
+
+// Turns STFT frames into columns of pixels, one per `col_secs` of audio,
+// and renders each video frame as soon as every column it shows is in.
+// Only the columns of one frame's width are kept.
+struct ScrollingVideo {
+    writer: VideoWriter,
+    width: usize,
+    height: usize,
+    fps: f64,
+    frame_count: u64, // video frames in the whole input
+    col_secs: f64,    // seconds of audio per pixel column
+    frame_center: f64, // center of the first STFT frame's window, in seconds
+    frame_secs: f64,  // seconds from one STFT frame to the next
+    row_bins: Vec<Range<usize>>, // bins pooled into each pixel row, bottom first
+    gain: f32,
+    min_db: f32,
+    stft_frames: u64, // STFT frames seen so far
+    levels: Vec<f32>, // per-bin maximum of the frames in the pending column
+    pending: Option<i64>,
+    columns: VecDeque<Vec<u8>>, // RGB, top row first
+    first_column: i64,          // index of the front of `columns`
+    next_frame: u64,
+    pixels: Vec<u8>,
+}
+
+impl ScrollingVideo {
+    fn create(args: &SpectrogramArgs, path: &Path, config: StftConfig, rate: u32, len: usize, gain: f32) -> Result<Self, Box<dyn Error>> {
+        if args.fps == 0 || args.span <= 0.0 {
+            return Err("fps and span must be positive".into());
+        }
+        let (width, height) = args.video_size;
+        let duration = len as f64 / rate as f64;
+        let writer = VideoWriter::spawn(path, width, height, args.fps, Some(&args.input))?;
+        let (width, height) = (width as usize, height as usize);
+        Ok(ScrollingVideo {
+            writer,
+            width,
+            height,
+            fps: args.fps as f64,
+            frame_count: (duration * args.fps as f64).ceil() as u64,
+            col_secs: args.span / width as f64,
+            frame_center: config.nfft as f64 / 2.0 / rate as f64,
+            frame_secs: config.hop as f64 / rate as f64,
+            row_bins: freq_scale().bin_ranges(rate as f32 / 2.0, config.nfft / 2 + 1, height),
+            gain,
+            min_db: -args.range,
+            stft_frames: 0,
+            levels: vec![0.0; config.nfft / 2 + 1],
+            pending: None,
+            columns: VecDeque::new(),
+            first_column: 0,
+            next_frame: 0,
+            pixels: vec![0; width * height * 3],
+        })
+    }
+
+    fn push(&mut self, frame: &[f32]) -> Result<(), Box<dyn Error>> {
+        let time = self.frame_center + self.stft_frames as f64 * self.frame_secs;
+        self.stft_frames += 1;
+        let column = (time / self.col_secs).floor() as i64;
+        match self.pending {
+            Some(pending) if pending == column => {
+                for (level, &mag) in self.levels.iter_mut().zip(frame) {
+                    *level = level.max(mag);
+                }
+                return Ok(());
+            }
+            Some(pending) => {
+                self.complete_column();
+                // columns between two STFT frames hold the earlier one
+                for _ in pending + 1..column {
+                    let held = self.columns.back().cloned().unwrap_or_default();
+                    self.columns.push_back(held);
+                }
+            }
+            None => self.first_column = column,
+        }
+        self.levels.copy_from_slice(frame);
+        self.pending = Some(column);
+        self.render_ready(false)
+    }
+
+    // color the pending column from its pooled magnitudes
+    fn complete_column(&mut self) {
+        let mut column = Vec::with_capacity(self.height * 3);
+        for range in self.row_bins.iter().rev() {
+            let mag = self.levels[range.clone()].iter().fold(0f32, |a, &b| a.max(b));
+            let db = (20.0 * (mag * self.gain + 1e-9).log10()).max(self.min_db);
+            let color = heat_color(db, self.min_db, 0.0);
+            column.extend_from_slice(&[color.0, color.1, color.2]);
+        }
+        self.columns.push_back(column);
+    }
+
+    // first column shown by video frame `frame`, whose playhead is centered
+    fn left_column(&self, frame: u64) -> i64 {
+        let time = frame as f64 / self.fps;
+        (time / self.col_secs).floor() as i64 - (self.width / 2) as i64
+    }
+
+    // render the frames whose columns are all in; at the end, all the rest
+    fn render_ready(&mut self, end: bool) -> Result<(), Box<dyn Error>> {
+        let complete = self.first_column + self.columns.len() as i64;
+        while self.next_frame < self.frame_count {
+            let left = self.left_column(self.next_frame);
+            if !end && left + self.width as i64 > complete {
+                break;
+            }
+            self.render(left, self.next_frame as f64 / self.fps)?;
+            self.next_frame += 1;
+            // drop the columns that have scrolled out of view
+            let left = self.left_column(self.next_frame);
+            while self.first_column < left && !self.columns.is_empty() {
+                self.columns.pop_front();
+                self.first_column += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, left: i64, time: f64) -> Result<(), Box<dyn Error>> {
+        // before the first frame and past the end reads as silence
+        let blank = heat_color(self.min_db, self.min_db, 0.0);
+        for x in 0..self.width {
+            let column = usize::try_from(left + x as i64 - self.first_column).ok().and_then(|c| self.columns.get(c));
+            for y in 0..self.height {
+                let pixel = &mut self.pixels[(y * self.width + x) * 3..][..3];
+                match column {
+                    Some(column) => pixel.copy_from_slice(&column[y * 3..y * 3 + 3]),
+                    None => pixel.copy_from_slice(&[blank.0, blank.1, blank.2]),
+                }
+            }
+        }
+
+        let (width, height) = (self.width as i32, self.height as i32);
+        {
+            let root_area = BitMapBackend::with_buffer(&mut self.pixels, (width as u32, height as u32)).into_drawing_area();
+            let playhead = width / 2;
+            root_area.draw(&PathElement::new(vec![(playhead, 0), (playhead, height)], WHITE.stroke_width(2)))?;
+            root_area.draw(&Text::new(format_time(time), (10, 10), ("sans-serif", 20).into_font().color(&WHITE)))?;
+            root_area.present()?;
+        }
+        self.writer.push(&self.pixels)?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<u64, Box<dyn Error>> {
+        if self.pending.take().is_some() {
+            self.complete_column();
+        }
+        self.render_ready(true)?;
+        self.writer.finish()?;
+        Ok(self.next_frame)
+    }
+}
+
+/// Render the spectrogram of the mono mix straight from the file, a block
+/// at a time. The STFT frames are pooled into plot cells as they arrive,
+/// so even hours of audio never have their whole STFT in memory. The same
+/// frames drive the --video renderer.
+pub fn run(args: SpectrogramArgs) -> Result<(), Box<dyn Error>> {
+    if args.stft.multi_res().is_some() {
+        return Err("--multi-res needs the whole signal in memory; use plot instead".into());
+    }
+    if args.stft.backend == BackendArg::Gpu {
+        warn!("the GPU backend transforms the whole signal at once; streaming on the CPU instead");
+    }
+    if args.range <= 0.0 {
+        return Err("range must be positive".into());
+    }
+
+    let mut lazy = LazyWavFile::open(File::open(&args.input)?)?;
+    let fmt = lazy.wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let (rate, channels) = (fmt.sample_rate, fmt.num_channels.max(1) as usize);
+    let len = lazy.wav.num_frames().unwrap_or(0) as usize;
+    let config = args.stft.config(len)?;
+    let frames = config.num_frames(len);
+    let frame_secs = config.hop as f32 / rate as f32;
+    let tile_frames = match args.tile {
+        Some(secs) if secs <= 0.0 => return Err("tile length must be positive".into()),
+        Some(secs) => ((secs / frame_secs as f64).round() as usize).max(1),
+        None => frames,
+    };
+    let tiles = frames.div_ceil(tile_frames);
+    debug!("STFT: window {}, hop {}; {} frames in {} tile(s)", config.nfft, config.hop, frames, tiles);
+
+    let bins = config.nfft / 2 + 1;
+    let nyquist = rate as f32 / 2.0;
+    let (width, height) = spectrogram_area()?;
+    let row_bins = spectrogram_rows(nyquist, bins, height);
+    let (cols, rows) = (tile_frames.min(width as usize).max(1), row_bins.len());
+    let window = args.stft.window().coefficients(config.nfft);
+    let gain = 2.0 / window.iter().sum::<f32>();
+    let mut tiler = Tiler {
+        args: &args,
+        tiles,
+        tile_frames,
+        frame_secs,
+        row_bins,
+        gain,
+        tile: SpectrogramTile { start: 0.0, end: 0.0, nyquist, cols, rows, levels: vec![0.0; cols * rows] },
+        grid: vec![0.0; cols * rows],
+        index: 0,
+        frame: 0,
+        stitch: args.stitch.as_ref().map(|_| SpillWriter::create(cols)).transpose()?,
+    };
+
+    let mut video = match &args.video {
+        Some(path) => Some(ScrollingVideo::create(&args, path, config, rate, len, gain)?),
+        None => None,
+    };
+
+    let mut stream = StftStream::new(config, args.stft.window());
+    for block in lazy.blocks(BLOCK_FRAMES) {
+        let mut result = Ok(());
+        stream.push(&mix_to_mono(&block?, channels), |frame| {
+            if result.is_ok() {
+                result = tiler.push(frame);
+            }
+            if let (Ok(()), Some(video)) = (&result, &mut video) {
+                result = video.push(frame);
+            }
+        });
+        result?;
+    }
+    tiler.flush()?;
+    if let (Some(path), Some(video)) = (&args.video, video) {
+        let frames = video.finish()?;
+        info!("Video of {} frames at {} fps saved to '{}'", frames, args.fps, path.display());
+    }
+
+    if let (Some(path), Some(stitch)) = (&args.stitch, tiler.stitch.take()) {
+        let filled_last = ((frames - (tiles - 1) * tile_frames) * cols).div_ceil(tile_frames);
+        let width = (tiles - 1) * cols + filled_last;
+        write_stitched(path, &stitch.finish()?, tiles, rows, width, -args.range)?;
+        info!("Stitched {} tiles into '{}' ({}x{})", tiles, path.display(), width, rows);
+    }
+
+    Ok(())
+}
+
+// One row of pixels per cell row, taken from every tile in turn and
+// streamed into the PNG, so the wide image is never whole in memory
+fn write_stitched(path: &Path, cells: &SpillMatrix, tiles: usize, rows: usize, width: usize, min_db: f32) -> Result<(), Box<dyn Error>> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width as u32, rows as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+
+    let mut reader = cells.reader()?;
+    let mut levels = Vec::new();
+    let mut pixels = Vec::with_capacity(width * 3);
+    for r in 0..rows {
+        pixels.clear();
+        for t in 0..tiles {
+            reader.read_rows(t * rows + r, 1, &mut levels)?;
+            for &db in &levels {
+                let color = heat_color(db.max(min_db), min_db, 0.0);
+                pixels.extend_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+        pixels.truncate(width * 3);
+        stream.write_all(&pixels)?;
+    }
+    stream.finish()?;
+    Ok(())
+}
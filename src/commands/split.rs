@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::dither::Dither;
+use fft_rs::silence::{detect_silence, split_points};
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_encoded_file, write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+#[derive(Args)]
+pub struct SplitArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Directory for the numbered track files (created if missing)
+    output_dir: PathBuf,
+    /// Level below which audio counts as silence, in dBFS
+    #[arg(long, default_value_t = -50.0, allow_hyphen_values = true)]
+    threshold: f32,
+    /// Shortest silence, in seconds, that separates two tracks
+    #[arg(long, default_value_t = 2.0)]
+    min_gap: f32,
+    /// Drop tracks shorter than this many seconds
+    #[arg(long, default_value_t = 1.0)]
+    min_length: f32,
+}
+
+/// Split a long recording into tracks at silent gaps, writing each track's
+/// frames unchanged into `<stem>_NN.wav`.
+pub fn run(args: SplitArgs) -> Result<(), Box<dyn Error>> {
+    let wav = WavFile::parse(&mut File::open(&args.input)?)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let bytes = wav.data.as_ref().and_then(|d| d.bytes()).ok_or("input has no data chunk")?;
+    let samples = wav.to_normalized_samples()?;
+
+    let channels = fmt.num_channels.max(1) as usize;
+    let total = samples.len() / channels;
+    let gaps = detect_silence(&samples, channels, fmt.sample_rate, args.threshold, args.min_gap);
+    let min_frames = (args.min_length * fmt.sample_rate as f32) as usize;
+
+    let spec = WavSpec {
+        channels: channels as u16,
+        sample_rate: fmt.sample_rate,
+        format: SampleFormat::matching(fmt),
+    };
+    let verbatim = spec.format.bits() == fmt.bits_per_sample;
+    let block = fmt.block_align.max(1) as usize;
+    let stem = args.input.file_stem().map_or("track".into(), |s| s.to_string_lossy());
+
+    fs::create_dir_all(&args.output_dir)?;
+
+    let tracks: Vec<_> = split_points(&gaps, total).into_iter()
+        .filter(|region| region.len() >= min_frames)
+        .collect();
+
+    for (i, region) in tracks.iter().enumerate() {
+        let path = args.output_dir.join(format!("{}_{:02}.wav", stem, i + 1));
+        if verbatim {
+            write_encoded_file(&path, spec, &bytes[region.start * block..region.end * block])?;
+        } else {
+            let slice = &samples[region.start * channels..region.end * channels];
+            write_wav_file(&path, spec, slice, Dither::Off)?;
+        }
+
+        let rate = fmt.sample_rate as f32;
+        println!(
+            "Track {:2}: {:8.2} s - {:8.2} s -> {}",
+            i + 1, region.start as f32 / rate, region.end as f32 / rate, path.display()
+        );
+    }
+
+    info!("Found {} silent gaps, wrote {} tracks", gaps.len(), tracks.len());
+
+    Ok(())
+}
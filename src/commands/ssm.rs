@@ -0,0 +1,142 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use fft_rs::stft::{Spectrogram, StftConfig};
+use fft_rs::structure::{chroma, mfcc, pool, self_similarity};
+use plotters::prelude::*;
+use tracing::{info, instrument};
+
+use super::load_mono;
+use crate::plots::{background, caption_font, heat_color, themed};
+
+const NFFT: usize = 4096;
+const HOP: usize = 2048;
+const MFCC_COUNT: usize = 13;
+
+#[derive(Args)]
+pub struct SsmArgs {
+    /// Input WAV file
+    input: PathBuf,
+    #[command(flatten)]
+    structure: StructureArgs,
+    /// Where to write the matrix image
+    #[arg(long, default_value = "ssm.png")]
+    plot: PathBuf,
+}
+
+/// Frame feature options shared by the structure analyses.
+#[derive(Args)]
+pub struct StructureArgs {
+    /// Frame features to compare: chroma follows harmony, MFCCs follow timbre
+    #[arg(long, value_enum, default_value_t = FeatureArg::Chroma)]
+    features: FeatureArg,
+    /// Average the features over blocks of this many seconds
+    #[arg(long, default_value_t = 0.5)]
+    resolution: f32,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FeatureArg {
+    Chroma,
+    Mfcc,
+}
+
+/// Pooled feature vectors of a signal.
+pub struct Features {
+    pub vectors: Vec<Vec<f32>>,
+    pub step: f32, // seconds covered by each vector
+    pub name: &'static str,
+}
+
+impl Default for StructureArgs {
+    fn default() -> Self {
+        StructureArgs { features: FeatureArg::Chroma, resolution: 0.5 }
+    }
+}
+
+impl StructureArgs {
+    /// The pooled feature vectors of a mono signal.
+    pub fn features(&self, samples: &[f32], rate: u32) -> Result<Features, Box<dyn Error>> {
+        let config = StftConfig { nfft: NFFT, hop: HOP };
+        config.validate(samples.len())?;
+        if self.resolution <= 0.0 {
+            return Err("resolution must be positive".into());
+        }
+
+        let spectrogram = Spectrogram::compute(samples, rate, config);
+        let (features, name) = match self.features {
+            FeatureArg::Chroma => (chroma(&spectrogram), "Chroma"),
+            FeatureArg::Mfcc => (mfcc(&spectrogram, MFCC_COUNT), "MFCC"),
+        };
+        let block = ((self.resolution * rate as f32 / HOP as f32).round() as usize).max(1);
+        Ok(Features { vectors: pool(&features, block), step: (block * HOP) as f32 / rate as f32, name })
+    }
+}
+
+/// Compute the self-similarity matrix of the mono mix and render it.
+pub fn run(args: SsmArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let Features { vectors, step, name } = args.structure.features(&samples, rate)?;
+    let matrix = self_similarity(&vectors);
+    println!("{} x {} matrix of {} features, {:.2} s per cell", matrix.len(), matrix.len(), name, step);
+
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_matrix(&matrix, step, &format!("Self-similarity ({})", name), path)?;
+    info!("Self-similarity plot saved to '{}'", path);
+
+    Ok(())
+}
+
+/// Plots a square matrix as a heat map, with time on both axes.
+///
+/// # Arguments
+///
+/// * `matrix` - The similarities, `matrix[i][j]` for cells i and j.
+/// * `step` - The seconds covered by one cell.
+/// * `caption` - The chart title.
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+fn plot_matrix(matrix: &[Vec<f32>], step: f32, caption: &str, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, (1080, 1080)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    let duration = matrix.len() as f32 * step;
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption(caption, caption_font(40))
+        .margin(20)
+        .x_label_area_size(60)
+        .y_label_area_size(80)
+        .build_cartesian_2d(0f32..duration, 0f32..duration)?;
+
+    themed(&mut chart.configure_mesh())
+        .disable_mesh()
+        .x_desc("Time (s)")
+        .y_desc("Time (s)")
+        .draw()?;
+
+    // Pool cells down to at most one per pixel (keeping the mean)
+    let (width, _) = chart.plotting_area().dim_in_pixel();
+    let n = matrix.len();
+    let cells = n.min(width as usize).max(1);
+    let mut grid = vec![(0f32, 0u32); cells * cells];
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            let cell = &mut grid[(i * cells / n) * cells + j * cells / n];
+            cell.0 += value;
+            cell.1 += 1;
+        }
+    }
+    let means: Vec<f32> = grid.iter().map(|&(sum, count)| sum / count.max(1) as f32).collect();
+    let (lo, hi) = means.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let hi = if hi > lo { hi } else { lo + 1.0 };
+
+    let size = duration / cells as f32;
+    chart.draw_series(means.iter().enumerate().map(|(index, &value)| {
+        let (x, y) = ((index / cells) as f32 * size, (index % cells) as f32 * size);
+        let color = heat_color(value, lo, hi);
+        Rectangle::new([(x, y), (x + size, y + size)], color.filled())
+    }))?;
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::rhythm::{onset_novelty, tempogram, Tempogram};
+use fft_rs::stft::{Spectrogram, StftConfig};
+use plotters::prelude::*;
+use tracing::{info, instrument};
+
+use super::load_mono;
+use crate::plots::{background, caption_font, heat_color, themed};
+
+const NFFT: usize = 2048;
+const HOP: usize = 512;
+
+#[derive(Args)]
+pub struct TempogramArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Length of each autocorrelation window in seconds
+    #[arg(long, default_value_t = 8.0)]
+    window: f32,
+    /// Slowest tempo shown, in BPM
+    #[arg(long, default_value_t = 30.0)]
+    min_bpm: f32,
+    /// Fastest tempo shown, in BPM
+    #[arg(long, default_value_t = 300.0)]
+    max_bpm: f32,
+    /// Where to write the tempogram plot
+    #[arg(long, default_value = "tempogram.png")]
+    plot: PathBuf,
+}
+
+/// Compute the onset novelty of the mono mix, its tempogram, and plot it.
+pub fn run(args: TempogramArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    if !(args.min_bpm > 0.0 && args.min_bpm < args.max_bpm) {
+        return Err("need 0 < --min-bpm < --max-bpm".into());
+    }
+    let config = StftConfig { nfft: NFFT, hop: HOP };
+    config.validate(samples.len())?;
+
+    let novelty = onset_novelty(&Spectrogram::compute(&samples, rate, config));
+    let novelty_rate = rate as f32 / HOP as f32;
+    let window = (args.window * novelty_rate).round() as usize;
+    // a window has to hold a couple of beats at the slowest tempo
+    if window < (120.0 * novelty_rate / args.min_bpm) as usize {
+        return Err(format!("window of {} s is too short for {} BPM", args.window, args.min_bpm).into());
+    }
+    if window > novelty.len() {
+        return Err(format!("input is shorter than the {} s window", args.window).into());
+    }
+
+    let result = tempogram(&novelty, novelty_rate, window, window / 8, (args.min_bpm, args.max_bpm));
+    if let Some(tempo) = result.dominant_tempo() {
+        println!("Dominant tempo: {:.1} BPM over {} windows", tempo, result.times.len());
+    }
+
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_tempogram(&result, args.window, path)?;
+    info!("Tempogram saved to '{}'", path);
+
+    Ok(())
+}
+
+/// Plots a tempogram as a heat map of strength over time and tempo.
+///
+/// # Arguments
+///
+/// * `tempogram` - The windows and their per-tempo strengths.
+/// * `window` - The window length in seconds (the width of one column).
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+fn plot_tempogram(tempogram: &Tempogram, window: f32, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, (1280, 720)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    let step = match tempogram.times.as_slice() {
+        [first, second, ..] => second - first,
+        _ => window,
+    };
+    let x_min = tempogram.times.first().map_or(0.0, |t| t - step / 2.0);
+    let x_max = tempogram.times.last().map_or(1.0, |t| t + step / 2.0);
+    let y_min = tempogram.tempi.last().copied().unwrap_or(0.0);
+    let y_max = tempogram.tempi.first().copied().unwrap_or(1.0).max(y_min + 1.0);
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Tempogram", caption_font(40))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
+
+    themed(&mut chart.configure_mesh())
+        .disable_mesh()
+        .x_desc("Time (s)")
+        .y_desc("Tempo (BPM)")
+        .draw()?;
+
+    let max = tempogram.strength.iter().flatten().copied().fold(f32::MIN_POSITIVE, f32::max);
+    // each lag spans from halfway to its faster neighbour to halfway to its slower one
+    let tempi = &tempogram.tempi;
+    let edge = |i: usize| match (i.checked_sub(1).and_then(|j| tempi.get(j)), tempi.get(i)) {
+        (Some(&faster), Some(&tempo)) => (faster + tempo) / 2.0,
+        (None, _) => y_max,
+        (_, None) => y_min,
+    };
+    for (&time, row) in tempogram.times.iter().zip(&tempogram.strength) {
+        chart.draw_series(row.iter().enumerate().map(|(i, &value)| {
+            let color = heat_color(value.max(0.0), 0.0, max);
+            Rectangle::new([(time - step / 2.0, edge(i + 1)), (time + step / 2.0, edge(i))], color.filled())
+        }))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::resample::decimate;
+use fft_rs::sample::mix_to_mono;
+use fft_rs::structure::{self_similarity, thumbnail};
+use fft_rs::wav::WavFile;
+use fft_rs::writer::{write_wav_file, SampleFormat, WavSpec};
+use tracing::info;
+
+use super::convert::DitherArg;
+use super::ssm::StructureArgs;
+use super::FadeArgs;
+use crate::plots::plot_waveform;
+
+// the waveform plot draws about this many points
+const PLOT_POINTS: usize = 20_000;
+
+#[derive(Args)]
+pub struct ThumbnailArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Output WAV file for the excerpt
+    output: PathBuf,
+    /// Excerpt length in seconds
+    #[arg(long, default_value_t = 15.0)]
+    length: f32,
+    #[command(flatten)]
+    structure: StructureArgs,
+    /// Dither used when quantizing back to an integer format
+    #[arg(long, value_enum, default_value_t = DitherArg::Tpdf)]
+    dither: DitherArg,
+    #[command(flatten)]
+    fade: FadeArgs,
+    /// Where to write the waveform with the excerpt highlighted
+    #[arg(long, default_value = "thumbnail.png")]
+    plot: PathBuf,
+}
+
+/// Pick the most representative excerpt, write it and show where it lies.
+pub fn run(args: ThumbnailArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    let samples = wav.to_normalized_samples()?;
+    let channels = fmt.num_channels.max(1) as usize;
+    let rate = fmt.sample_rate;
+    let mono = mix_to_mono(&samples, channels);
+
+    let features = args.structure.features(&mono, rate)?;
+    let cells = (args.length / features.step).round() as usize;
+    let start = thumbnail(&self_similarity(&features.vectors), cells.max(1))
+        .ok_or_else(|| format!("input is shorter than the {} s excerpt", args.length))?;
+
+    let first = ((start as f32 * features.step * rate as f32) as usize).min(mono.len());
+    let last = (first + (args.length * rate as f32) as usize).min(mono.len());
+    let mut excerpt = samples[first * channels..last * channels].to_vec();
+    args.fade.apply(&mut excerpt, channels, rate);
+
+    let spec = WavSpec { channels: channels as u16, sample_rate: rate, format: SampleFormat::matching(fmt) };
+    write_wav_file(&args.output, spec, &excerpt, args.dither.into())?;
+    info!(
+        "Thumbnail {:.2} s - {:.2} s written to {}",
+        first as f32 / rate as f32, last as f32 / rate as f32, args.output.display()
+    );
+
+    let step = (mono.len() / PLOT_POINTS).max(1);
+    let overview = decimate(&mono, 1, step);
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_waveform(&overview, &[], &[], Some((first / step, last / step)), path)?;
+    info!("Waveform plot saved to '{}'", path);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::error::Error;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::phase::{group_delay, unwrap_phase};
+use fft_rs::stft::StftConfig;
+use fft_rs::transfer::CrossSpectra;
+use fft_rs::wav::WavFile;
+use plotters::style::RGBColor;
+use tracing::info;
+
+use crate::plots::{plot_lines, Series};
+
+#[derive(Args)]
+pub struct TransferArgs {
+    /// Stereo capture: channel 1 the stimulus, channel 2 the response
+    input: PathBuf,
+    /// STFT window size for the averaged spectra
+    #[arg(long, default_value_t = 4096)]
+    nfft: usize,
+}
+
+/// Estimate H1 and coherence from a dual-channel capture and plot them,
+/// with the phase unwrapped and its group delay.
+pub fn run(args: TransferArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    if fmt.num_channels != 2 {
+        return Err(format!("need a 2-channel capture, got {} channel(s)", fmt.num_channels).into());
+    }
+    let samples = wav.to_normalized_samples()?;
+    let stimulus: Vec<f32> = samples.iter().step_by(2).copied().collect();
+    let response: Vec<f32> = samples.iter().skip(1).step_by(2).copied().collect();
+
+    let config = StftConfig::with_overlap(args.nfft, 0.5);
+    config.validate(stimulus.len())?;
+    let spectra = CrossSpectra::compute(&stimulus, &response, fmt.sample_rate, config);
+
+    // skip DC, which a log frequency axis can't show
+    let bins = 1..spectra.sxx.len();
+    let magnitude: Vec<(f32, f32)> = bins.clone()
+        .map(|k| (spectra.frequency(k), 20.0 * (spectra.h1(k).norm() as f32).max(1e-9).log10()))
+        .collect();
+    // unwrapped so delays read as a steady slope rather than a sawtooth
+    let frequencies: Vec<f64> = bins.clone().map(|k| spectra.frequency(k) as f64).collect();
+    let unwrapped = unwrap_phase(&bins.clone().map(|k| spectra.h1(k).arg()).collect::<Vec<_>>());
+    let phase: Vec<(f32, f32)> = frequencies.iter().zip(&unwrapped)
+        .map(|(&f, &p)| (f as f32, p.to_degrees() as f32))
+        .collect();
+    let delay: Vec<(f32, f32)> = frequencies.iter().zip(group_delay(&frequencies, &unwrapped))
+        .map(|(&f, d)| (f as f32, (d * 1000.0) as f32))
+        .collect();
+    let coherence: Vec<(f32, f32)> = bins.map(|k| (spectra.frequency(k), spectra.coherence(k))).collect();
+
+    let mean_coherence = coherence.iter().map(|c| c.1).sum::<f32>() / coherence.len() as f32;
+    println!("H1 over {} bins, mean coherence {:.3}", coherence.len(), mean_coherence);
+
+    let blue = RGBColor(0, 90, 200);
+    plot_lines("Transfer Magnitude (H1)", "Frequency (Hz)", "Gain (dB)",
+        &[Series { label: "|H1|", points: &magnitude, color: blue }], true, "transfer_magnitude.png")?;
+    plot_lines("Transfer Phase (H1)", "Frequency (Hz)", "Phase (degrees)",
+        &[Series { label: "arg H1", points: &phase, color: blue }], true, "transfer_phase.png")?;
+    plot_lines("Group Delay (H1)", "Frequency (Hz)", "Delay (ms)",
+        &[Series { label: "Group delay", points: &delay, color: blue }], true, "transfer_group_delay.png")?;
+    plot_lines("Coherence", "Frequency (Hz)", "Magnitude-squared coherence",
+        &[Series { label: "Coherence", points: &coherence, color: blue }], true, "transfer_coherence.png")?;
+    info!(
+        "Plots saved to 'transfer_magnitude.png', 'transfer_phase.png', 'transfer_group_delay.png' and 'transfer_coherence.png'"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::modulation::{detect_tremolo, TREMOLO_HIGH, TREMOLO_LOW};
+use plotters::style::RGBColor;
+use tracing::info;
+
+use super::load_mono;
+use crate::plots::{plot_lines, Series};
+
+#[derive(Args)]
+pub struct TremoloArgs {
+    /// Input WAV file
+    input: PathBuf,
+    /// Weakest modulation reported as tremolo, in percent depth
+    #[arg(long, default_value_t = 5.0)]
+    min_depth: f32,
+    /// Where to write the envelope spectrum plot
+    #[arg(long, default_value = "tremolo.png")]
+    plot: PathBuf,
+}
+
+/// Report the rate and depth of the strongest periodic amplitude
+/// modulation of the mono mix, and plot its level envelope's spectrum.
+pub fn run(args: TremoloArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let tremolo = detect_tremolo(&samples, rate).ok_or_else(|| format!(
+        "need at least {} s of sound to look for tremolo down to {} Hz",
+        1.0 / TREMOLO_LOW, TREMOLO_LOW
+    ))?;
+
+    if tremolo.depth * 100.0 < args.min_depth {
+        println!(
+            "No tremolo between {} and {} Hz (strongest: {:.1}% depth at {:.2} Hz)",
+            TREMOLO_LOW, TREMOLO_HIGH, tremolo.depth * 100.0, tremolo.rate
+        );
+    } else {
+        println!("Rate:  {:.2} Hz", tremolo.rate);
+        println!("Depth: {:.1}% ({:.1} dB swing)", tremolo.depth * 100.0, tremolo.swing_db());
+    }
+
+    let points: Vec<(f32, f32)> = tremolo.spectrum.iter().map(|&(f, depth)| (f, depth * 100.0)).collect();
+    let series = [Series { label: "Envelope spectrum", points: &points, color: RGBColor(70, 130, 180) }];
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_lines("Tremolo", "Modulation frequency (Hz)", "Depth (%)", &series, true, path)?;
+    info!("Envelope spectrum saved to '{}'", path);
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use fft_rs::pitch::note_name;
+use fft_rs::tuner::{Instrument, Reading, Tuner};
+
+use super::{format_time, load_mono, parse_time};
+
+// the meter spans this many cents either side of the target
+const METER_CENTS: f32 = 50.0;
+const METER_WIDTH: usize = 41;
+
+#[derive(Args)]
+pub struct TuneArgs {
+    /// WAV file to read instead of the live input
+    input: Option<PathBuf>,
+    /// Instrument whose open strings are the targets; chromatic takes the
+    /// nearest semitone
+    #[arg(long, value_enum, default_value_t = InstrumentArg::Guitar)]
+    instrument: InstrumentArg,
+    /// Deviation still shown as in tune, in cents
+    #[arg(long, default_value_t = 5.0)]
+    tolerance: f32,
+    /// Readings per second
+    #[arg(long, default_value_t = 10.0)]
+    rate: f32,
+    /// Stop live capture after this long (default: run until interrupted)
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    duration: Option<f64>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum InstrumentArg {
+    Guitar,
+    Bass,
+    Ukulele,
+    Violin,
+    Viola,
+    Cello,
+    Chromatic,
+}
+
+impl From<InstrumentArg> for Instrument {
+    fn from(arg: InstrumentArg) -> Self {
+        match arg {
+            InstrumentArg::Guitar => Instrument::Guitar,
+            InstrumentArg::Bass => Instrument::Bass,
+            InstrumentArg::Ukulele => Instrument::Ukulele,
+            InstrumentArg::Violin => Instrument::Violin,
+            InstrumentArg::Viola => Instrument::Viola,
+            InstrumentArg::Cello => Instrument::Cello,
+            InstrumentArg::Chromatic => Instrument::Chromatic,
+        }
+    }
+}
+
+/// Show the nearest string, its deviation in cents and whether it is in
+/// tune, live from the default input device or through a WAV file.
+pub fn run(args: TuneArgs) -> Result<(), Box<dyn Error>> {
+    if args.rate <= 0.0 || args.tolerance < 0.0 {
+        return Err("rate must be positive and tolerance non-negative".into());
+    }
+    let instrument = Instrument::from(args.instrument);
+    let strings: Vec<String> = instrument.strings().iter().map(|&key| note_name(key)).collect();
+    if !strings.is_empty() {
+        println!("Strings 1-{}: {}", strings.len(), strings.join(" "));
+    }
+
+    match &args.input {
+        Some(path) => {
+            let (samples, rate) = load_mono(path)?;
+            let mut tuner = Tuner::new(instrument, rate, args.rate);
+            let mut count = 0;
+            tuner.push(&samples, |reading| {
+                count += 1;
+                let time = count as f64 / args.rate as f64;
+                println!("{:>8}  {}", format_time(time), describe(reading, args.tolerance));
+            });
+            Ok(())
+        }
+        None => live(&args, instrument),
+    }
+}
+
+// one line: target, frequency, cents, a meter and the verdict
+fn describe(reading: Option<Reading>, tolerance: f32) -> String {
+    let Some(reading) = reading else { return "--".to_string() };
+    let target = match reading.string {
+        Some(string) => format!("{:<3} (string {})", note_name(reading.target), string),
+        None => format!("{:<3}", note_name(reading.target)),
+    };
+    let mut meter = vec![b'-'; METER_WIDTH];
+    meter[METER_WIDTH / 2] = b'|';
+    let offset = (reading.cents / METER_CENTS).clamp(-1.0, 1.0) * (METER_WIDTH / 2) as f32;
+    meter[((METER_WIDTH as f32 / 2.0 + offset) as usize).min(METER_WIDTH - 1)] = b'^';
+    let verdict = if reading.in_tune(tolerance) {
+        "IN TUNE"
+    } else if reading.cents < 0.0 {
+        "flat"
+    } else {
+        "sharp"
+    };
+    format!(
+        "{}  {:7.1} Hz  {:+6.1} cents  [{}]  {}",
+        target,
+        reading.frequency,
+        reading.cents,
+        String::from_utf8_lossy(&meter),
+        verdict
+    )
+}
+
+#[cfg(feature = "live")]
+fn live(args: &TuneArgs, instrument: Instrument) -> Result<(), Box<dyn Error>> {
+    use std::io::{IsTerminal, Write};
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::SampleFormat;
+    use fft_rs::sample::mix_to_mono;
+    use tracing::{info, warn};
+
+    let device = cpal::default_host().default_input_device().ok_or("no audio input device")?;
+    let config = device.default_input_config()?;
+    let (rate, channels) = (config.sample_rate().0, config.channels().max(1) as usize);
+    info!("Listening on '{}' at {} Hz; Ctrl-C stops", device.name().unwrap_or_default(), rate);
+
+    // the callback only mixes down and hands the block over
+    let (sender, receiver) = mpsc::channel::<Vec<f32>>();
+    let on_error = |e: cpal::StreamError| warn!("input stream: {}", e);
+    let stream_config = config.config();
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => device.build_input_stream(
+            &stream_config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let _ = sender.send(mix_to_mono(data, channels));
+            },
+            on_error,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_input_stream(
+            &stream_config,
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                let data: Vec<f32> = data.iter().map(|&s| s as f32 / 32768.0).collect();
+                let _ = sender.send(mix_to_mono(&data, channels));
+            },
+            on_error,
+            None,
+        )?,
+        SampleFormat::U16 => device.build_input_stream(
+            &stream_config,
+            move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                let data: Vec<f32> = data.iter().map(|&s| (s as f32 - 32768.0) / 32768.0).collect();
+                let _ = sender.send(mix_to_mono(&data, channels));
+            },
+            on_error,
+            None,
+        )?,
+        format => return Err(format!("unsupported input sample format {}", format).into()),
+    };
+    stream.play()?;
+
+    // redraw one line in place on a terminal, else print every reading
+    let terminal = std::io::stdout().is_terminal();
+    let mut out = std::io::stdout().lock();
+    let mut tuner = Tuner::new(instrument, rate, args.rate);
+    let started = Instant::now();
+    let deadline = args.duration.map(Duration::from_secs_f64);
+    while deadline.is_none_or(|d| started.elapsed() < d) {
+        let Ok(block) = receiver.recv_timeout(Duration::from_millis(200)) else { continue };
+        let mut result = Ok(());
+        tuner.push(&block, |reading| {
+            let line = describe(reading, args.tolerance);
+            if result.is_ok() {
+                result = if terminal { write!(out, "\r{}\x1b[K", line).and_then(|_| out.flush()) } else { writeln!(out, "{}", line) };
+            }
+        });
+        result?;
+    }
+    if terminal {
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "live"))]
+fn live(_: &TuneArgs, _: Instrument) -> Result<(), Box<dyn Error>> {
+    Err("live input needs a build with the live feature (cargo build --features live); pass a WAV file instead".into())
+}
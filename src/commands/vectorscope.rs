@@ -0,0 +1,98 @@
+use std::error::Error;
+use std::f32::consts::SQRT_2;
+use std::fs::File;
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::meter::sample_peak;
+use fft_rs::stereo::{correlation, side_mid_ratio_db, vectorscope};
+use fft_rs::wav::WavFile;
+use plotters::prelude::*;
+use tracing::{info, instrument};
+
+use crate::plots::{background, caption_font, foreground, heat_color};
+
+// density grid cells per side; coarser than the pixels, so the shading
+// shows where the signal dwells rather than single samples
+const DENSITY_CELLS: usize = 256;
+
+#[derive(Args)]
+pub struct VectorscopeArgs {
+    /// Stereo input WAV file
+    input: PathBuf,
+    /// Where to write the vectorscope image
+    #[arg(long, default_value = "vectorscope.png")]
+    plot: PathBuf,
+}
+
+/// Print the phase correlation and width of a stereo file and render its
+/// goniometer (vectorscope) image.
+pub fn run(args: VectorscopeArgs) -> Result<(), Box<dyn Error>> {
+    let mut file = File::open(&args.input)?;
+    let wav = WavFile::parse(&mut file)?;
+    let fmt = wav.fmt.as_ref().ok_or("input has no fmt chunk")?;
+    if fmt.num_channels != 2 {
+        return Err(format!("need a 2-channel file, got {} channel(s)", fmt.num_channels).into());
+    }
+    let samples = wav.to_normalized_samples()?;
+    let (Some(correlation), Some(ratio)) = (correlation(&samples), side_mid_ratio_db(&samples)) else {
+        return Err("a channel is silent: nothing to show".into());
+    };
+
+    println!("Correlation: {:+.2}", correlation);
+    println!("Side/mid ratio: {:.2} dB", ratio);
+    if correlation < 0.0 {
+        println!("Warning: the channels are mostly out of phase and will cancel when summed to mono");
+    }
+
+    let path = args.plot.to_str().ok_or("plot path is not valid UTF-8")?;
+    plot_vectorscope(&samples, "Vectorscope", path)?;
+    info!("Vectorscope saved to '{}'", path);
+
+    Ok(())
+}
+
+/// Plots a goniometer: every L/R sample pair as a point, mid up and side
+/// across, shaded by how many pairs land in each cell (log scale). The
+/// scale follows the sample peak, so a mono signal at its peak reaches the
+/// top edge.
+///
+/// # Arguments
+///
+/// * `stereo` - Interleaved left/right samples.
+/// * `caption` - The chart title.
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+fn plot_vectorscope(stereo: &[f32], caption: &str, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, (1080, 1080)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption(caption, caption_font(40))
+        .margin(40)
+        .build_cartesian_2d(-1f32..1f32, -1f32..1f32)?;
+
+    let size = DENSITY_CELLS;
+    let counts = vectorscope(stereo, size, sample_peak(stereo) * SQRT_2);
+    let densest = counts.iter().copied().max().unwrap_or(0);
+    let top = (densest as f32).ln_1p().max(f32::MIN_POSITIVE);
+    let cell = 2.0 / size as f32;
+    chart.draw_series(counts.iter().enumerate().filter(|(_, &count)| count > 0).map(|(index, &count)| {
+        let (x, y) = ((index % size) as f32 * cell - 1.0, (index / size) as f32 * cell - 1.0);
+        let color = heat_color((count as f32).ln_1p(), 0.0, top);
+        Rectangle::new([(x, y), (x + cell, y + cell)], color.filled())
+    }))?;
+
+    // guides: mid and side axes, and where left or right alone would fall
+    let guide = foreground().mix(0.3);
+    let half = SQRT_2 / 2.0;
+    for (from, to) in [((0.0, -1.0), (0.0, 1.0)), ((-1.0, 0.0), (1.0, 0.0)), ((-half, -half), (half, half)), ((half, -half), (-half, half))] {
+        chart.draw_series(std::iter::once(PathElement::new([from, to], guide)))?;
+    }
+    let label = caption_font(30);
+    for (text, position) in [("M", (0.02, 0.92)), ("L", (-half - 0.02, half + 0.06)), ("R", (half - 0.02, half + 0.06)), ("S", (0.92, 0.06))] {
+        chart.draw_series(std::iter::once(Text::new(text, position, label.clone())))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+use fft_rs::pitch::{note_name, segment_notes, SegmentConfig};
+use fft_rs::vibrato::{measure_vibrato, VIBRATO_HIGH, VIBRATO_LOW};
+use tracing::info;
+
+use super::load_mono;
+use super::pitch::PitchTrackingArgs;
+
+// wide vibrato swings up to a semitone either way without leaving the note
+const NOTE_TOLERANCE: f32 = 1.0;
+
+#[derive(Args)]
+pub struct VibratoArgs {
+    /// Input WAV file (one voice or instrument at a time)
+    input: PathBuf,
+    #[command(flatten)]
+    tracking: PitchTrackingArgs,
+    /// Write every vibrato half cycle as CSV (note, time, rate, extent)
+    #[arg(long)]
+    csv: Option<PathBuf>,
+}
+
+/// Transcribe the mono mix into notes and measure the vibrato rate and
+/// extent of each from its pitch contour.
+pub fn run(args: VibratoArgs) -> Result<(), Box<dyn Error>> {
+    let (samples, rate) = load_mono(&args.input)?;
+    let points = args.tracking.track(&samples, rate)?;
+    let segment = SegmentConfig { tolerance: NOTE_TOLERANCE, ..SegmentConfig::default() };
+    let notes = segment_notes(&points, segment);
+    let vibratos: Vec<_> = notes.iter().map(|note| measure_vibrato(&note.contour)).collect();
+
+    let with_vibrato = vibratos.iter().flatten().count();
+    println!(
+        "{} notes, {} with vibrato ({}-{} Hz)",
+        notes.len(), with_vibrato, VIBRATO_LOW, VIBRATO_HIGH
+    );
+    if !notes.is_empty() {
+        println!(
+            "{:>10} {:>10} {:>6} {:>9} {:>9} {:>12} {:>12}",
+            "Start (s)", "Length (s)", "Pitch", "Rate Hz", "Rate SD", "Extent cents", "Max cents"
+        );
+    }
+    for (note, vibrato) in notes.iter().zip(&vibratos) {
+        print!("{:>10.3} {:>10.3} {:>6}", note.start, note.end - note.start, note_name(note.key));
+        match vibrato {
+            Some(v) => println!(" {:>9.2} {:>9.2} {:>12.0} {:>12.0}", v.rate, v.rate_sd, v.extent, v.extent_max),
+            None => println!(" {:>9} {:>9} {:>12} {:>12}", "-", "-", "-", "-"),
+        }
+    }
+
+    // over all half cycles of all notes, so long notes count for more
+    let cycles: Vec<_> = vibratos.iter().flatten().flat_map(|v| &v.points).collect();
+    if !cycles.is_empty() {
+        let count = cycles.len() as f32;
+        println!(
+            "Overall: {:.2} Hz, +/-{:.0} cents",
+            cycles.iter().map(|p| p.rate).sum::<f32>() / count,
+            cycles.iter().map(|p| p.extent).sum::<f32>() / count
+        );
+    }
+
+    if let Some(path) = &args.csv {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "note,time,rate,extent")?;
+        for (i, vibrato) in vibratos.iter().enumerate() {
+            for p in vibrato.iter().flat_map(|v| &v.points) {
+                writeln!(out, "{},{:.4},{:.3},{:.1}", i, p.time, p.rate, p.extent)?;
+            }
+        }
+        out.flush()?;
+        info!("Vibrato cycles written to '{}'", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// fft-rs.toml: analysis presets shared by a team. Each value becomes the
+// default of the matching command-line option, so explicit flags still win.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use clap::Command;
+use serde::Deserialize;
+
+/// Looked for in the working directory when `--config` isn't given.
+pub const DEFAULT_PATH: &str = "fft-rs.toml";
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    stft: StftSection,
+    plot: PlotSection,
+    export: ExportSection,
+}
+
+/// `[stft]`: framing of every command that takes the shared STFT options.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StftSection {
+    window: Option<String>,
+    nfft: Option<usize>,
+    hop: Option<usize>,
+    backend: Option<String>,
+}
+
+/// `[plot]`: chart appearance.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PlotSection {
+    theme: Option<String>,
+    colormap: Option<String>,
+    freq_scale: Option<String>,
+}
+
+/// `[export]`: formats written by `export` when `--format` isn't given,
+/// and its memory budget.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ExportSection {
+    formats: Option<Vec<String>>,
+    max_mem: Option<String>,
+}
+
+impl Config {
+    /// Read and parse a config file.
+    pub fn load(path: &Path) -> Result<Config, Box<dyn Error>> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Install the config values as defaults on `command` (the top-level
+    /// CLI) and its subcommands.
+    pub fn apply(&self, command: Command) -> Command {
+        let command = self.apply_to(command);
+        command.mut_subcommands(|sub| self.apply_to(sub))
+    }
+
+    fn apply_to(&self, mut command: Command) -> Command {
+        let has = |command: &Command, id: &str| command.get_arguments().any(|arg| arg.get_id() == id);
+
+        // only commands flattening the shared STFT options (recognised by
+        // --multi-res) take the preset; others have their own tuned framing
+        if has(&command, "multi_res") {
+            let stft = [
+                ("window", self.stft.window.clone()),
+                ("nfft", self.stft.nfft.map(|n| n.to_string())),
+                ("hop", self.stft.hop.map(|n| n.to_string())),
+                ("backend", self.stft.backend.clone()),
+            ];
+            for (id, value) in stft {
+                if let Some(value) = value {
+                    command = command.mut_arg(id, |arg| arg.default_value(value));
+                }
+            }
+        }
+        let plot = [("theme", &self.plot.theme), ("colormap", &self.plot.colormap), ("freq_scale", &self.plot.freq_scale)];
+        for (id, value) in plot {
+            if let Some(value) = value.clone().filter(|_| has(&command, id)) {
+                command = command.mut_arg(id, |arg| arg.default_value(value));
+            }
+        }
+        if let Some(formats) = self.export.formats.clone().filter(|_| command.get_name() == "export") {
+            command = command.mut_arg("format", |arg| arg.default_values(formats));
+        }
+        if let Some(max_mem) = self.export.max_mem.clone().filter(|_| command.get_name() == "export") {
+            command = command.mut_arg("max_mem", |arg| arg.default_value(max_mem));
+        }
+        command
+    }
+}
@@ -0,0 +1,32 @@
+// Crossover: split a signal into adjacent frequency bands with
+// Linkwitz-Riley (two cascaded Butterworth) low/high-pass pairs.
+
+use std::f64::consts::FRAC_1_SQRT_2;
+
+use crate::biquad::Biquad;
+
+fn run_cascade(samples: &[f32], mut sections: [Biquad; 2]) -> Vec<f32> {
+    samples.iter().map(|&s| {
+        let mut x = s as f64;
+        for section in &mut sections {
+            x = section.process(x);
+        }
+        x as f32
+    }).collect()
+}
+
+/// Split mono `samples` at the ascending `crossovers` (Hz) into
+/// `crossovers.len() + 1` bands, lowest first. Each split is 4th-order
+/// (24 dB/octave) and the bands sum back to flat magnitude.
+pub fn split_bands(samples: &[f32], sample_rate: u32, crossovers: &[f32]) -> Vec<Vec<f32>> {
+    let mut bands = Vec::with_capacity(crossovers.len() + 1);
+    let mut rest = samples.to_vec();
+    for &f in crossovers {
+        let lowpass = Biquad::lowpass(sample_rate, f as f64, FRAC_1_SQRT_2);
+        let highpass = Biquad::highpass(sample_rate, f as f64, FRAC_1_SQRT_2);
+        bands.push(run_cascade(&rest, [lowpass; 2]));
+        rest = run_cascade(&rest, [highpass; 2]);
+    }
+    bands.push(rest);
+    bands
+}
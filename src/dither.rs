@@ -0,0 +1,64 @@
+// Requantization from normalized f32 down to an integer sample type, with
+// optional TPDF dither so truncation error turns into benign noise instead
+// of signal-correlated distortion.
+
+use crate::sample::Sample;
+
+/// How to handle the rounding error when reducing bit depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Plain rounding.
+    Off,
+    /// Triangular-PDF dither of +/-1 LSB.
+    Tpdf,
+    /// TPDF dither plus first-order error-feedback noise shaping, which
+    /// pushes the noise floor up towards Nyquist where hearing is least
+    /// sensitive.
+    Shaped,
+}
+
+/// Convert interleaved normalized samples to `T`, applying `dither` scaled
+/// to one LSB of the target type. Float targets are converted unchanged.
+pub fn requantize<T: Sample>(samples: &[f32], channels: usize, dither: Dither) -> Vec<T> {
+    let channels = channels.max(1);
+    let lsb = 1.0 / T::FULL_SCALE;
+
+    if dither == Dither::Off || T::FULL_SCALE <= 1.0 {
+        return samples.iter().map(|&s| T::from_f32(s)).collect();
+    }
+
+    // fixed seed: dithered output is reproducible run to run
+    let mut rng = XorShift32(0x9E37_79B9);
+    let mut error = vec![0.0f32; channels]; // last quantization error per channel
+
+    samples.iter().enumerate().map(|(i, &s)| {
+        let ch = i % channels;
+
+        // sum of two independent uniforms on [-0.5, 0.5) LSB is triangular on +/-1 LSB
+        let noise = (rng.next_f32() - rng.next_f32()) * lsb;
+        let shaped = if dither == Dither::Shaped { s - error[ch] } else { s };
+
+        let out = T::from_f32(shaped + noise);
+        error[ch] = out.to_f32() - shaped;
+        out
+    }).collect()
+}
+
+// small, fast, deterministic generator; statistical quality is plenty for dither
+struct XorShift32(u32);
+
+impl XorShift32 {
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    // uniform on [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
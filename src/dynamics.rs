@@ -0,0 +1,101 @@
+// Dynamic range measures: the DR score (crest factor of the loudest blocks)
+// and peak-to-loudness ratio.
+
+// DR score analysis block length
+const DR_BLOCK_SECS: f32 = 3.0;
+
+// crest-over-time window and step
+const CREST_WINDOW_SECS: f32 = 0.4;
+const CREST_STEP_SECS: f32 = 0.1;
+
+// windows quieter than this (dBFS RMS) have no meaningful crest factor
+const CREST_FLOOR_DB: f32 = -70.0;
+
+/// DR score of each channel and the track (their mean, rounded to whole dB
+/// the way DR meters report it).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrScore {
+    pub channels: Vec<f32>,
+    pub track: i32,
+}
+
+/// DR score: split each channel into 3 s blocks, take the RMS of the loudest
+/// 20% of blocks and the second-highest block peak, and report their ratio in
+/// dB. Low scores mean heavily limited material. `None` for empty input.
+pub fn dr_score(samples: &[f32], channels: usize, sample_rate: u32) -> Option<DrScore> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    if frames == 0 {
+        return None;
+    }
+    let block = ((sample_rate as f32 * DR_BLOCK_SECS) as usize).clamp(1, frames);
+
+    let scores: Vec<f32> = (0..channels).map(|ch| {
+        // (rms, peak) per block; the RMS carries the DR meter's sqrt(2) so
+        // a sine reads the same as its peak
+        let mut blocks: Vec<(f32, f32)> = (0..frames).step_by(block).map(|start| {
+            let end = (start + block).min(frames);
+            let (sum, peak) = (start..end).fold((0.0f32, 0.0f32), |(sum, peak), i| {
+                let s = samples[i * channels + ch];
+                (sum + s * s, peak.max(s.abs()))
+            });
+            ((2.0 * sum / (end - start) as f32).sqrt(), peak)
+        }).collect();
+
+        let mut peaks: Vec<f32> = blocks.iter().map(|b| b.1).collect();
+        peaks.sort_by(|a, b| b.total_cmp(a));
+        let peak = peaks.get(1).copied().unwrap_or(peaks[0]);
+
+        blocks.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let loudest = (blocks.len() / 5).max(1);
+        let rms = (blocks[..loudest].iter().map(|b| b.0 * b.0).sum::<f32>() / loudest as f32).sqrt();
+
+        20.0 * (peak / rms.max(f32::MIN_POSITIVE)).log10()
+    }).collect();
+
+    let track = (scores.iter().sum::<f32>() / channels as f32).round() as i32;
+    Some(DrScore { channels: scores, track })
+}
+
+/// Crest factor (peak over RMS) of a block of samples, in dB.
+pub fn crest_factor_db(samples: &[f32]) -> f32 {
+    let peak = samples.iter().fold(0.0f32, |p, &s| p.max(s.abs()));
+    let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt();
+    20.0 * (peak / rms.max(f32::MIN_POSITIVE)).log10()
+}
+
+/// RMS level over time as (window start in seconds, dBFS) of a mono
+/// signal, from the same 400 ms windows every 100 ms as the crest factor.
+pub fn rms_over_time(samples: &[f32], sample_rate: u32) -> Vec<(f32, f32)> {
+    let window = ((sample_rate as f32 * CREST_WINDOW_SECS) as usize).max(1);
+    let step = ((sample_rate as f32 * CREST_STEP_SECS) as usize).max(1);
+    if samples.len() < window {
+        return Vec::new();
+    }
+
+    (0..=samples.len() - window).step_by(step).map(|start| {
+        let block = &samples[start..start + window];
+        let mean_square = block.iter().map(|s| s * s).sum::<f32>() / window as f32;
+        (start as f32 / sample_rate as f32, 10.0 * (mean_square + 1e-12).log10())
+    }).collect()
+}
+
+/// Crest factor over time as (window start in seconds, dB), from 400 ms
+/// windows every 100 ms across all channels. Near-silent windows are left
+/// out. Low stretches are where the material is most compressed.
+pub fn crest_factor_over_time(samples: &[f32], channels: usize, sample_rate: u32) -> Vec<(f32, f32)> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let window = ((sample_rate as f32 * CREST_WINDOW_SECS) as usize).max(1);
+    let step = ((sample_rate as f32 * CREST_STEP_SECS) as usize).max(1);
+    if frames < window {
+        return Vec::new();
+    }
+
+    (0..=frames - window).step_by(step).filter_map(|start| {
+        let block = &samples[start * channels..(start + window) * channels];
+        let rms = (block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32).sqrt();
+        (20.0 * rms.log10() > CREST_FLOOR_DB)
+            .then(|| (start as f32 / sample_rate as f32, crest_factor_db(block)))
+    }).collect()
+}
@@ -0,0 +1,124 @@
+// EQ suggestions: a track's long-term band levels against a reference
+// curve, both taken relative to their mean so only the spectral balance
+// (tilt and bumps) counts, turned into corrective band gains.
+
+use crate::bands::Band;
+
+// bands this far below the track's loudest carry too little to correct
+const CONTENT_FLOOR_DB: f32 = -60.0;
+
+// pink noise has unbounded power toward 0 Hz, so bands reaching below
+// this have no reference level
+const PINK_FLOOR_HZ: f32 = 16.0;
+
+/// The suggestion for one band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EqBand {
+    pub nominal: f32,
+    pub center: f32,
+    pub track_db: f32,        // track level re its mean over the bands used
+    pub reference_db: f32,    // reference level re its mean
+    pub gain_db: Option<f32>, // suggested boost or cut; None if the band is (nearly) empty
+}
+
+/// Pink noise carries equal power per octave, so its level in a band goes
+/// with the band's width in octaves: flat over fractional-octave bands,
+/// rising with frequency over Bark or ERB bands. Bands reaching below
+/// 16 Hz come out as NaN, which `suggest_eq` leaves out.
+pub fn pink_reference(bands: &[Band]) -> Vec<f32> {
+    bands.iter().map(|band| {
+        if band.lower < PINK_FLOOR_HZ {
+            return f32::NAN;
+        }
+        10.0 * (band.upper / band.lower).log2().log10()
+    }).collect()
+}
+
+/// Gains that move `track`'s band balance onto `reference` (levels of the
+/// same bands, in any dB offset), limited to +-`max_gain_db`.
+pub fn suggest_eq(track: &[Band], reference: &[f32], max_gain_db: f32) -> Vec<EqBand> {
+    let loudest = track.iter().map(|b| b.level_db).fold(f32::NEG_INFINITY, f32::max);
+    let used: Vec<bool> = track.iter().zip(reference)
+        .map(|(band, &r)| band.level_db >= loudest + CONTENT_FLOOR_DB && r.is_finite())
+        .collect();
+    let count = used.iter().filter(|&&u| u).count().max(1) as f32;
+    let track_mean = track.iter().zip(&used).filter(|(_, &u)| u).map(|(b, _)| b.level_db).sum::<f32>() / count;
+    let reference_mean = reference.iter().zip(&used).filter(|(_, &u)| u).map(|(r, _)| r).sum::<f32>() / count;
+
+    track.iter().zip(reference).zip(&used).map(|((band, &r), &used)| {
+        let track_db = band.level_db - track_mean;
+        let reference_db = r - reference_mean;
+        EqBand {
+            nominal: band.nominal,
+            center: band.center,
+            track_db,
+            reference_db,
+            gain_db: used.then(|| (reference_db - track_db).clamp(-max_gain_db, max_gain_db)),
+        }
+    }).collect()
+}
+
+/// A target spectrum shape to compare a track against: level in dB per
+/// frequency, in per-bin (per-Hz) terms, up to an arbitrary offset.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetCurve {
+    /// Pink noise: power per Hz falls as 1/f, i.e. 3 dB per octave.
+    Pink,
+    /// (frequency Hz, level dB) points, ascending in frequency.
+    Points(Vec<(f32, f32)>),
+}
+
+impl TargetCurve {
+    /// Parse "frequency,level_db" lines. Blank lines, `#` comments and a
+    /// leading header line are skipped.
+    pub fn parse_csv(text: &str) -> Result<TargetCurve, String> {
+        let mut points: Vec<(f32, f32)> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split(',').map(str::trim);
+            let parsed = match (fields.next(), fields.next()) {
+                (Some(f), Some(l)) => f.parse::<f32>().ok().zip(l.parse::<f32>().ok()),
+                _ => None,
+            };
+            let (frequency, level) = match parsed {
+                Some(point) => point,
+                None if points.is_empty() && i == 0 => continue, // header
+                None => return Err(format!("line {}: expected frequency,level_db", i + 1)),
+            };
+            if frequency <= 0.0 || !frequency.is_finite() || !level.is_finite() {
+                return Err(format!("line {}: frequency must be positive and the level finite", i + 1));
+            }
+            if points.last().is_some_and(|&(last, _)| frequency <= last) {
+                return Err(format!("line {}: frequencies must ascend", i + 1));
+            }
+            points.push((frequency, level));
+        }
+        if points.is_empty() {
+            return Err("target curve has no points".to_string());
+        }
+        Ok(TargetCurve::Points(points))
+    }
+
+    /// Level at `frequency` (> 0): pink is 0 dB at 1 kHz; points are
+    /// interpolated linearly over log frequency and held past either end.
+    pub fn level_db(&self, frequency: f32) -> f32 {
+        match self {
+            TargetCurve::Pink => -10.0 * (frequency / 1000.0).log10(),
+            TargetCurve::Points(points) => {
+                let i = points.partition_point(|&(f, _)| f < frequency);
+                if i == 0 {
+                    return points[0].1;
+                }
+                if i == points.len() {
+                    return points[i - 1].1;
+                }
+                let ((f0, l0), (f1, l1)) = (points[i - 1], points[i]);
+                let t = (frequency / f0).ln() / (f1 / f0).ln();
+                l0 + t * (l1 - l0)
+            }
+        }
+    }
+}
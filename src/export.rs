@@ -0,0 +1,224 @@
+// Analysis matrices for other tools: NumPy .npy arrays and .npz archives.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::spill::SpillMatrix;
+
+/// A named f32 array in row-major (C) order.
+#[derive(Debug, Clone)]
+pub struct NamedArray {
+    pub name: String,
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+impl NamedArray {
+    /// A 1-D array.
+    pub fn vector(name: &str, data: Vec<f32>) -> Self {
+        NamedArray { name: name.into(), shape: vec![data.len()], data }
+    }
+
+    /// A 2-D array from equal-length rows (e.g. frames of bins).
+    pub fn matrix(name: &str, rows: &[Vec<f32>]) -> Self {
+        let columns = rows.first().map_or(0, |row| row.len());
+        debug_assert!(rows.iter().all(|row| row.len() == columns));
+        NamedArray { name: name.into(), shape: vec![rows.len(), columns], data: rows.concat() }
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+// magic, version, header length and the header dict, padded so the data
+// starts on a 64-byte boundary
+fn npy_header(shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [n] => format!("({},)", n),
+        dims => format!("({})", dims.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")),
+    };
+    let mut header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': {}, }}", shape);
+    // magic (6) + version (2) + header length (2) + header + newline
+    let padding = (64 - (10 + header.len() + 1) % 64) % 64;
+    header.extend(std::iter::repeat_n(' ', padding));
+    header.push('\n');
+
+    let mut bytes = Vec::with_capacity(10 + header.len());
+    bytes.extend_from_slice(b"\x93NUMPY\x01\x00");
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes
+}
+
+/// Encode `array` as a version 1.0 .npy file: magic, header dict padded so
+/// the data starts on a 64-byte boundary, then little-endian f32 data.
+pub fn encode_npy(array: &NamedArray) -> io::Result<Vec<u8>> {
+    if array.shape.iter().product::<usize>() != array.data.len() {
+        return Err(invalid("array shape does not match its length"));
+    }
+    let mut bytes = npy_header(&array.shape);
+    bytes.reserve(4 * array.data.len());
+    for value in &array.data {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    Ok(bytes)
+}
+
+/// One array of an export: held in memory, or a matrix spilled to disk
+/// that is copied over a block of rows at a time.
+pub enum Member<'a> {
+    Array(&'a NamedArray),
+    Spilled(&'a str, &'a SpillMatrix),
+}
+
+// rows of a spilled matrix read per block when copying it out
+const SPILL_BLOCK_ROWS: usize = 256;
+
+impl Member<'_> {
+    pub fn name(&self) -> &str {
+        match self {
+            Member::Array(array) => &array.name,
+            Member::Spilled(name, _) => name,
+        }
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        match self {
+            Member::Array(array) => array.shape.clone(),
+            Member::Spilled(_, matrix) => vec![matrix.rows(), matrix.columns()],
+        }
+    }
+
+    // length of the member's .npy encoding
+    fn npy_len(&self) -> u64 {
+        let values: usize = self.shape().iter().product();
+        npy_header(&self.shape()).len() as u64 + 4 * values as u64
+    }
+
+    // pass the .npy encoding to `write` in pieces
+    fn write_npy(&self, write: &mut dyn FnMut(&[u8]) -> io::Result<()>) -> io::Result<()> {
+        match self {
+            Member::Array(array) => write(&encode_npy(array)?),
+            Member::Spilled(_, matrix) => {
+                write(&npy_header(&self.shape()))?;
+                let mut bytes = Vec::new();
+                matrix.for_each_block(SPILL_BLOCK_ROWS, |values| {
+                    bytes.clear();
+                    for value in values {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    write(&bytes)
+                })
+            }
+        }
+    }
+}
+
+// CRC-32 (IEEE 802.3, reflected) as zip requires, continued over `bytes`
+// from a running `crc` (start from 0)
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Write `arrays` as an .npz archive: an uncompressed zip of one
+/// `<name>.npy` member per array, which `numpy.load` opens by name.
+pub fn write_npz<W: Write>(writer: &mut W, arrays: &[NamedArray]) -> io::Result<()> {
+    let members: Vec<Member> = arrays.iter().map(Member::Array).collect();
+    write_npz_members(writer, &members)
+}
+
+/// `write_npz` for members that may live on disk. Spilled matrices are read
+/// twice, once for the checksum the zip header needs and once to copy them.
+pub fn write_npz_members<W: Write>(writer: &mut W, members: &[Member]) -> io::Result<()> {
+    let too_large = || invalid("npz archive exceeds 4 GiB");
+    // 1980-01-01 00:00, the earliest DOS timestamp
+    let (time, date) = (0u16, 0x21u16);
+
+    let mut central = Vec::new();
+    let mut offset = 0u32;
+    for member in members {
+        let name = format!("{}.npy", member.name());
+        let size = u32::try_from(member.npy_len()).map_err(|_| too_large())?;
+        let mut crc = 0;
+        member.write_npy(&mut |bytes| {
+            crc = crc32_update(crc, bytes);
+            Ok(())
+        })?;
+
+        // fields shared by the local and central headers
+        let mut common = Vec::new();
+        common.extend_from_slice(&20u16.to_le_bytes()); // version needed: 2.0
+        common.extend_from_slice(&0u16.to_le_bytes()); // flags
+        common.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+        common.extend_from_slice(&time.to_le_bytes());
+        common.extend_from_slice(&date.to_le_bytes());
+        common.extend_from_slice(&crc.to_le_bytes());
+        common.extend_from_slice(&size.to_le_bytes()); // compressed
+        common.extend_from_slice(&size.to_le_bytes()); // uncompressed
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+
+        writer.write_all(&0x0403_4b50u32.to_le_bytes())?;
+        writer.write_all(&common)?;
+        writer.write_all(name.as_bytes())?;
+        member.write_npy(&mut |bytes| writer.write_all(bytes))?;
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&common);
+        central.extend_from_slice(&[0; 6]); // comment length, disk, internal attributes
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+
+        let entry = 30 + name.len() as u64 + size as u64;
+        offset = u32::try_from(offset as u64 + entry).map_err(|_| too_large())?;
+    }
+
+    let central_size = u32::try_from(central.len()).map_err(|_| too_large())?;
+    writer.write_all(&central)?;
+    writer.write_all(&0x0605_4b50u32.to_le_bytes())?;
+    writer.write_all(&[0; 4])?; // disk numbers
+    writer.write_all(&(members.len() as u16).to_le_bytes())?;
+    writer.write_all(&(members.len() as u16).to_le_bytes())?;
+    writer.write_all(&central_size.to_le_bytes())?;
+    writer.write_all(&offset.to_le_bytes())?;
+    writer.write_all(&0u16.to_le_bytes()) // comment length
+}
+
+/// `encode_npy` into a newly created file at `path`.
+pub fn write_npy_file<P: AsRef<Path>>(path: P, array: &NamedArray) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&encode_npy(array)?)?;
+    writer.flush()
+}
+
+/// `write_npz` into a newly created file at `path`.
+pub fn write_npz_file<P: AsRef<Path>>(path: P, arrays: &[NamedArray]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_npz(&mut writer, arrays)?;
+    writer.flush()
+}
+
+/// A member's .npy encoding into a newly created file at `path`.
+pub fn write_npy_member_file<P: AsRef<Path>>(path: P, member: &Member) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    member.write_npy(&mut |bytes| writer.write_all(bytes))?;
+    writer.flush()
+}
+
+/// `write_npz_members` into a newly created file at `path`.
+pub fn write_npz_members_file<P: AsRef<Path>>(path: P, members: &[Member]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_npz_members(&mut writer, members)?;
+    writer.flush()
+}
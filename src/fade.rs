@@ -0,0 +1,52 @@
+// Gain ramps at the start and end of a clip.
+
+/// Shape of a fade's gain ramp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeCurve {
+    /// Gain rises linearly in amplitude.
+    Linear,
+    /// Gain rises linearly in decibels (from -60 dB), which sounds even to
+    /// the ear; starts from true silence.
+    Log,
+}
+
+impl FadeCurve {
+    // gain at position t in [0, 1] through a fade-in
+    fn gain(self, t: f32) -> f32 {
+        match self {
+            FadeCurve::Linear => t,
+            FadeCurve::Log if t <= 0.0 => 0.0,
+            FadeCurve::Log => 10f32.powf((1.0 - t) * -60.0 / 20.0),
+        }
+    }
+}
+
+/// Apply a fade-in over the first `fade_in` seconds and a fade-out over the
+/// last `fade_out` seconds of interleaved audio, in place. Fades longer
+/// than the clip are clamped to it.
+pub fn apply_fades(
+    samples: &mut [f32],
+    channels: usize,
+    sample_rate: u32,
+    fade_in: f32,
+    fade_out: f32,
+    curve: FadeCurve,
+) {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let to_frames = |secs: f32| ((secs.max(0.0) * sample_rate as f32).round() as usize).min(frames);
+
+    let fade_in = to_frames(fade_in);
+    for i in 0..fade_in {
+        let gain = curve.gain(i as f32 / fade_in as f32);
+        samples[i * channels..(i + 1) * channels].iter_mut().for_each(|s| *s *= gain);
+    }
+
+    let fade_out = to_frames(fade_out);
+    for i in 0..fade_out {
+        // i counts back from the last frame, which ends at silence
+        let frame = frames - 1 - i;
+        let gain = curve.gain(i as f32 / fade_out as f32);
+        samples[frame * channels..(frame + 1) * channels].iter_mut().for_each(|s| *s *= gain);
+    }
+}
@@ -0,0 +1,75 @@
+// Wow and flutter from a recorded test tone (IEC 60386 style).
+
+use crate::biquad::Biquad;
+
+// the usual test tone
+pub const TEST_TONE_HZ: f32 = 3150.0;
+
+// band-pass Q used to isolate the test tone before zero-crossing timing
+const TONE_Q: f64 = 5.0;
+
+// The weighting curve peaks at 4 Hz, where the ear is most sensitive to
+// speed variation; a broad band-pass is a close approximation.
+const WEIGHTING_HZ: f64 = 4.0;
+const WEIGHTING_Q: f64 = 0.56;
+
+/// Speed deviation measurement.
+#[derive(Debug, Clone)]
+pub struct Flutter {
+    pub mean_frequency: f32,
+    pub deviation: Vec<(f32, f32)>, // (time in seconds, deviation in percent)
+    pub unweighted_rms: f32,        // percent
+    pub weighted_rms: f32,          // percent
+    pub weighted_peak: f32,         // percent, 2-sigma (exceeded 5% of the time)
+}
+
+/// Track the frequency of a test tone near `tone_hz` from its zero
+/// crossings and measure how much it wanders. `None` if fewer than a second
+/// of crossings were found.
+pub fn measure_flutter(samples: &[f32], sample_rate: u32, tone_hz: f32) -> Option<Flutter> {
+    // isolate the tone so noise doesn't add false crossings
+    let mut bandpass = Biquad::bandpass(sample_rate, tone_hz as f64, TONE_Q);
+    let filtered: Vec<f64> = samples.iter().map(|&s| bandpass.process(s as f64)).collect();
+
+    // rising zero crossings, interpolated to a fraction of a sample
+    let crossings: Vec<f64> = filtered.windows(2).enumerate()
+        .filter(|(_, w)| w[0] < 0.0 && w[1] >= 0.0)
+        .map(|(i, w)| i as f64 + w[0] / (w[0] - w[1]))
+        .collect();
+    if crossings.len() < tone_hz as usize {
+        return None;
+    }
+
+    // skip the filter's settling time
+    let settle = (crossings.len() / 50).max(1);
+    let periods: Vec<(f64, f64)> = crossings[settle..].windows(2)
+        .map(|w| (w[0] / sample_rate as f64, sample_rate as f64 / (w[1] - w[0])))
+        .collect();
+    let mean = periods.iter().map(|p| p.1).sum::<f64>() / periods.len() as f64;
+
+    // one deviation reading per cycle, so the readings run at ~tone_hz
+    let deviation: Vec<(f32, f32)> = periods.iter()
+        .map(|&(t, f)| (t as f32, ((f - mean) / mean * 100.0) as f32))
+        .collect();
+
+    let mut weighting = Biquad::bandpass(mean.round() as u32, WEIGHTING_HZ, WEIGHTING_Q);
+    let weighted: Vec<f32> = deviation.iter().map(|d| weighting.process(d.1 as f64) as f32).collect();
+    // let the weighting filter settle as well
+    let weighted = &weighted[(mean as usize).min(weighted.len() / 2)..];
+
+    let rms = |values: &mut dyn Iterator<Item = f32>| {
+        let (sum, count) = values.fold((0.0f64, 0usize), |(s, c), v| (s + v as f64 * v as f64, c + 1));
+        (sum / count.max(1) as f64).sqrt() as f32
+    };
+    let mut magnitudes: Vec<f32> = weighted.iter().map(|w| w.abs()).collect();
+    magnitudes.sort_by(f32::total_cmp);
+    let weighted_peak = magnitudes[(magnitudes.len() as f32 * 0.95) as usize - 1];
+
+    Some(Flutter {
+        mean_frequency: mean as f32,
+        unweighted_rms: rms(&mut deviation.iter().map(|d| d.1)),
+        weighted_rms: rms(&mut weighted.iter().copied()),
+        weighted_peak,
+        deviation,
+    })
+}
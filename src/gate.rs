@@ -0,0 +1,47 @@
+// Spectral gate: STFT bins below a threshold are zeroed before
+// resynthesis, a simple denoiser (or isolator, with a high threshold).
+
+use crate::stft::{padded_stft, trimmed_istft, StftConfig};
+
+// the noise estimate of a bin is this percentile of its magnitudes over time
+const NOISE_PERCENTILE: f32 = 0.1;
+
+/// Where a bin has to reach to be let through.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GateThreshold {
+    /// A fixed level in dBFS (sine amplitude).
+    Absolute(f32),
+    /// This many dB above the bin's own noise estimate.
+    AboveNoise(f32),
+}
+
+/// Gate a mono signal; the output has the same length as the input.
+pub fn spectral_gate(samples: &[f32], config: StftConfig, threshold: GateThreshold) -> Vec<f32> {
+    let n = config.nfft;
+    let mut frames = padded_stft(samples, config);
+    if frames.is_empty() {
+        return samples.to_vec();
+    }
+
+    // a full-scale sine reaches |X| = nfft / 4 under a Hann window
+    let full_scale = n as f32 / 4.0;
+    let thresholds: Vec<f32> = match threshold {
+        GateThreshold::Absolute(db) => vec![full_scale * 10f32.powf(db / 20.0); n / 2 + 1],
+        GateThreshold::AboveNoise(db) => (0..=n / 2).map(|k| {
+            let mut bin: Vec<f32> = frames.iter().map(|frame| frame[k].norm()).collect();
+            bin.sort_by(f32::total_cmp);
+            let noise = bin[((bin.len() - 1) as f32 * NOISE_PERCENTILE) as usize];
+            noise * 10f32.powf(db / 20.0)
+        }).collect(),
+    };
+
+    for frame in &mut frames {
+        for (c, &limit) in frame.iter_mut().zip(&thresholds) {
+            if c.norm() < limit {
+                *c = Default::default();
+            }
+        }
+    }
+
+    trimmed_istft(&frames, config, samples.len())
+}
@@ -0,0 +1,93 @@
+// Test signal generators: tones, sweeps, noise and impulses as mono f32
+// samples in [-1, 1], scaled to a given peak amplitude.
+
+use std::f64::consts::PI;
+
+/// A sine tone at `frequency` Hz.
+pub fn sine(frequency: f64, sample_rate: u32, len: usize, amplitude: f32) -> Vec<f32> {
+    let step = 2.0 * PI * frequency / sample_rate as f64;
+    (0..len).map(|n| amplitude * (step * n as f64).sin() as f32).collect()
+}
+
+/// An exponential (Farina) sine sweep from `f0` to `f1` Hz over `len`
+/// samples: equal time per octave, as deconvolution of room and system
+/// responses expects.
+pub fn log_sweep(f0: f64, f1: f64, sample_rate: u32, len: usize, amplitude: f32) -> Vec<f32> {
+    let ratio = (f1 / f0).ln();
+    if ratio.abs() < 1e-9 {
+        return sine(f0, sample_rate, len, amplitude);
+    }
+    let duration = len as f64 / sample_rate as f64;
+    let scale = 2.0 * PI * f0 * duration / ratio;
+    (0..len).map(|n| {
+        let t = n as f64 / sample_rate as f64;
+        amplitude * (scale * ((t / duration * ratio).exp() - 1.0)).sin() as f32
+    }).collect()
+}
+
+/// SplitMix64: a tiny pure-integer generator, so a given seed yields the
+/// same noise on every run and platform.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform on [-1, 1). The top 24 bits convert to f32 exactly, so no
+    /// rounding mode or libm is involved.
+    pub fn uniform(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u32 << 23) as f32 - 1.0
+    }
+}
+
+/// Uniform white noise peaking at `amplitude`, drawn from `seed`.
+pub fn white_noise(len: usize, amplitude: f32, seed: u64) -> Vec<f32> {
+    let mut rng = Rng::new(seed);
+    (0..len).map(|_| amplitude * rng.uniform()).collect()
+}
+
+/// Pink (-3 dB/octave) noise: white noise through Paul Kellet's refined
+/// filter, normalized to peak at `amplitude`.
+pub fn pink_noise(len: usize, amplitude: f32, seed: u64) -> Vec<f32> {
+    let mut rng = Rng::new(seed);
+    let mut b = [0.0f32; 7];
+    let mut pink: Vec<f32> = (0..len).map(|_| {
+        let white = rng.uniform();
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.153852;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let out = b.iter().sum::<f32>() + white * 0.5362;
+        b[6] = white * 0.115926;
+        out
+    }).collect();
+
+    let peak = pink.iter().fold(0.0f32, |p, &s| p.max(s.abs()));
+    if peak > 0.0 {
+        for s in &mut pink {
+            *s *= amplitude / peak;
+        }
+    }
+    pink
+}
+
+/// A single sample at `amplitude` followed by silence.
+pub fn impulse(len: usize, amplitude: f32) -> Vec<f32> {
+    let mut samples = vec![0.0; len];
+    if let Some(first) = samples.first_mut() {
+        *first = amplitude;
+    }
+    samples
+}
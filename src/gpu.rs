@@ -0,0 +1,197 @@
+// Batched FFT on the GPU (wgpu compute shaders), for transforming the
+// thousands of frames of a long STFT in one go. Radix-2 Stockham passes, so
+// only power-of-two sizes; callers fall back to rustfft for the rest.
+
+use std::sync::mpsc;
+
+use tracing::{debug, instrument};
+use wgpu::util::DeviceExt;
+
+use crate::stft::StftConfig;
+
+// one thread per butterfly; dispatches wrap into a second dimension past
+// the per-dimension workgroup limit
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_GROUPS_X: u32 = 65535;
+
+// complex values per storage buffer, kept well below the default 128 MiB
+// binding limit
+const MAX_BATCH_VALUES: usize = 1 << 23;
+
+const SHADER: &str = r#"
+struct Params {
+    n: u32,          // transform size
+    span: u32,       // size of the sub-transforms merged by this pass
+    butterflies: u32, // n / 2 times the frames in the batch
+    stride: u32,     // threads per row of workgroups
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> src: array<vec2<f32>>;
+@group(0) @binding(2) var<storage, read_write> dst: array<vec2<f32>>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let t = id.x + id.y * params.stride;
+    if (t >= params.butterflies) {
+        return;
+    }
+    let half = params.n / 2u;
+    let base = (t / half) * params.n;
+    let i = t % half;
+    let k = i & (params.span - 1u);
+
+    let a = src[base + i];
+    let b = src[base + i + half];
+    let angle = -3.14159265358979 * f32(k) / f32(params.span);
+    let w = vec2<f32>(cos(angle), sin(angle));
+    let bw = vec2<f32>(b.x * w.x - b.y * w.y, b.x * w.y + b.y * w.x);
+
+    let j = (i - k) * 2u + k;
+    dst[base + j] = a + bw;
+    dst[base + j + params.span] = a - bw;
+}
+"#;
+
+/// A GPU device with the FFT pipeline compiled.
+pub struct GpuFft {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuFft {
+    /// Open the first adapter that can run compute shaders. Fails when
+    /// there is none, e.g. on a headless machine without drivers.
+    pub fn new() -> Result<Self, String> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or("no GPU adapter found")?;
+        let info = adapter.get_info();
+        if !adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS) {
+            return Err(format!("{} ({:?}) cannot run compute shaders", info.name, info.backend));
+        }
+        debug!("GPU adapter: {} ({:?})", info.name, info.backend);
+
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("fft"),
+            required_features: wgpu::Features::empty(),
+            required_limits: adapter.limits(),
+            memory_hints: wgpu::MemoryHints::Performance,
+        }, None))
+        .map_err(|e| e.to_string())?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("fft"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("fft"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Ok(GpuFft { device, queue, pipeline })
+    }
+
+    /// Magnitudes of the windowed STFT of `samples`, the same frames as
+    /// `Spectrogram::compute_windowed` gives (bins 0..=nfft/2). `config.nfft`
+    /// must be a power of two.
+    #[instrument(name = "stft", level = "debug", skip_all, fields(nfft = config.nfft, hop = config.hop, backend = "gpu"))]
+    pub fn stft_magnitudes(&self, samples: &[f32], config: StftConfig, window: &[f32]) -> Result<Vec<Vec<f32>>, String> {
+        let n = config.nfft;
+        if !n.is_power_of_two() || n < 2 {
+            return Err(format!("GPU FFT needs a power-of-two size (got {})", n));
+        }
+        let frames = config.num_frames(samples.len());
+        let per_batch = (MAX_BATCH_VALUES / n).max(1);
+
+        let mut magnitudes = Vec::with_capacity(frames);
+        let mut first = 0;
+        while first < frames {
+            let count = per_batch.min(frames - first);
+            let mut input = Vec::with_capacity(count * n * 2);
+            for frame in first..first + count {
+                let start = frame * config.hop;
+                for (&s, &w) in samples[start..start + n].iter().zip(window) {
+                    input.extend_from_slice(&[s * w, 0.0]);
+                }
+            }
+            let output = self.transform(&input, n, count)?;
+            magnitudes.extend(output.chunks_exact(n * 2).map(|spectrum| {
+                spectrum[..n + 2].chunks_exact(2).map(|c| c[0].hypot(c[1])).collect()
+            }));
+            first += count;
+        }
+        Ok(magnitudes)
+    }
+
+    // forward FFT of `count` interleaved (re, im) frames of `n` points
+    fn transform(&self, input: &[f32], n: usize, count: usize) -> Result<Vec<f32>, String> {
+        let size = std::mem::size_of_val(input) as u64;
+        let usage = wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST;
+        let buffers = [
+            self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("fft ping"),
+                contents: bytemuck::cast_slice(input),
+                usage,
+            }),
+            self.device.create_buffer(&wgpu::BufferDescriptor { label: Some("fft pong"), size, usage, mapped_at_creation: false }),
+        ];
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("fft readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let butterflies = (count * n / 2) as u32;
+        let groups = butterflies.div_ceil(WORKGROUP_SIZE);
+        let (groups_x, groups_y) = (groups.min(MAX_GROUPS_X), groups.div_ceil(MAX_GROUPS_X));
+        let layout = self.pipeline.get_bind_group_layout(0);
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("fft") });
+        let passes = n.trailing_zeros() as usize;
+        for pass in 0..passes {
+            let params = [n as u32, 1 << pass, butterflies, groups_x * WORKGROUP_SIZE];
+            let uniform = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("fft params"),
+                contents: bytemuck::cast_slice(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+            let (src, dst) = (&buffers[pass % 2], &buffers[(pass + 1) % 2]);
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("fft pass"),
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: uniform.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: src.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: dst.as_entire_binding() },
+                ],
+            });
+            let mut compute = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("fft pass"), timestamp_writes: None });
+            compute.set_pipeline(&self.pipeline);
+            compute.set_bind_group(0, &bind_group, &[]);
+            compute.dispatch_workgroups(groups_x, groups_y, 1);
+        }
+        encoder.copy_buffer_to_buffer(&buffers[passes % 2], 0, &readback, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv().map_err(|e| e.to_string())?.map_err(|e| e.to_string())?;
+
+        let output = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        Ok(output)
+    }
+}
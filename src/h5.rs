@@ -0,0 +1,41 @@
+// HDF5 analysis bundles: one file per recording with a group per kind of
+// result, for tools that want everything in one portable artifact.
+
+use std::path::Path;
+
+use hdf5::types::VarLenUnicode;
+use hdf5::File;
+use ndarray::{ArrayViewD, IxDyn};
+
+use crate::export::NamedArray;
+
+/// Write a bundle: a `metadata` group carrying `numbers` and `text` as
+/// attributes, then one group per entry of `groups` holding its arrays as
+/// f32 datasets (row-major, with the arrays' shapes).
+pub fn write_h5_file<P: AsRef<Path>>(
+    path: P,
+    numbers: &[(&str, f64)],
+    text: &[(&str, &str)],
+    groups: &[(&str, Vec<NamedArray>)],
+) -> hdf5::Result<()> {
+    let file = File::create(path)?;
+
+    let metadata = file.create_group("metadata")?;
+    for (name, value) in numbers {
+        metadata.new_attr::<f64>().create(*name)?.write_scalar(value)?;
+    }
+    for (name, value) in text {
+        let value: VarLenUnicode = value.parse().map_err(|_| format!("{} is not valid HDF5 text", name))?;
+        metadata.new_attr::<VarLenUnicode>().create(*name)?.write_scalar(&value)?;
+    }
+
+    for (group_name, arrays) in groups {
+        let group = file.create_group(group_name)?;
+        for array in arrays {
+            let view = ArrayViewD::from_shape(IxDyn(&array.shape), &array.data)
+                .map_err(|e| format!("{}: {}", array.name, e))?;
+            group.new_dataset_builder().with_data(view).create(array.name.as_str())?;
+        }
+    }
+    file.flush()
+}
@@ -0,0 +1,51 @@
+// Harmonic/percussive source separation by median filtering the
+// spectrogram (Fitzgerald 2010): sustained tones are smooth along time,
+// hits are smooth along frequency.
+
+use crate::stft::{padded_stft, trimmed_istft, StftConfig};
+
+/// The two parts of a signal; they add back up to (nearly) the input.
+#[derive(Debug, Clone)]
+pub struct Separation {
+    pub harmonic: Vec<f32>,
+    pub percussive: Vec<f32>,
+}
+
+// median of the values of `len` indices around `center`, clamped to the range
+fn median_around(len: usize, center: usize, half: usize, value: impl Fn(usize) -> f32) -> f32 {
+    let (lo, hi) = (center.saturating_sub(half), (center + half).min(len - 1));
+    let mut window: Vec<f32> = (lo..=hi).map(value).collect();
+    let mid = window.len() / 2;
+    *window.select_nth_unstable_by(mid, f32::total_cmp).1
+}
+
+/// Split a mono signal. Each bin's magnitude is median filtered over
+/// `time_kernel` frames (enhancing harmonics) and over `freq_kernel` bins
+/// (enhancing percussion); the two filtered spectrograms give Wiener-style
+/// soft masks applied to the STFT before resynthesis.
+pub fn hpss(samples: &[f32], config: StftConfig, time_kernel: usize, freq_kernel: usize) -> Separation {
+    let frames = padded_stft(samples, config);
+    if frames.is_empty() {
+        return Separation { harmonic: samples.to_vec(), percussive: vec![0.0; samples.len()] };
+    }
+    let magnitudes: Vec<Vec<f32>> = frames.iter()
+        .map(|frame| frame.iter().map(|c| c.norm()).collect())
+        .collect();
+    let (num_frames, num_bins) = (frames.len(), config.nfft / 2 + 1);
+
+    let mut harmonic = frames.clone();
+    let mut percussive = frames;
+    for t in 0..num_frames {
+        for k in 0..num_bins {
+            let h = median_around(num_frames, t, time_kernel / 2, |i| magnitudes[i][k]);
+            let p = median_around(num_bins, k, freq_kernel / 2, |j| magnitudes[t][j]);
+            let (h2, p2) = (h * h, p * p);
+            let mask = if h2 + p2 > 0.0 { h2 / (h2 + p2) } else { 0.5 };
+            harmonic[t][k] *= mask;
+            percussive[t][k] *= 1.0 - mask;
+        }
+    }
+
+    let resynthesize = |frames: &[Vec<_>]| trimmed_istft(frames, config, samples.len());
+    Separation { harmonic: resynthesize(&harmonic), percussive: resynthesize(&percussive) }
+}
@@ -0,0 +1,117 @@
+// Mains hum detection: 50 or 60 Hz and its harmonics standing above the
+// local noise floor of a long spectrum.
+
+use crate::biquad::Biquad;
+use crate::spectrum::Spectrum;
+
+pub const MAINS_FREQUENCIES: [f32; 2] = [50.0, 60.0];
+
+// mains frequency drifts a little; search this far (relative) around each harmonic
+const SEARCH_TOLERANCE: f32 = 0.005;
+
+// the local floor is the median of bins within this many Hz of a harmonic,
+// excluding the search window itself
+const FLOOR_SPAN_HZ: f32 = 10.0;
+
+// a harmonic counts as present this far above its local floor; the max of a
+// few noise bins already sits ~6 dB over their median
+pub const PRESENT_DB: f32 = 12.0;
+
+/// One harmonic of a mains candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HumHarmonic {
+    pub harmonic: usize, // 1 = the fundamental
+    pub frequency: f32,  // strongest bin in the search window
+    pub level_db: f32,   // sine amplitude in dBFS
+    pub prominence_db: f32, // above the local noise floor
+}
+
+impl HumHarmonic {
+    pub fn is_present(&self) -> bool {
+        self.prominence_db >= PRESENT_DB
+    }
+}
+
+/// The harmonics of one mains frequency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HumCandidate {
+    pub mains_hz: f32,
+    pub harmonics: Vec<HumHarmonic>,
+}
+
+impl HumCandidate {
+    fn score(&self) -> f32 {
+        self.harmonics.iter().filter(|h| h.is_present()).map(|h| h.prominence_db).sum()
+    }
+}
+
+/// Measure the first `harmonics` harmonics of both mains frequencies in an
+/// (unwindowed) spectrum of `signal_len` samples.
+pub fn hum_candidates(spectrum: &Spectrum, signal_len: usize, harmonics: usize) -> Vec<HumCandidate> {
+    let bin_width = spectrum.frequencies.get(1).copied().unwrap_or(1.0);
+    let bins_in = |lo: f32, hi: f32| {
+        let first = (lo / bin_width).ceil().max(0.0) as usize;
+        let last = ((hi / bin_width).floor() as usize).min(spectrum.magnitudes.len().saturating_sub(1));
+        first..=last
+    };
+
+    MAINS_FREQUENCIES.iter().map(|&mains| {
+        let harmonics = (1..=harmonics).filter_map(|n| {
+            let target = mains * n as f32;
+            let tolerance = (target * SEARCH_TOLERANCE).max(bin_width);
+            let window = bins_in(target - tolerance, target + tolerance);
+            let peak = window.clone().max_by(|&a, &b| spectrum.magnitudes[a].total_cmp(&spectrum.magnitudes[b]))?;
+
+            let mut floor: Vec<f32> = bins_in(target - FLOOR_SPAN_HZ, target + FLOOR_SPAN_HZ)
+                .filter(|k| !window.contains(k))
+                .map(|k| spectrum.magnitudes[k])
+                .collect();
+            if floor.is_empty() {
+                return None;
+            }
+            floor.sort_by(f32::total_cmp);
+            let median = floor[floor.len() / 2].max(1e-12);
+
+            let magnitude = spectrum.magnitudes[peak];
+            Some(HumHarmonic {
+                harmonic: n,
+                frequency: spectrum.frequencies[peak],
+                level_db: 20.0 * (2.0 * magnitude / signal_len.max(1) as f32).max(1e-12).log10(),
+                prominence_db: 20.0 * (magnitude.max(1e-12) / median).log10(),
+            })
+        }).collect();
+        HumCandidate { mains_hz: mains, harmonics }
+    }).collect()
+}
+
+/// Which mains frequency is present, judged by the summed prominence of its
+/// present harmonics; `None` if neither has any.
+pub fn detect_mains(candidates: &[HumCandidate]) -> Option<f32> {
+    candidates.iter()
+        .filter(|c| c.score() > 0.0)
+        .max_by(|a, b| a.score().total_cmp(&b.score()))
+        .map(|c| c.mains_hz)
+}
+
+/// Notch out `mains_hz` and its first `harmonics` harmonics (those below
+/// Nyquist) from interleaved `samples` in place, one cascade per channel.
+pub fn remove_hum(samples: &mut [f32], channels: usize, sample_rate: u32, mains_hz: f32, harmonics: usize, q: f32) {
+    let channels = channels.max(1);
+    let nyquist = sample_rate as f32 / 2.0;
+    let cascade: Vec<Biquad> = (1..=harmonics)
+        .map(|n| mains_hz * n as f32)
+        .take_while(|&f| f < nyquist)
+        .map(|f| Biquad::notch(sample_rate, f as f64, q as f64))
+        .collect();
+
+    for ch in 0..channels {
+        let mut filters = cascade.clone();
+        for sample in samples.iter_mut().skip(ch).step_by(channels) {
+            let mut x = *sample as f64;
+            for filter in &mut filters {
+                x = filter.process(x);
+            }
+            *sample = x as f32;
+        }
+    }
+}
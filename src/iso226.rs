@@ -0,0 +1,43 @@
+// Equal-loudness contours of ISO 226:2003: the SPL at which a pure tone of
+// each frequency sounds as loud as a 1 kHz tone of a given level (phon).
+
+// the standard's third-octave frequencies, with its exponent of loudness
+// perception (af), magnitude of the linear transfer function normalized at
+// 1 kHz (Lu, dB) and threshold of hearing (Tf, dB SPL)
+const FREQUENCIES: [f32; 29] = [
+    20.0, 25.0, 31.5, 40.0, 50.0, 63.0, 80.0, 100.0, 125.0, 160.0, 200.0, 250.0, 315.0, 400.0, 500.0,
+    630.0, 800.0, 1000.0, 1250.0, 1600.0, 2000.0, 2500.0, 3150.0, 4000.0, 5000.0, 6300.0, 8000.0,
+    10000.0, 12500.0,
+];
+const AF: [f32; 29] = [
+    0.532, 0.506, 0.480, 0.455, 0.432, 0.409, 0.387, 0.367, 0.349, 0.330, 0.315, 0.301, 0.288, 0.276,
+    0.267, 0.259, 0.253, 0.250, 0.246, 0.244, 0.243, 0.243, 0.243, 0.242, 0.242, 0.245, 0.254, 0.271,
+    0.301,
+];
+const LU: [f32; 29] = [
+    -31.6, -27.2, -23.0, -19.1, -15.9, -13.0, -10.3, -8.1, -6.2, -4.5, -3.1, -2.0, -1.1, -0.4, 0.0,
+    0.3, 0.5, 0.0, -2.7, -4.1, -1.0, 1.7, 2.5, 1.2, -2.1, -7.1, -11.2, -10.7, -3.1,
+];
+const TF: [f32; 29] = [
+    78.5, 68.7, 59.5, 51.1, 44.0, 37.5, 31.5, 26.5, 22.1, 17.9, 14.4, 11.4, 8.6, 6.2, 4.4, 3.0, 2.2,
+    2.4, 3.5, 1.7, -1.3, -4.2, -6.0, -5.4, -1.5, 6.0, 12.6, 13.9, 12.3,
+];
+
+/// Loudness levels the standard covers (phon); above 80 phon it holds only
+/// up to 1 kHz.
+pub const MIN_PHON: f32 = 0.0;
+pub const MAX_PHON: f32 = 90.0;
+
+/// The contour of `phon` as (frequency Hz, dB SPL) points from 20 Hz to
+/// 12.5 kHz, or None outside the standard's range.
+pub fn equal_loudness_contour(phon: f32) -> Option<Vec<(f32, f32)>> {
+    if !(MIN_PHON..=MAX_PHON).contains(&phon) {
+        return None;
+    }
+    let points = (0..FREQUENCIES.len()).map(|i| {
+        let a = 4.47e-3 * (10f32.powf(0.025 * phon) - 1.15)
+            + (0.4 * 10f32.powf((TF[i] + LU[i]) / 10.0 - 9.0)).powf(AF[i]);
+        (FREQUENCIES[i], 10.0 / AF[i] * a.log10() - LU[i] + 94.0)
+    });
+    Some(points.collect())
+}
@@ -0,0 +1,95 @@
+// The per-sample loops that dominate spectrogram time after the FFT itself:
+// applying the window, |X| of every bin and the conversion to dB. With the
+// simd feature each runs eight lanes at a time through `wide`; the scalar
+// loops handle the remainder, and everything without the feature.
+
+use rustfft::num_complex::Complex;
+
+/// `out[i] = samples[i] * window[i]` as a real-valued FFT input, over the
+/// shortest of the three slices.
+pub fn window_frame(samples: &[f32], window: &[f32], out: &mut [Complex<f32>]) {
+    let n = samples.len().min(window.len()).min(out.len());
+    let done = lanes::window_frame(&samples[..n], &window[..n], &mut out[..n]);
+    for ((o, &s), &w) in out[done..n].iter_mut().zip(&samples[done..n]).zip(&window[done..n]) {
+        *o = Complex { re: s * w, im: 0.0 };
+    }
+}
+
+/// `out[i] = |bins[i]|`, over the shorter slice.
+pub fn magnitudes(bins: &[Complex<f32>], out: &mut [f32]) {
+    let n = bins.len().min(out.len());
+    let done = lanes::magnitudes(&bins[..n], &mut out[..n]);
+    for (o, c) in out[done..n].iter_mut().zip(&bins[done..n]) {
+        *o = c.norm();
+    }
+}
+
+/// `out[i] = 20 log10(values[i] + floor)`, over the shorter slice; the
+/// floor keeps silent bins finite.
+pub fn amplitude_db(values: &[f32], floor: f32, out: &mut [f32]) {
+    let n = values.len().min(out.len());
+    let done = lanes::amplitude_db(&values[..n], floor, &mut out[..n]);
+    for (o, &v) in out[done..n].iter_mut().zip(&values[done..n]) {
+        *o = 20.0 * (v + floor).log10();
+    }
+}
+
+// each function handles a multiple of eight elements (of equal-length
+// slices) and returns how many, leaving the rest to the scalar loop
+#[cfg(feature = "simd")]
+mod lanes {
+    use rustfft::num_complex::Complex;
+    use wide::f32x8;
+
+    const LANES: usize = 8;
+
+    fn load(chunk: &[f32]) -> f32x8 {
+        f32x8::from(<[f32; LANES]>::try_from(chunk).unwrap())
+    }
+
+    pub fn window_frame(samples: &[f32], window: &[f32], out: &mut [Complex<f32>]) -> usize {
+        let done = out.len() / LANES * LANES;
+        for ((s, w), o) in samples[..done].chunks_exact(LANES).zip(window.chunks_exact(LANES)).zip(out.chunks_exact_mut(LANES)) {
+            for (o, re) in o.iter_mut().zip((load(s) * load(w)).to_array()) {
+                *o = Complex { re, im: 0.0 };
+            }
+        }
+        done
+    }
+
+    pub fn magnitudes(bins: &[Complex<f32>], out: &mut [f32]) -> usize {
+        let done = out.len() / LANES * LANES;
+        for (c, o) in bins[..done].chunks_exact(LANES).zip(out.chunks_exact_mut(LANES)) {
+            let re = f32x8::from(std::array::from_fn::<f32, LANES, _>(|i| c[i].re));
+            let im = f32x8::from(std::array::from_fn::<f32, LANES, _>(|i| c[i].im));
+            o.copy_from_slice(&re.mul_add(re, im * im).sqrt().to_array());
+        }
+        done
+    }
+
+    pub fn amplitude_db(values: &[f32], floor: f32, out: &mut [f32]) -> usize {
+        let done = out.len() / LANES * LANES;
+        let (floor, twenty) = (f32x8::splat(floor), f32x8::splat(20.0));
+        for (v, o) in values[..done].chunks_exact(LANES).zip(out.chunks_exact_mut(LANES)) {
+            o.copy_from_slice(&(twenty * (load(v) + floor).log10()).to_array());
+        }
+        done
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+mod lanes {
+    use rustfft::num_complex::Complex;
+
+    pub fn window_frame(_: &[f32], _: &[f32], _: &mut [Complex<f32>]) -> usize {
+        0
+    }
+
+    pub fn magnitudes(_: &[Complex<f32>], _: &mut [f32]) -> usize {
+        0
+    }
+
+    pub fn amplitude_db(_: &[f32], _: f32, _: &mut [f32]) -> usize {
+        0
+    }
+}
@@ -0,0 +1,71 @@
+// Musical key estimation: the summed chroma of a recording correlated with
+// the Krumhansl-Kessler major and minor key profiles in all 12 rotations.
+
+const MAJOR_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+pub const PITCH_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Major,
+    Minor,
+}
+
+/// The best-matching key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+    pub tonic: usize, // pitch class, C = 0
+    pub mode: Mode,
+    pub correlation: f32, // Pearson correlation with the key's profile
+}
+
+impl Key {
+    /// E.g. "A minor" or "F# major".
+    pub fn name(&self) -> String {
+        let mode = match self.mode {
+            Mode::Major => "major",
+            Mode::Minor => "minor",
+        };
+        format!("{} {}", PITCH_NAMES[self.tonic], mode)
+    }
+}
+
+fn pearson(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len() as f32;
+    let (mean_a, mean_b) = (a.iter().sum::<f32>() / n, b.iter().sum::<f32>() / n);
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a) * (x - mean_a);
+        var_b += (y - mean_b) * (y - mean_b);
+    }
+    if var_a > 0.0 && var_b > 0.0 { cov / (var_a * var_b).sqrt() } else { 0.0 }
+}
+
+/// Estimate the key from per-frame chroma vectors (as from
+/// `structure::chroma`); `None` if there is no pitched content.
+pub fn detect_key(chroma: &[Vec<f32>]) -> Option<Key> {
+    let mut total = [0.0f32; 12];
+    for frame in chroma {
+        for (t, &c) in total.iter_mut().zip(frame) {
+            *t += c;
+        }
+    }
+    if total.iter().all(|&t| t == 0.0) {
+        return None;
+    }
+
+    let mut best: Option<Key> = None;
+    for (mode, profile) in [(Mode::Major, MAJOR_PROFILE), (Mode::Minor, MINOR_PROFILE)] {
+        for tonic in 0..12 {
+            // the profile rotated so its tonic lands on pitch class `tonic`
+            let rotated: Vec<f32> = (0..12).map(|pc| profile[(pc + 12 - tonic) % 12]).collect();
+            let correlation = pearson(&total, &rotated);
+            if best.is_none_or(|key| correlation > key.correlation) {
+                best = Some(Key { tonic, mode, correlation });
+            }
+        }
+    }
+    best
+}
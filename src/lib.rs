@@ -0,0 +1,61 @@
+pub mod analyzer;
+pub mod animation;
+pub mod auditory;
+pub mod bands;
+pub mod baseline;
+pub mod biquad;
+pub mod budget;
+pub mod bitdepth;
+pub mod classify;
+pub mod clicks;
+pub mod crossover;
+pub mod dither;
+pub mod dynamics;
+pub mod eq;
+pub mod export;
+pub mod fade;
+pub mod flutter;
+pub mod gammatone;
+pub mod gate;
+pub mod generate;
+pub mod hpss;
+pub mod hum;
+pub mod iso226;
+pub mod kernels;
+pub mod key;
+pub mod lpc;
+pub mod mat;
+pub mod meter;
+pub mod midi;
+pub mod modulation;
+pub mod nmf;
+pub mod partials;
+pub mod pass;
+pub mod phase;
+pub mod pitch;
+pub mod resample;
+pub mod reverb;
+pub mod rhythm;
+pub mod room;
+pub mod sample;
+pub mod silence;
+pub mod spectrum;
+pub mod spill;
+pub mod stereo;
+pub mod stft;
+pub mod structure;
+pub mod transfer;
+pub mod tuner;
+pub mod vibrato;
+pub mod wav;
+pub mod window;
+pub mod writer;
+
+#[cfg(feature = "async")]
+pub mod async_io;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "hdf5")]
+pub mod h5;
+#[cfg(feature = "parquet")]
+pub mod pq;
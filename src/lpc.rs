@@ -0,0 +1,137 @@
+// Linear predictive coding (autocorrelation method, Levinson-Durbin) and
+// formant estimation from the roots of the prediction polynomial.
+
+use std::f64::consts::PI;
+
+use rustfft::num_complex::Complex;
+
+use crate::stft::hann;
+
+// pre-emphasis flattens the glottal tilt before fitting
+const PRE_EMPHASIS: f32 = 0.97;
+
+// roots wider than this (Hz) are spectral shaping, not formants
+const MAX_FORMANT_BANDWIDTH: f32 = 400.0;
+const MIN_FORMANT_HZ: f32 = 90.0;
+
+const ROOT_ITERATIONS: usize = 500;
+
+/// Prediction polynomial A(z) = 1 + a1 z^-1 + ... + ap z^-p of one frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lpc {
+    pub coefficients: Vec<f32>, // [1, a1, .., ap]
+    pub error: f32,             // prediction error power
+}
+
+/// A resonance of the LPC model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Formant {
+    pub frequency: f32,
+    pub bandwidth: f32,
+}
+
+/// Autocorrelation of `frame` at lags 0..=order.
+pub fn autocorrelation(frame: &[f32], order: usize) -> Vec<f64> {
+    (0..=order).map(|lag| {
+        frame.iter().zip(frame.iter().skip(lag)).map(|(&a, &b)| a as f64 * b as f64).sum()
+    }).collect()
+}
+
+/// Solve the normal equations for an order-p predictor from autocorrelation
+/// `r` (lags 0..=p). `None` for a silent frame.
+pub fn levinson_durbin(r: &[f64]) -> Option<Lpc> {
+    let order = r.len().checked_sub(1)?;
+    if r[0] <= 0.0 {
+        return None;
+    }
+    let mut a = vec![0.0f64; order + 1];
+    a[0] = 1.0;
+    let mut error = r[0];
+
+    for i in 1..=order {
+        let acc: f64 = (1..i).map(|j| a[j] * r[i - j]).sum::<f64>() + r[i];
+        let k = -acc / error;
+        let previous = a.clone();
+        for j in 1..i {
+            a[j] = previous[j] + k * previous[i - j];
+        }
+        a[i] = k;
+        error *= 1.0 - k * k;
+        if error <= 0.0 {
+            break;
+        }
+    }
+
+    Some(Lpc { coefficients: a.iter().map(|&c| c as f32).collect(), error: error.max(0.0) as f32 })
+}
+
+/// Pre-emphasize, Hann-window and fit an order-`order` LPC model to `frame`.
+pub fn lpc(frame: &[f32], order: usize) -> Option<Lpc> {
+    let window = hann(frame.len());
+    let shaped: Vec<f32> = frame.iter().enumerate()
+        .map(|(i, &s)| (s - if i > 0 { PRE_EMPHASIS * frame[i - 1] } else { 0.0 }) * window[i])
+        .collect();
+    // a touch of white-noise correction keeps the recursion stable
+    let mut r = autocorrelation(&shaped, order);
+    r[0] *= 1.0 + 1e-9;
+    levinson_durbin(&r)
+}
+
+/// Magnitude of the model's spectrum, sqrt(error) / |A(e^jw)|, at each of
+/// `frequencies`, with the pre-emphasis undone so it lines up with the
+/// spectrum of the plain signal. `sample_rate` is that of the samples the
+/// model was fitted to, which after decimation need not be whole.
+pub fn envelope(lpc: &Lpc, frequencies: &[f32], sample_rate: f32) -> Vec<f32> {
+    let gain = (lpc.error as f64).sqrt();
+    frequencies.iter().map(|&f| {
+        let w = 2.0 * PI * f as f64 / sample_rate as f64;
+        let a: Complex<f64> = lpc.coefficients.iter().enumerate()
+            .map(|(k, &c)| Complex::from_polar(c as f64, -w * k as f64))
+            .sum();
+        let emphasis = (Complex::new(1.0, 0.0) - Complex::from_polar(PRE_EMPHASIS as f64, -w)).norm();
+        (gain / (a.norm() * emphasis).max(1e-12)) as f32
+    }).collect()
+}
+
+// all roots of the monic polynomial z^p + a1 z^(p-1) + .. + ap (Durand-Kerner)
+fn polynomial_roots(coefficients: &[f32]) -> Vec<Complex<f64>> {
+    let degree = coefficients.len().saturating_sub(1);
+    let eval = |z: Complex<f64>| coefficients.iter().fold(Complex::new(0.0, 0.0), |acc, &c| acc * z + c as f64);
+
+    let seed = Complex::from_polar(0.9, 0.4);
+    let mut roots: Vec<Complex<f64>> = (0..degree).map(|i| seed.powu(i as u32)).collect();
+    for _ in 0..ROOT_ITERATIONS {
+        let mut moved = 0.0f64;
+        for i in 0..degree {
+            let denominator = (0..degree)
+                .filter(|&j| j != i)
+                .fold(Complex::new(1.0, 0.0), |acc, j| acc * (roots[i] - roots[j]));
+            if denominator.norm() == 0.0 {
+                continue;
+            }
+            let step = eval(roots[i]) / denominator;
+            roots[i] -= step;
+            moved = moved.max(step.norm());
+        }
+        if moved < 1e-12 {
+            break;
+        }
+    }
+    roots
+}
+
+/// Formants of an LPC model, lowest first: roots in the upper half plane,
+/// above 90 Hz and narrower than 400 Hz.
+pub fn formants(lpc: &Lpc, sample_rate: u32) -> Vec<Formant> {
+    let rate = sample_rate as f64;
+    let mut found: Vec<Formant> = polynomial_roots(&lpc.coefficients).into_iter()
+        .filter(|z| z.im > 0.0)
+        .map(|z| Formant {
+            frequency: (z.arg() * rate / (2.0 * PI)) as f32,
+            bandwidth: (-z.norm().ln() * rate / PI) as f32,
+        })
+        .filter(|f| f.frequency > MIN_FORMANT_HZ && f.bandwidth > 0.0 && f.bandwidth < MAX_FORMANT_BANDWIDTH)
+        .collect();
+    found.sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+    found
+}
@@ -1,196 +1,247 @@
-mod ffmpegwav;
-
-use std::fs::File;
-use plotters::prelude::*;
-use rustfft::{FftPlanner, num_complex::Complex};
-use ffmpegwav::FfmpegWavFile;
-
-const GRAY: RGBColor = RGBColor(128, 128, 128);
-
-fn main() {
-    let mut file = File::open("440hz.wav").expect("File could not be opened");
-    let wav_file = FfmpegWavFile::parse(&mut file).expect("Failed to parse WAV file");
-    let downsampled_samples: Vec<f32> = wav_file.to_normalized_samples()
-        .iter().step_by(16).cloned().collect();
-    plot_waveform(&downsampled_samples, "waveform.png").expect("Failed to plot waveform");
-    println!("Waveform plot saved to 'waveform.png'");
-    plot_fft(&downsampled_samples, wav_file.fmt.sample_rate, "fft_spectrum.png").expect("Failed to plot FFT spectrum");
-    println!("FFT spectrum plot saved to 'fft_spectrum.png'");
+mod commands;
+mod config;
+mod plots;
+
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use config::Config;
+use plots::{set_style, Colormap, FreqScaleArg, Theme};
+use tracing::Level;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::time::Uptime;
+use tracing_subscriber::prelude::*;
+
+#[derive(Parser)]
+#[command(about = "WAV inspection and FFT analysis")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Preset file whose values become the option defaults (by default
+    /// fft-rs.toml in the working directory, if there is one)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Chart background and text colors
+    #[arg(long, global = true, value_enum, default_value_t = Theme::Light)]
+    theme: Theme,
+    /// Color scale of spectrograms and other heat maps
+    #[arg(long, global = true, value_enum, default_value_t = Colormap::Viridis)]
+    colormap: Colormap,
+    /// Frequency axis of spectrograms and spectrum plots: linear, or warped
+    /// to the mel, Bark (critical-band) or ERB-rate scale [default: linear;
+    /// ERB for cochleagrams]
+    #[arg(long, global = true, value_enum)]
+    freq_scale: Option<FreqScaleArg>,
+    /// More diagnostics on stderr: -v adds debug messages and timings of
+    /// the decode, FFT and render phases, -vv everything
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Only report warnings and errors on stderr
+    #[arg(short, long, global = true)]
+    quiet: bool,
 }
 
-fn plot_waveform(samples: &[f32], output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Define the dimensions of the plot
-    let root_area = BitMapBackend::new(output_path, (1280, 720)).into_drawing_area();
-    root_area.fill(&WHITE)?;
-
-    // Create a chart context
-    let mut chart = ChartBuilder::on(&root_area)
-        .caption("Audio Waveform", ("sans-serif", 40).into_font())
-        .margin(20)
-        .x_label_area_size(40)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0..samples.len(), -1.0f32..1.0f32)?;
-
-    // Configure the mesh (axes)
-    chart
-        .configure_mesh()
-        .x_desc("Sample Index")
-        .y_desc("Amplitude")
-        .axis_desc_style(("sans-serif", 30))
-        .draw()?;
-
-    // Prepare the data as plot points
-    let plot_points: Vec<(usize, f32)> = samples.iter().enumerate().map(|(i, &y)| (i, y)).collect();
-
-    // Draw the waveform line
-    chart.draw_series(LineSeries::new(
-        plot_points,
-        &BLUE, // Waveform color
-    ))?
-    .label("Waveform")
-    .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], &BLUE));
-
-    // Draw the legend
-    chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .draw()?;
-
-    Ok(())
+#[derive(Subcommand)]
+enum Command {
+    /// Turn the louder of two files down to the other's integrated
+    /// loudness for a level-matched A/B comparison
+    AbMatch(commands::ab_match::AbMatchArgs),
+    /// Print a level report: peaks, loudness and ReplayGain
+    Analyze(commands::analyze::AnalyzeArgs),
+    /// Animate the spectrum over time as a GIF or APNG
+    Animate(commands::animate::AnimateArgs),
+    /// Check a render's band levels and spectral peaks against a stored
+    /// baseline, exiting nonzero on deviation
+    Assert(commands::assert::AssertArgs),
+    /// Measure many files at once, optionally into a SQLite database
+    Batch(commands::batch::BatchArgs),
+    /// Time decoding, windowing, FFTs and rendering on this machine
+    Bench(commands::bench::BenchArgs),
+    /// Render a cochleagram: gammatone filterbank envelopes over time, the
+    /// auditory counterpart of the spectrogram
+    Cochleagram(commands::cochleagram::CochleagramArgs),
+    /// Join WAV files end to end
+    Concat(commands::concat::ConcatArgs),
+    /// Transcode to another sample rate and/or bit depth
+    Convert(commands::convert::ConvertArgs),
+    /// Extract a time range into a new file
+    Cut(commands::cut::CutArgs),
+    /// Recover an impulse response from a played sweep and its recording
+    Deconvolve(commands::deconvolve::DeconvolveArgs),
+    /// Notch out mains hum and its harmonics
+    Dehum(commands::dehum::DehumArgs),
+    /// Suggest band gains that move a track's long-term spectral balance
+    /// onto pink noise or a reference file
+    Eq(commands::eq::EqArgs),
+    /// Write the spectrogram and feature matrices for NumPy or MATLAB
+    Export(commands::export::ExportArgs),
+    /// Wow and flutter of a recorded test tone
+    Flutter(commands::flutter::FlutterArgs),
+    /// LPC formant tracks (F1-F3) of speech or singing
+    Formants(commands::formants::FormantsArgs),
+    /// Envelopes of a gammatone (auditory) filterbank's channels as CSV
+    Gammatone(commands::gammatone::GammatoneArgs),
+    /// Spectral gate: drop STFT bins below a threshold and resynthesize
+    Gate(commands::gate::GateArgs),
+    /// Generate a test signal: tone, sweep, noise or impulse
+    Gen(commands::gen::GenArgs),
+    /// Split into harmonic and percussive parts by median filtering
+    Hpss(commands::hpss::HpssArgs),
+    /// Format details and effective bit depth
+    Info(commands::info::InfoArgs),
+    /// Show smpl loop points and export a loop region
+    Loops(commands::loops::LoopsArgs),
+    /// Modulation spectrum of each gammatone band's envelope (0.5-64 Hz):
+    /// how fast and how deeply the level of each band fluctuates
+    Modulation(commands::modulation::ModulationArgs),
+    /// RMS over time of each band of a crossover split
+    Multiband(commands::multiband::MultibandArgs),
+    /// Non-negative matrix factorization of the spectrogram
+    Nmf(commands::nmf::NmfArgs),
+    /// Apply gain to reach a peak or loudness target
+    Normalize(commands::normalize::NormalizeArgs),
+    /// Export the onset or structural novelty curve as CSV or JSON
+    Novelty(commands::novelty::NoveltyArgs),
+    /// Zoom on a segment with its 8x oversampled reconstruction to show
+    /// inter-sample overs
+    Overs(commands::overs::OversArgs),
+    /// Track sinusoidal partials across STFT frames
+    Partials(commands::partials::PartialsArgs),
+    /// Track the fundamental (f0) of a monophonic recording and transcribe
+    /// it into notes (CSV, JSON or MIDI)
+    Pitch(commands::pitch::PitchArgs),
+    /// Plot the waveform, FFT spectrum and spectrogram
+    Plot(commands::plot::PlotArgs),
+    /// Frequency response of a processed file relative to its reference
+    Response(commands::response::ResponseArgs),
+    /// Reverberation times (EDT, T20, T30) of an impulse response
+    Rt60(commands::rt60::Rt60Args),
+    /// Render the spectrogram straight from the file, optionally in tiles,
+    /// for inputs too long to plot whole
+    Spectrogram(commands::spectrogram::SpectrogramArgs),
+    /// Split a recording into tracks at silent gaps
+    Split(commands::split::SplitArgs),
+    /// Self-similarity matrix of chroma or MFCC features (song structure)
+    Ssm(commands::ssm::SsmArgs),
+    /// Tempo strength over time from the onset novelty curve
+    Tempogram(commands::tempogram::TempogramArgs),
+    /// Export the most representative excerpt as a preview
+    Thumbnail(commands::thumbnail::ThumbnailArgs),
+    /// H1 transfer function and coherence of a stimulus/response capture
+    Transfer(commands::transfer::TransferArgs),
+    /// Rate and depth of a tremolo (periodic amplitude modulation), with
+    /// the spectrum of the level envelope
+    Tremolo(commands::tremolo::TremoloArgs),
+    /// Tune an instrument: nearest string, cents off and an in-tune
+    /// indicator, live from the input device or from a WAV file
+    Tune(commands::tune::TuneArgs),
+    /// Goniometer (vectorscope) image and phase correlation of a stereo
+    /// file
+    Vectorscope(commands::vectorscope::VectorscopeArgs),
+    /// Vibrato rate and extent of each note, from the f0 contour
+    Vibrato(commands::vibrato::VibratoArgs),
 }
 
-fn plot_fft(samples: &[f32], sample_rate: u32, output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Step 1: Determine FFT size (next power of two)
-    let fft_size = samples.len().next_power_of_two();
-    println!("FFT Size: {}", fft_size);
-
-    // Step 2: Prepare input for FFT (pad with zeros if necessary)
-    let mut input: Vec<Complex<f32>> = samples.iter()
-        .cloned()
-        .map(|s| Complex{ re: s, im: 0.0 })
-        .collect();
-    input.resize(fft_size, Complex{ re: 0.0, im: 0.0 });
-
-    // Step 3: Perform FFT
-    let mut planner = FftPlanner::new();
-    let fft = planner.plan_fft_forward(fft_size);
-    fft.process(&mut input);
-
-    // Step 4: Compute magnitude spectrum
-    let magnitudes: Vec<f32> = input.iter()
-        .take(fft_size / 2) // Only need the first half (Nyquist)
-        .map(|c| c.norm())
-        .collect();
-
-    // Step 5: Map FFT bins to frequencies
-    let freq_resolution = sample_rate as f32 / fft_size as f32;
-    let frequencies: Vec<f32> = (0..magnitudes.len())
-        .map(|i| i as f32 * freq_resolution)
-        .collect();
-
-    // Step 6: Identify top 5 frequencies
-    let mut freq_magnitude_map: Vec<(f32, f32)> = frequencies.iter()
-        .cloned()
-        .zip(magnitudes.iter().cloned())
-        .collect();
-
-    // Sort by magnitude descending
-    freq_magnitude_map.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-    // Select top 5 unique frequencies
-    let top_five: Vec<(f32, f32)> = freq_magnitude_map.into_iter()
-        .filter(|&(_, magnitude)| magnitude > 0.0)
-        .take(5)
-        .collect();
-
-    println!("Top 5 Frequencies:");
-    for (freq, mag) in &top_five {
-        println!("Frequency: {:.2} Hz, Magnitude: {:.4}", freq, mag);
+fn main() {
+    let cli = parse_cli().unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    });
+    init_logging(cli.verbose, cli.quiet);
+    set_style(cli.theme, cli.colormap, cli.freq_scale.map(FreqScaleArg::into));
+
+    let result = match cli.command {
+        Command::AbMatch(args) => commands::ab_match::run(args),
+        Command::Analyze(args) => commands::analyze::run(args),
+        Command::Animate(args) => commands::animate::run(args),
+        Command::Assert(args) => commands::assert::run(args),
+        Command::Batch(args) => commands::batch::run(args),
+        Command::Bench(args) => commands::bench::run(args),
+        Command::Cochleagram(args) => commands::cochleagram::run(args),
+        Command::Concat(args) => commands::concat::run(args),
+        Command::Convert(args) => commands::convert::run(args),
+        Command::Cut(args) => commands::cut::run(args),
+        Command::Deconvolve(args) => commands::deconvolve::run(args),
+        Command::Dehum(args) => commands::dehum::run(args),
+        Command::Eq(args) => commands::eq::run(args),
+        Command::Export(args) => commands::export::run(args),
+        Command::Flutter(args) => commands::flutter::run(args),
+        Command::Formants(args) => commands::formants::run(args),
+        Command::Gammatone(args) => commands::gammatone::run(args),
+        Command::Gate(args) => commands::gate::run(args),
+        Command::Gen(args) => commands::gen::run(args),
+        Command::Hpss(args) => commands::hpss::run(args),
+        Command::Info(args) => commands::info::run(args),
+        Command::Loops(args) => commands::loops::run(args),
+        Command::Modulation(args) => commands::modulation::run(args),
+        Command::Multiband(args) => commands::multiband::run(args),
+        Command::Nmf(args) => commands::nmf::run(args),
+        Command::Normalize(args) => commands::normalize::run(args),
+        Command::Novelty(args) => commands::novelty::run(args),
+        Command::Overs(args) => commands::overs::run(args),
+        Command::Partials(args) => commands::partials::run(args),
+        Command::Pitch(args) => commands::pitch::run(args),
+        Command::Plot(args) => commands::plot::run(args),
+        Command::Response(args) => commands::response::run(args),
+        Command::Rt60(args) => commands::rt60::run(args),
+        Command::Spectrogram(args) => commands::spectrogram::run(args),
+        Command::Split(args) => commands::split::run(args),
+        Command::Ssm(args) => commands::ssm::run(args),
+        Command::Tempogram(args) => commands::tempogram::run(args),
+        Command::Thumbnail(args) => commands::thumbnail::run(args),
+        Command::Transfer(args) => commands::transfer::run(args),
+        Command::Tremolo(args) => commands::tremolo::run(args),
+        Command::Tune(args) => commands::tune::run(args),
+        Command::Vectorscope(args) => commands::vectorscope::run(args),
+        Command::Vibrato(args) => commands::vibrato::run(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
     }
-
-    // Step 7: Plot the FFT magnitude spectrum
-    plot_fft_spectrum(&frequencies, &magnitudes, &top_five, output_path)?;
-
-    Ok(())
 }
 
-/// Plots the FFT magnitude spectrum, highlights the top 5 frequencies, and labels them.
-///
-/// # Arguments
-///
-/// * `frequencies` - A slice of frequencies corresponding to FFT bins.
-/// * `magnitudes` - A slice of magnitudes corresponding to FFT bins.
-/// * `top_five` - A slice of tuples containing the top 5 frequencies and their magnitudes.
-/// * `output_path` - The file path where the FFT plot image will be saved.
-fn plot_fft_spectrum(frequencies: &[f32], magnitudes: &[f32], top_five: &[(f32, f32)], output_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    // Define the dimensions of the plot (High resolution for better quality)
-    let root_area = BitMapBackend::new(output_path, (1920, 1080)).into_drawing_area();
-    root_area.fill(&WHITE)?;
-
-    // Determine the maximum magnitude for y-axis scaling
-    let max_magnitude = magnitudes.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
-    // Alternatively, use logarithmic scaling if desired
-
-    // Create a chart context
-    let mut chart = ChartBuilder::on(&root_area)
-        .caption("FFT Magnitude Spectrum", ("sans-serif", 40).into_font())
-        .margin(20)
-        .x_label_area_size(80)
-        .y_label_area_size(60)
-        .build_cartesian_2d(0f32..frequencies.last().cloned().unwrap_or(0.0), 0f32..max_magnitude)?;
-
-    // Configure the mesh (axes) to eliminate extra padding
-    chart
-        .configure_mesh()
-        .disable_mesh() // Disable grid lines for cleaner look
-        .x_desc("Frequency (Hz)")
-        .y_desc("Magnitude")
-        .axis_desc_style(("sans-serif", 30))
-        .light_line_style(&GRAY.mix(0.3))
-        .draw()?;
-
-    // Prepare the data as plot points
-    let plot_points: Vec<(f32, f32)> = frequencies.iter()
-        .cloned()
-        .zip(magnitudes.iter().cloned())
-        .collect();
-
-    // Draw the FFT magnitude line
-    chart.draw_series(LineSeries::new(
-        plot_points,
-        RGBColor(255, 0, 0).stroke_width(2), // Red color with stroke width 2
-    ))?
-    .label("Magnitude")
-    .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RGBColor(255, 0, 0)));
-
-    // Highlight and label the top 5 frequencies
-    for &(freq, mag) in top_five {
-        // Draw a blue vertical line at the top frequency
-        chart.draw_series(LineSeries::new(
-            vec![(freq, 0.0), (freq, mag)],
-            RGBColor(0, 0, 255).stroke_width(2), // Blue color with stroke width 2
-        ))?
-        .label(format!("{:.1} Hz", freq))
-        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RGBColor(0, 0, 255)));
+// parse once (leniently) to find the config file, then for real with its
+// values installed as defaults, so --help shows them too
+fn parse_cli() -> Result<Cli, Box<dyn std::error::Error>> {
+    let path = Cli::command().ignore_errors(true).try_get_matches().ok()
+        .and_then(|matches| matches.get_one::<PathBuf>("config").cloned())
+        .or_else(|| Path::new(config::DEFAULT_PATH).exists().then(|| config::DEFAULT_PATH.into()));
+    let command = match path {
+        Some(path) => Config::load(&path)?.apply(Cli::command()),
+        None => Cli::command(),
+    };
+    Ok(Cli::from_arg_matches(&command.get_matches())?)
+}
 
-        // Add text labels for top frequencies at the bottom
-        chart.draw_series(vec![
-            Text::new(
-                format!("{:.1} Hz", freq),
-                (freq, 0.0), // Position at the bottom of the plot
-                ("sans-serif", 20).into_font().color(&BLACK),
-            )
-        ])?;
+// progress messages (files written, plots saved) are info events on
+// stderr, so results on stdout stay clean to pipe
+fn init_logging(verbose: u8, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => Level::WARN,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    };
+    // -v is about our own phases; dependencies (wgpu above all) are chatty
+    // at debug level, so only -vv lets them through
+    let dependencies = if verbose > 1 { level } else { level.min(Level::INFO) };
+    let mut filter = Targets::new().with_target("fft_rs", level).with_default(dependencies);
+    if verbose < 2 {
+        // wgpu reports every graphics backend it fails to probe
+        filter = filter.with_target("wgpu_hal", LevelFilter::OFF);
     }
-
-    // Draw the legend
-    chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .position(SeriesLabelPosition::UpperLeft)
-        .draw()?;
-
-    Ok(())
-}
\ No newline at end of file
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_writer(std::io::stderr);
+    if verbose > 0 {
+        // span close events carry the busy time of each phase
+        builder.with_span_events(FmtSpan::CLOSE).with_timer(Uptime::default()).finish().with(filter).init();
+    } else {
+        builder.without_time().finish().with(filter).init();
+    }
+}
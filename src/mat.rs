@@ -0,0 +1,85 @@
+// MATLAB level-5 MAT-file writer (uncompressed), readable by MATLAB's and
+// Octave's `load`.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::export::NamedArray;
+
+// data types and array classes of the level-5 format
+const MI_INT8: u32 = 1;
+const MI_UINT16: u32 = 4;
+const MI_INT32: u32 = 5;
+const MI_UINT32: u32 = 6;
+const MI_SINGLE: u32 = 7;
+const MI_MATRIX: u32 = 14;
+const MX_CHAR_CLASS: u32 = 4;
+const MX_SINGLE_CLASS: u32 = 7;
+
+// one data element: type, byte count, payload padded to 8 bytes
+fn element(out: &mut Vec<u8>, data_type: u32, payload: &[u8]) {
+    out.extend_from_slice(&data_type.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    out.extend_from_slice(payload);
+    out.resize(out.len().next_multiple_of(8), 0);
+}
+
+// a miMATRIX element around the flags, dimensions, name and data
+fn matrix(name: &str, class: u32, dims: [usize; 2], data_type: u32, data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    element(&mut body, MI_UINT32, &[class.to_le_bytes(), 0u32.to_le_bytes()].concat());
+    let dims: Vec<u8> = dims.iter().flat_map(|&d| (d as i32).to_le_bytes()).collect();
+    element(&mut body, MI_INT32, &dims);
+    element(&mut body, MI_INT8, name.as_bytes());
+    element(&mut body, data_type, data);
+
+    let size = u32::try_from(body.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "MAT variable exceeds 4 GiB"))?;
+    let mut out = Vec::with_capacity(8 + body.len());
+    out.extend_from_slice(&MI_MATRIX.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Write `arrays` as single-precision variables (vectors become columns,
+/// 2-D arrays keep their rows and columns) and `text` as char row vectors.
+pub fn write_mat<W: Write>(writer: &mut W, arrays: &[NamedArray], text: &[(&str, &str)]) -> io::Result<()> {
+    let mut header = format!("MATLAB 5.0 MAT-file, Created by: fft-rs {}", env!("CARGO_PKG_VERSION")).into_bytes();
+    header.resize(116, b' ');
+    header.extend_from_slice(&[0; 8]); // no subsystem data
+    header.extend_from_slice(&0x0100u16.to_le_bytes());
+    header.extend_from_slice(b"IM");
+    writer.write_all(&header)?;
+
+    for array in arrays {
+        let (rows, columns) = match array.shape.as_slice() {
+            [n] => (*n, 1),
+            [rows, columns] => (*rows, *columns),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "MAT export takes 1-D or 2-D arrays")),
+        };
+        // MATLAB stores column-major
+        let mut data = Vec::with_capacity(4 * array.data.len());
+        for column in 0..columns {
+            for row in 0..rows {
+                data.extend_from_slice(&array.data[row * columns + column].to_le_bytes());
+            }
+        }
+        writer.write_all(&matrix(&array.name, MX_SINGLE_CLASS, [rows, columns], MI_SINGLE, &data)?)?;
+    }
+
+    for (name, value) in text {
+        let units: Vec<u16> = value.encode_utf16().collect();
+        let data: Vec<u8> = units.iter().flat_map(|u| u.to_le_bytes()).collect();
+        writer.write_all(&matrix(name, MX_CHAR_CLASS, [1, units.len()], MI_UINT16, &data)?)?;
+    }
+    Ok(())
+}
+
+/// `write_mat` into a newly created file at `path`.
+pub fn write_mat_file<P: AsRef<Path>>(path: P, arrays: &[NamedArray], text: &[(&str, &str)]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_mat(&mut writer, arrays, text)?;
+    writer.flush()
+}
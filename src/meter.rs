@@ -0,0 +1,369 @@
+// Level metering: sample peak, true peak, ITU-R BS.1770 integrated loudness
+// and ReplayGain 2.0.
+
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+use crate::biquad::Biquad;
+
+/// Largest absolute sample value.
+pub fn sample_peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |peak, &s| peak.max(s.abs()))
+}
+
+// BS.1770-4 Annex 2 interpolation filter: 48 taps split into the four
+// phases of a 4x polyphase oversampler
+const TRUE_PEAK_PHASES: [[f64; 12]; 4] = [
+    [
+        0.001708984375, 0.010986328125, -0.0196533203125, 0.033203125,
+        -0.0594482421875, 0.1373291015625, 0.97216796875, -0.102294921875,
+        0.047607421875, -0.026611328125, 0.014892578125, -0.00830078125,
+    ],
+    [
+        -0.0291748046875, 0.029296875, -0.0517578125, 0.089111328125,
+        -0.16650390625, 0.465087890625, 0.77978515625, -0.2003173828125,
+        0.1015625, -0.0582275390625, 0.0330810546875, -0.0189208984375,
+    ],
+    [
+        -0.0189208984375, 0.0330810546875, -0.0582275390625, 0.1015625,
+        -0.2003173828125, 0.77978515625, 0.465087890625, -0.16650390625,
+        0.089111328125, -0.0517578125, 0.029296875, -0.0291748046875,
+    ],
+    [
+        -0.00830078125, 0.014892578125, -0.026611328125, 0.047607421875,
+        -0.102294921875, 0.97216796875, 0.1373291015625, -0.0594482421875,
+        0.033203125, -0.0196533203125, 0.010986328125, 0.001708984375,
+    ],
+];
+
+/// Largest absolute value of the 4x oversampled signal (BS.1770 true peak),
+/// which catches inter-sample overs that `sample_peak` misses. Never less
+/// than the sample peak.
+pub fn true_peak(samples: &[f32], channels: usize) -> f32 {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let taps = TRUE_PEAK_PHASES[0].len();
+
+    let mut peak = sample_peak(samples);
+    for ch in 0..channels {
+        // run on past the last frame so the filter rings out
+        for n in 0..frames + taps - 1 {
+            for phase in &TRUE_PEAK_PHASES {
+                let y: f64 = phase.iter().enumerate()
+                    .filter(|&(k, _)| k <= n && n - k < frames)
+                    .map(|(k, &h)| h * samples[(n - k) * channels + ch] as f64)
+                    .sum();
+                peak = peak.max(y.abs() as f32);
+            }
+        }
+    }
+    peak
+}
+
+/// Linear amplitude to decibels (20 log10); 0 maps to -inf.
+pub fn to_db(amplitude: f32) -> f32 {
+    20.0 * amplitude.log10()
+}
+
+/// Decibels to a linear amplitude factor.
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+// BS.1770 K-weighting: a high shelf (head effects) followed by the RLB
+// high-pass, with coefficients derived for any sample rate
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let fs = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let shelf = Biquad::new(
+        [vh + vb * k / q + k * k, 2.0 * (k * k - vh), vh - vb * k / q + k * k],
+        [1.0 + k / q + k * k, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    );
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / fs).tan();
+    let highpass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [1.0 + k / q + k * k, 2.0 * (k * k - 1.0), 1.0 - k / q + k * k],
+    );
+
+    [shelf, highpass]
+}
+
+// per-channel weight: surrounds count +1.5 dB, the LFE of a 5.1 layout is ignored
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match (channels, channel) {
+        (6, 3) => 0.0,
+        (6, 4..=5) | (5, 3..=4) => 1.41,
+        _ => 1.0,
+    }
+}
+
+/// Mean square of the K-weighted signal in each 400 ms gating block (75%
+/// overlap), already summed across channels with their weights.
+pub fn block_powers(samples: &[f32], channels: usize, sample_rate: u32) -> Vec<f64> {
+    windowed_powers(samples, channels, sample_rate, 0.4)
+}
+
+/// Short-term loudness (3 s window, 100 ms step) in LUFS.
+pub fn short_term_loudness(samples: &[f32], channels: usize, sample_rate: u32) -> Vec<f32> {
+    windowed_powers(samples, channels, sample_rate, 3.0).into_iter()
+        .map(|p| power_to_lufs(p) as f32)
+        .collect()
+}
+
+// weighted K-filtered mean square over `window` seconds every 100 ms
+fn windowed_powers(samples: &[f32], channels: usize, sample_rate: u32, window: f64) -> Vec<f64> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+
+    // K-weight each channel and square
+    let mut squared = vec![0.0f64; frames * channels];
+    for ch in 0..channels {
+        let mut filters = k_weighting(sample_rate);
+        for i in 0..frames {
+            let y = filters.iter_mut().fold(samples[i * channels + ch] as f64, |x, f| f.process(x));
+            squared[i * channels + ch] = y * y;
+        }
+    }
+
+    let block = (sample_rate as f64 * window).round() as usize;
+    let step = (sample_rate as f64 * 0.1).round() as usize;
+    if block == 0 || frames < block {
+        return Vec::new();
+    }
+
+    (0..=(frames - block) / step).map(|b| {
+        let start = b * step;
+        (0..channels).map(|ch| {
+            let sum: f64 = (start..start + block).map(|i| squared[i * channels + ch]).sum();
+            channel_weight(ch, channels) * sum / block as f64
+        }).sum()
+    }).collect()
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Gated integrated loudness in LUFS, or `None` when the input is shorter
+/// than one block or everything is below the -70 LUFS absolute gate.
+pub fn integrated_loudness(samples: &[f32], channels: usize, sample_rate: u32) -> Option<f32> {
+    gated_loudness(&block_powers(samples, channels, sample_rate))
+}
+
+/// The BS.1770 gating of `integrated_loudness`, applied to the powers of
+/// its 400 ms blocks (as from `block_powers`).
+pub fn gated_loudness(powers: &[f64]) -> Option<f32> {
+    let mean = |blocks: &[f64]| blocks.iter().sum::<f64>() / blocks.len() as f64;
+
+    let absolute: Vec<f64> = powers.iter().copied()
+        .filter(|&p| power_to_lufs(p) > -70.0)
+        .collect();
+    if absolute.is_empty() {
+        return None;
+    }
+
+    // relative gate sits 10 LU below the loudness of the absolute-gated blocks
+    let threshold = power_to_lufs(mean(&absolute)) - 10.0;
+    let relative: Vec<f64> = absolute.into_iter()
+        .filter(|&p| power_to_lufs(p) > threshold)
+        .collect();
+
+    Some(power_to_lufs(mean(&relative)) as f32)
+}
+
+// ReplayGain 2.0 reference level
+const REPLAYGAIN_REFERENCE_LUFS: f32 = -18.0;
+
+/// ReplayGain 2.0 track values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+    pub gain_db: f32, // gain that brings the track to -18 LUFS
+    pub peak: f32,    // linear true peak, 1.0 = full scale
+}
+
+/// ReplayGain 2.0 track gain and peak, or `None` when the loudness can't be
+/// measured (see `integrated_loudness`).
+pub fn replay_gain(samples: &[f32], channels: usize, sample_rate: u32) -> Option<ReplayGain> {
+    let loudness = integrated_loudness(samples, channels, sample_rate)?;
+    Some(ReplayGain {
+        gain_db: REPLAYGAIN_REFERENCE_LUFS - loudness,
+        peak: true_peak(samples, channels),
+    })
+}
+
+/// Sample peak, true peak and integrated loudness of a recording fed in
+/// blocks of interleaved samples, so any length is measured in constant
+/// memory (apart from one power value per 100 ms). Reads the same as
+/// `sample_peak`, `true_peak` and `integrated_loudness` of the whole signal.
+#[derive(Debug, Clone)]
+pub struct LevelMeter {
+    channels: usize,
+    block: usize, // frames per 400 ms gating block
+    step: usize,  // frames between block starts
+    filters: Vec<[Biquad; 2]>,
+    history: Vec<[f64; 12]>, // latest inputs per channel, newest first, for the true-peak filter
+    squared: Vec<f64>,       // K-weighted squares of the last `block` frames, a ring
+    frames: usize,
+    powers: Vec<f64>,
+    sample_peak: f32,
+    true_peak: f32,
+}
+
+impl LevelMeter {
+    pub fn new(channels: usize, sample_rate: u32) -> Self {
+        let channels = channels.max(1);
+        let block = (sample_rate as f64 * 0.4).round() as usize;
+        LevelMeter {
+            channels,
+            block,
+            step: ((sample_rate as f64 * 0.1).round() as usize).max(1),
+            filters: vec![k_weighting(sample_rate); channels],
+            history: vec![[0.0; 12]; channels],
+            squared: vec![0.0; block * channels],
+            frames: 0,
+            powers: Vec::new(),
+            sample_peak: 0.0,
+            true_peak: 0.0,
+        }
+    }
+
+    /// Measure the next whole frames of interleaved samples.
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            let slot = if self.block > 0 { self.frames % self.block } else { 0 };
+            for (ch, &x) in frame.iter().enumerate() {
+                self.sample_peak = self.sample_peak.max(x.abs());
+
+                let history = &mut self.history[ch];
+                history.copy_within(..11, 1);
+                history[0] = x as f64;
+                self.true_peak = self.true_peak.max(oversampled_peak(history));
+
+                if self.block > 0 {
+                    let y = self.filters[ch].iter_mut().fold(x as f64, |x, f| f.process(x));
+                    self.squared[slot * self.channels + ch] = y * y;
+                }
+            }
+            self.frames += 1;
+
+            if self.block > 0 && self.frames >= self.block && (self.frames - self.block).is_multiple_of(self.step) {
+                let start = self.frames - self.block;
+                let power = (0..self.channels).map(|ch| {
+                    let sum: f64 = (start..self.frames)
+                        .map(|i| self.squared[(i % self.block) * self.channels + ch])
+                        .sum();
+                    channel_weight(ch, self.channels) * sum / self.block as f64
+                }).sum();
+                self.powers.push(power);
+            }
+        }
+    }
+
+    pub fn sample_peak(&self) -> f32 {
+        self.sample_peak
+    }
+
+    /// True peak so far, with the interpolation filter rung out past the
+    /// last sample pushed.
+    pub fn true_peak(&self) -> f32 {
+        let mut peak = self.true_peak.max(self.sample_peak);
+        for history in &self.history {
+            let mut history = *history;
+            for _ in 1..history.len() {
+                history.copy_within(..11, 1);
+                history[0] = 0.0;
+                peak = peak.max(oversampled_peak(&history));
+            }
+        }
+        peak
+    }
+
+    pub fn integrated_loudness(&self) -> Option<f32> {
+        gated_loudness(&self.powers)
+    }
+
+    /// The `replay_gain` of everything pushed so far.
+    pub fn replay_gain(&self) -> Option<ReplayGain> {
+        Some(ReplayGain {
+            gain_db: REPLAYGAIN_REFERENCE_LUFS - self.integrated_loudness()?,
+            peak: self.true_peak(),
+        })
+    }
+}
+
+// largest output of the four true-peak filter phases over one input history
+fn oversampled_peak(history: &[f64; 12]) -> f32 {
+    TRUE_PEAK_PHASES.iter().fold(0.0f32, |peak, phase| {
+        let y: f64 = phase.iter().zip(history).map(|(&h, &x)| h * x).sum();
+        peak.max(y.abs() as f32)
+    })
+}
+
+/// Maps dBFS to dB SPL from a calibrator recording: a tone of known SPL
+/// recorded at a known RMS level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub reference_spl: f32,        // calibrator level, e.g. 94 dB SPL
+    pub reference_hz: Option<f32>, // calibrator frequency, informational
+    pub reference_dbfs: f32,       // RMS level the calibrator recorded at
+}
+
+impl Calibration {
+    /// dB to add to a dBFS (RMS) level to get dB SPL.
+    pub fn offset_db(&self) -> f32 {
+        self.reference_spl - self.reference_dbfs
+    }
+
+    pub fn to_spl(&self, dbfs: f32) -> f32 {
+        dbfs + self.offset_db()
+    }
+}
+
+/// Parses `94dB@1kHz=<rms>`, where the RMS is linear (`0.05`) or in dBFS
+/// (`-26dBFS`) and the `@frequency` part is optional.
+impl FromStr for Calibration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let err = || format!("not a calibration: {} (use e.g. 94dB@1kHz=0.05 or 94dB=-26dBFS)", s);
+        let lower = s.trim().to_ascii_lowercase();
+        let (reference, rms) = lower.split_once('=').ok_or_else(err)?;
+
+        let (spl, hz) = match reference.split_once('@') {
+            Some((spl, hz)) => (spl, Some(hz)),
+            None => (reference, None),
+        };
+        let reference_spl: f32 = spl.trim().trim_end_matches("spl").trim()
+            .trim_end_matches("db").trim().parse().map_err(|_| err())?;
+        let reference_hz = hz.map(|hz| {
+            let hz = hz.trim().trim_end_matches("hz");
+            match hz.strip_suffix('k') {
+                Some(khz) => khz.trim().parse::<f32>().map(|k| k * 1000.0),
+                None => hz.trim().parse::<f32>(),
+            }.map_err(|_| err())
+        }).transpose()?;
+
+        let rms = rms.trim();
+        let reference_dbfs = match rms.strip_suffix("dbfs") {
+            Some(db) => db.trim().parse().map_err(|_| err())?,
+            None => {
+                let linear: f32 = rms.parse().map_err(|_| err())?;
+                if linear <= 0.0 {
+                    return Err(err());
+                }
+                to_db(linear)
+            }
+        };
+
+        Ok(Calibration { reference_spl, reference_hz, reference_dbfs })
+    }
+}
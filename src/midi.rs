@@ -0,0 +1,118 @@
+// Standard MIDI files: one track of channel events at times in seconds,
+// written as format 0 at a fixed tempo.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// 120 bpm at 480 ticks per quarter note: 960 ticks per second
+const TICKS_PER_QUARTER: u16 = 480;
+const MICROS_PER_QUARTER: u32 = 500_000;
+const TICKS_PER_SECOND: f64 = TICKS_PER_QUARTER as f64 * 1e6 / MICROS_PER_QUARTER as f64;
+
+/// A channel event of a `MidiTrack`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MidiEvent {
+    NoteOn { key: u8, velocity: u8 },
+    NoteOff { key: u8 },
+    /// Bend in semitones, within the track's bend range.
+    PitchBend(f32),
+}
+
+/// Events on channel 1, kept in the order they were added among those at
+/// the same time.
+#[derive(Debug, Clone, Default)]
+pub struct MidiTrack {
+    events: Vec<(f64, MidiEvent)>,
+    bend_range: Option<u8>,
+}
+
+impl MidiTrack {
+    pub fn new() -> Self {
+        MidiTrack::default()
+    }
+
+    /// Announce a pitch bend range of `semitones` up and down at the start
+    /// (RPN 0); `PitchBend` values are scaled to it.
+    pub fn set_bend_range(&mut self, semitones: u8) {
+        self.bend_range = Some(semitones.clamp(1, 24));
+    }
+
+    pub fn push(&mut self, time: f64, event: MidiEvent) {
+        self.events.push((time.max(0.0), event));
+    }
+
+    /// A note from `start` to `end` seconds.
+    pub fn note(&mut self, start: f64, end: f64, key: u8, velocity: u8) {
+        self.push(start, MidiEvent::NoteOn { key: key.min(127), velocity: velocity.clamp(1, 127) });
+        self.push(end.max(start), MidiEvent::NoteOff { key: key.min(127) });
+    }
+
+    /// The whole file: header chunk and one track chunk.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut track = Vec::new();
+        // tempo, then the bend range as RPN 0 (coarse in CC 6, fine in CC 38)
+        track.extend_from_slice(&[0x00, 0xFF, 0x51, 0x03]);
+        track.extend_from_slice(&MICROS_PER_QUARTER.to_be_bytes()[1..]);
+        if let Some(range) = self.bend_range {
+            for (controller, value) in [(101, 0), (100, 0), (6, range), (38, 0), (101, 127), (100, 127)] {
+                track.extend_from_slice(&[0x00, 0xB0, controller, value]);
+            }
+        }
+
+        let mut events: Vec<&(f64, MidiEvent)> = self.events.iter().collect();
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let range = self.bend_range.unwrap_or(2) as f32;
+        let mut tick = 0u64;
+        for (time, event) in events {
+            let at = (time * TICKS_PER_SECOND).round() as u64;
+            write_varlen(&mut track, (at - tick) as u32);
+            tick = at;
+            match *event {
+                MidiEvent::NoteOn { key, velocity } => track.extend_from_slice(&[0x90, key, velocity]),
+                MidiEvent::NoteOff { key } => track.extend_from_slice(&[0x80, key, 0x40]),
+                MidiEvent::PitchBend(semitones) => {
+                    let value = (8192.0 + semitones / range * 8192.0).round().clamp(0.0, 16383.0) as u16;
+                    track.extend_from_slice(&[0xE0, (value & 0x7F) as u8, (value >> 7) as u8]);
+                }
+            }
+        }
+        track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut bytes = Vec::with_capacity(track.len() + 22);
+        bytes.extend_from_slice(b"MThd");
+        bytes.extend_from_slice(&6u32.to_be_bytes());
+        bytes.extend_from_slice(&0u16.to_be_bytes()); // format 0
+        bytes.extend_from_slice(&1u16.to_be_bytes()); // one track
+        bytes.extend_from_slice(&TICKS_PER_QUARTER.to_be_bytes());
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&track);
+        bytes
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(&self.to_bytes())?;
+        out.flush()
+    }
+}
+
+// variable-length quantity: 7 bits per byte, most significant first, the
+// high bit set on all but the last
+fn write_varlen(out: &mut Vec<u8>, value: u32) {
+    let mut groups = [0u8; 5];
+    let mut count = 0;
+    let mut rest = value;
+    loop {
+        groups[count] = (rest & 0x7F) as u8;
+        count += 1;
+        rest >>= 7;
+        if rest == 0 {
+            break;
+        }
+    }
+    for i in (0..count).rev() {
+        out.push(groups[i] | if i > 0 { 0x80 } else { 0 });
+    }
+}
@@ -0,0 +1,169 @@
+// Modulation spectra: the spectrum of a band's envelope, i.e. how fast and
+// how deeply its level rises and falls. Speech concentrates near the 4 Hz
+// syllable rate, tremolo shows as a single line, and fast modulations
+// (30-70 Hz) are heard as roughness.
+
+use crate::analyzer::{with_shared, SpectrumAnalyzer};
+use crate::biquad::Biquad;
+use crate::partials::pick_peaks;
+use crate::spectrum::Spectrum;
+use crate::stft::hann;
+
+/// Slowest and fastest modulation analyzed, in Hz.
+pub const MODULATION_LOW: f32 = 0.5;
+pub const MODULATION_HIGH: f32 = 64.0;
+
+const BANDS_PER_OCTAVE: f32 = 3.0;
+
+// zero-pad until at least this many FFT bins fall in the narrowest band
+const BINS_PER_BAND: f32 = 4.0;
+
+/// Tremolo rates searched, in Hz: from a rotary speaker's slow (chorale)
+/// setting to fast tremolo effects.
+pub const TREMOLO_LOW: f32 = 0.5;
+pub const TREMOLO_HIGH: f32 = 20.0;
+
+// the level envelope for tremolo is low-passed at ENVELOPE_CUTOFF Hz and
+// sampled at about TREMOLO_ENVELOPE_RATE
+pub const ENVELOPE_CUTOFF: f32 = 50.0;
+const TREMOLO_ENVELOPE_RATE: f32 = 200.0;
+
+// zero-pad the envelope spectrum to at least this many bins per Hz, so the
+// peak is found between bins
+const TREMOLO_BINS_PER_HZ: f32 = 20.0;
+
+/// A modulation frequency band.
+#[derive(Debug, Clone, Copy)]
+pub struct ModulationBand {
+    pub lower: f32,
+    pub center: f32,
+    pub upper: f32,
+}
+
+/// Third-octave bands with centers from `MODULATION_LOW` to
+/// `MODULATION_HIGH` (base-2 spacing, so each octave lands on a center).
+pub fn modulation_bands() -> Vec<ModulationBand> {
+    let count = (BANDS_PER_OCTAVE * (MODULATION_HIGH / MODULATION_LOW).log2()).round() as usize + 1;
+    let half = 2f32.powf(0.5 / BANDS_PER_OCTAVE);
+    (0..count)
+        .map(|k| {
+            let center = MODULATION_LOW * 2f32.powf(k as f32 / BANDS_PER_OCTAVE);
+            ModulationBand { lower: center / half, center, upper: center * half }
+        })
+        .collect()
+}
+
+/// Modulation depth of `envelope` (sampled at `envelope_rate` Hz) in each
+/// band of `modulation_bands`: the depth of the sinusoidal amplitude
+/// modulation that carries the band's power, relative to the mean level
+/// (1.0 swings all the way down to silence). A silent envelope reads 0.
+pub fn modulation_depths(analyzer: &mut SpectrumAnalyzer, envelope: &[f32], envelope_rate: f32) -> Vec<f32> {
+    let bands = modulation_bands();
+    let narrowest = bands[0].upper - bands[0].lower;
+    let min_size = (BINS_PER_BAND * envelope_rate / narrowest).ceil() as usize;
+    let Some((mean, spectrum)) = fluctuation_spectrum(analyzer, envelope, envelope_rate, min_size) else {
+        return vec![0.0; bands.len()];
+    };
+    let fft_size = spectrum.fft_size;
+    let bin_width = envelope_rate / fft_size as f32;
+
+    // a band's power is its share of the windowed signal's energy, by
+    // Parseval 2 sum |X|^2 / (N sum w^2) over the band's bins
+    bands.iter()
+        .map(|band| {
+            let bins = (band.lower / bin_width).ceil() as usize..(band.upper / bin_width).ceil() as usize;
+            let energy: f64 = spectrum.magnitudes[bins.start.min(fft_size / 2)..bins.end.min(fft_size / 2)]
+                .iter()
+                .map(|&m| m as f64 * m as f64)
+                .sum();
+            let power = 2.0 * energy / (fft_size as f64 * spectrum.window_power as f64);
+            ((2.0 * power).sqrt() / mean) as f32
+        })
+        .collect()
+}
+
+/// Periodic amplitude modulation (tremolo) of a whole signal.
+#[derive(Debug, Clone)]
+pub struct Tremolo {
+    pub rate: f32,                 // Hz
+    pub depth: f32,                // modulation depth re the mean level, 0..1
+    pub spectrum: Vec<(f32, f32)>, // (modulation Hz, depth) of the envelope from TREMOLO_LOW to ENVELOPE_CUTOFF
+}
+
+impl Tremolo {
+    /// Peak-to-trough level swing in dB (infinite at full depth).
+    pub fn swing_db(&self) -> f32 {
+        20.0 * ((1.0 + self.depth) / (1.0 - self.depth).max(0.0)).log10()
+    }
+}
+
+/// Find the strongest periodic amplitude modulation of `samples` between
+/// `TREMOLO_LOW` and `TREMOLO_HIGH`: the interpolated peak of the level
+/// envelope's spectrum. `None` for silence or a signal shorter than one
+/// period of `TREMOLO_LOW`.
+pub fn detect_tremolo(samples: &[f32], sample_rate: u32) -> Option<Tremolo> {
+    if (samples.len() as f32) < sample_rate as f32 / TREMOLO_LOW {
+        return None;
+    }
+    let hop = (sample_rate as f32 / TREMOLO_ENVELOPE_RATE).round().max(1.0) as usize;
+    let envelope_rate = sample_rate as f32 / hop as f32;
+
+    // RMS envelope: the squared signal through a 4th-order Butterworth
+    // low-pass (two biquads), which removes the carrier's ripple at twice
+    // its frequency but keeps the tremolo and its harmonics
+    let cutoff = ENVELOPE_CUTOFF as f64;
+    let mut lowpass = [
+        Biquad::lowpass(sample_rate, cutoff, 0.5412),
+        Biquad::lowpass(sample_rate, cutoff, 1.3066),
+    ];
+    let envelope: Vec<f32> = samples.iter().enumerate()
+        .filter_map(|(n, &s)| {
+            let square = lowpass.iter_mut().fold(s as f64 * s as f64, |x, filter| filter.process(x));
+            (n % hop == 0).then(|| square.max(0.0).sqrt() as f32)
+        })
+        .collect();
+    // skip the filters' settling time
+    let envelope = &envelope[(envelope_rate / ENVELOPE_CUTOFF).ceil() as usize * 4..];
+
+    let min_size = (TREMOLO_BINS_PER_HZ * envelope_rate).ceil() as usize;
+    let (mean, spectrum) = with_shared(|analyzer| fluctuation_spectrum(analyzer, envelope, envelope_rate, min_size))?;
+    let bin_width = envelope_rate / spectrum.fft_size as f32;
+    let depth_of = |m: f32| (spectrum.sine_amplitude(m) as f64 / mean) as f32;
+
+    let search = (TREMOLO_LOW / bin_width).floor() as usize..(TREMOLO_HIGH / bin_width).ceil() as usize + 1;
+    let (bin, magnitude) = pick_peaks(&spectrum.magnitudes[search.start - 1..search.end + 1], 0.0, 1)
+        .first()
+        .map(|&(offset, m)| (search.start as f32 - 1.0 + offset, m))?;
+
+    let spectrum: Vec<(f32, f32)> = (search.start..=(ENVELOPE_CUTOFF / bin_width) as usize)
+        .map(|k| (k as f32 * bin_width, depth_of(spectrum.magnitudes[k])))
+        .collect();
+    Some(Tremolo { rate: bin * bin_width, depth: depth_of(magnitude).min(1.0), spectrum })
+}
+
+// Hann-windowed FFT of an envelope's fluctuation about its mean, zero-padded
+// to at least `min_size` points, with the mean; None if the envelope is
+// silent
+fn fluctuation_spectrum(
+    analyzer: &mut SpectrumAnalyzer,
+    envelope: &[f32],
+    envelope_rate: f32,
+    min_size: usize,
+) -> Option<(f64, Spectrum)> {
+    let mean = envelope.iter().map(|&e| e as f64).sum::<f64>() / envelope.len().max(1) as f64;
+    if mean <= 0.0 {
+        return None;
+    }
+    let window = hann(envelope.len());
+    let fluctuation: Vec<f32> = envelope.iter().zip(&window)
+        .map(|(&e, &w)| ((e as f64 - mean) * w as f64) as f32)
+        .collect();
+
+    let fft_size = envelope.len().max(min_size).next_power_of_two();
+    // the envelope rate is rarely a whole number, so callers place the
+    // bins themselves rather than by the spectrum's own frequencies
+    let spectrum = analyzer.spectrum(&fluctuation, envelope_rate.round() as u32, fft_size);
+    let window_sum = window.iter().sum();
+    let window_power = window.iter().map(|w| w * w).sum();
+    Some((mean, Spectrum { window_sum, window_power, ..spectrum }))
+}
@@ -0,0 +1,118 @@
+// Non-negative matrix factorization of a magnitude spectrogram into K
+// spectral templates and their activations over time.
+
+use crate::stft::{padded_stft, trimmed_istft, StftConfig};
+
+// keeps the divisions of the multiplicative updates finite
+const EPSILON: f32 = 1e-12;
+
+/// V ~ W H: `basis[c]` is the spectrum of component c (bins 0..=nfft/2,
+/// normalized to unit sum) and `activations[c][t]` its gain in frame t.
+#[derive(Debug, Clone)]
+pub struct Nmf {
+    pub basis: Vec<Vec<f32>>,
+    pub activations: Vec<Vec<f32>>,
+}
+
+impl Nmf {
+    /// Magnitude of component `c` (or of all of them, with `None`) in frame `t`, bin `k`.
+    fn model(&self, c: Option<usize>, t: usize, k: usize) -> f32 {
+        match c {
+            Some(c) => self.basis[c][k] * self.activations[c][t],
+            None => (0..self.basis.len()).map(|c| self.basis[c][k] * self.activations[c][t]).sum(),
+        }
+    }
+}
+
+// xorshift32 in (0.5, 1.5): a fixed seed keeps runs reproducible
+fn initial_values(count: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed.max(1);
+    (0..count).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        0.5 + state as f32 / u32::MAX as f32
+    }).collect()
+}
+
+/// Factorize `frames[t][k]` (magnitudes) into `components` parts with
+/// `iterations` rounds of the multiplicative updates that minimize the
+/// Kullback-Leibler divergence (Lee & Seung).
+pub fn nmf(frames: &[Vec<f32>], components: usize, iterations: usize) -> Nmf {
+    let num_frames = frames.len();
+    let num_bins = frames.first().map_or(0, |f| f.len());
+    let mean = frames.iter().flatten().sum::<f32>() / (num_frames * num_bins).max(1) as f32;
+
+    let mut model = Nmf {
+        basis: (0..components).map(|c| initial_values(num_bins, 0x9e37 + c as u32)).collect(),
+        activations: (0..components)
+            .map(|c| initial_values(num_frames, 0x7f4a + c as u32).iter().map(|v| v * mean).collect())
+            .collect(),
+    };
+
+    let mut ratio = vec![vec![0.0f32; num_bins]; num_frames];
+    let update_ratio = |model: &Nmf, ratio: &mut [Vec<f32>]| {
+        for (t, row) in ratio.iter_mut().enumerate() {
+            for (k, r) in row.iter_mut().enumerate() {
+                *r = frames[t][k] / (model.model(None, t, k) + EPSILON);
+            }
+        }
+    };
+
+    for _ in 0..iterations {
+        update_ratio(&model, &mut ratio);
+        for c in 0..components {
+            let basis = &model.basis[c];
+            let total: f32 = basis.iter().sum::<f32>() + EPSILON;
+            for (h, row) in model.activations[c].iter_mut().zip(&ratio) {
+                let gain: f32 = basis.iter().zip(row).map(|(w, r)| w * r).sum();
+                *h *= gain / total;
+            }
+        }
+
+        update_ratio(&model, &mut ratio);
+        for c in 0..components {
+            let total: f32 = model.activations[c].iter().sum::<f32>() + EPSILON;
+            for k in 0..num_bins {
+                let gain: f32 = model.activations[c].iter().zip(&ratio).map(|(h, r)| h * r[k]).sum();
+                model.basis[c][k] *= gain / total;
+            }
+        }
+
+        // move the scale into the activations so templates stay comparable
+        for c in 0..components {
+            let sum: f32 = model.basis[c].iter().sum::<f32>() + EPSILON;
+            model.basis[c].iter_mut().for_each(|w| *w /= sum);
+            model.activations[c].iter_mut().for_each(|h| *h *= sum);
+        }
+    }
+    model
+}
+
+/// Factorize the STFT magnitudes of a mono signal and resynthesize each
+/// component by soft masking: bin by bin, a component keeps its share
+/// W_c H_c / W H of the mixture. The components add back up to the input.
+pub fn separate(samples: &[f32], config: StftConfig, components: usize, iterations: usize) -> (Nmf, Vec<Vec<f32>>) {
+    let frames = padded_stft(samples, config);
+    if frames.is_empty() {
+        // nothing to factorize: the first component takes the whole signal
+        let parts = (0..components)
+            .map(|c| if c == 0 { samples.to_vec() } else { vec![0.0; samples.len()] })
+            .collect();
+        return (nmf(&[], components, 0), parts);
+    }
+    let magnitudes: Vec<Vec<f32>> = frames.iter()
+        .map(|frame| frame.iter().map(|c| c.norm()).collect())
+        .collect();
+    let model = nmf(&magnitudes, components, iterations);
+
+    let outputs = (0..components).map(|c| {
+        let masked: Vec<Vec<_>> = frames.iter().enumerate().map(|(t, frame)| {
+            frame.iter().enumerate()
+                .map(|(k, &x)| x * (model.model(Some(c), t, k) / (model.model(None, t, k) + EPSILON)))
+                .collect()
+        }).collect();
+        trimmed_istft(&masked, config, samples.len())
+    }).collect();
+    (model, outputs)
+}
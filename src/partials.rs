@@ -0,0 +1,159 @@
+// Sinusoidal partial tracking: spectral peaks picked frame by frame and
+// linked into frequency/amplitude trajectories.
+
+use crate::stft::Spectrogram;
+
+/// Tuning of `track_partials`.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackingConfig {
+    pub threshold_db: f32,  // peaks below this sine amplitude (dBFS) are ignored
+    pub max_peaks: usize,   // strongest peaks kept per frame
+    pub max_deviation: f32, // largest frequency jump between frames, as a ratio
+    pub min_frames: usize,  // shorter tracks are dropped as noise
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        TrackingConfig { threshold_db: -60.0, max_peaks: 40, max_deviation: 0.03, min_frames: 5 }
+    }
+}
+
+/// A spectral peak, refined by parabolic interpolation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Peak {
+    pub frequency: f32, // Hz
+    pub amplitude: f32, // sine amplitude re full scale
+}
+
+/// One point of a partial: its peak in the frame centered at `time`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PartialPoint {
+    pub frame: usize,
+    pub time: f32, // seconds
+    pub frequency: f32,
+    pub amplitude: f32,
+}
+
+/// A sinusoid followed over consecutive frames.
+#[derive(Debug, Clone)]
+pub struct Partial {
+    pub points: Vec<PartialPoint>,
+}
+
+impl Partial {
+    pub fn mean_frequency(&self) -> f32 {
+        self.points.iter().map(|p| p.frequency).sum::<f32>() / self.points.len().max(1) as f32
+    }
+
+    pub fn peak_amplitude(&self) -> f32 {
+        self.points.iter().map(|p| p.amplitude).fold(0.0, f32::max)
+    }
+}
+
+/// Local maxima of one magnitude frame above `threshold` (a raw
+/// magnitude), strongest first, at most `max_peaks` of them. Positions and
+/// heights come from a parabola through the peak bin and its neighbours in
+/// dB; frequencies are in bins.
+pub fn pick_peaks(frame: &[f32], threshold: f32, max_peaks: usize) -> Vec<(f32, f32)> {
+    let db = |m: f32| 20.0 * (m + 1e-12).log10();
+    let mut peaks: Vec<(f32, f32)> = (1..frame.len().saturating_sub(1))
+        .filter(|&k| frame[k] >= threshold && frame[k] > frame[k - 1] && frame[k] >= frame[k + 1])
+        .map(|k| {
+            let (a, b, c) = (db(frame[k - 1]), db(frame[k]), db(frame[k + 1]));
+            let denominator = a - 2.0 * b + c;
+            let offset = if denominator.abs() > 1e-12 { 0.5 * (a - c) / denominator } else { 0.0 };
+            let height = b - 0.25 * (a - c) * offset;
+            (k as f32 + offset, 10f32.powf(height / 20.0))
+        })
+        .collect();
+    peaks.sort_by(|x, y| y.1.total_cmp(&x.1));
+    peaks.truncate(max_peaks);
+    peaks
+}
+
+/// Pick peaks in every frame of `spectrogram` and link each to the nearest
+/// peak of the next frame within `max_deviation`, strongest partials
+/// choosing first. Unmatched peaks start new partials; a partial with no
+/// match ends.
+pub fn track_partials(spectrogram: &Spectrogram, config: TrackingConfig) -> Vec<Partial> {
+    let nfft = spectrogram.config.nfft;
+    // a sine of amplitude A peaks at A * sum(w) / 2
+    let window_sum: f32 = spectrogram.window.coefficients(nfft).iter().sum();
+    let to_amplitude = 2.0 / window_sum;
+    let threshold = 10f32.powf(config.threshold_db / 20.0) / to_amplitude;
+    let bin_width = spectrogram.bin_frequency(1);
+    let center = nfft as f32 / 2.0 / spectrogram.sample_rate as f32;
+
+    let mut finished = Vec::new();
+    let mut active: Vec<Partial> = Vec::new();
+    for (t, frame) in spectrogram.frames.iter().enumerate() {
+        let peaks: Vec<Peak> = pick_peaks(frame, threshold, config.max_peaks).into_iter()
+            .map(|(bin, m)| Peak { frequency: bin * bin_width, amplitude: m * to_amplitude })
+            .collect();
+        let mut claimed = vec![false; peaks.len()];
+        let time = spectrogram.frame_time(t) + center;
+
+        active.sort_by(|a, b| {
+            let last = |p: &Partial| p.points.last().map_or(0.0, |q| q.amplitude);
+            last(b).total_cmp(&last(a))
+        });
+        let mut continuing = Vec::new();
+        for mut partial in active.drain(..) {
+            let previous = partial.points.last().expect("partials are never empty").frequency;
+            let reach = (previous * config.max_deviation).max(bin_width);
+            let nearest = peaks.iter().enumerate()
+                .filter(|(i, peak)| !claimed[*i] && (peak.frequency - previous).abs() <= reach)
+                .min_by(|(_, a), (_, b)| (a.frequency - previous).abs().total_cmp(&(b.frequency - previous).abs()));
+            match nearest {
+                Some((i, peak)) => {
+                    claimed[i] = true;
+                    partial.points.push(PartialPoint { frame: t, time, frequency: peak.frequency, amplitude: peak.amplitude });
+                    continuing.push(partial);
+                }
+                None => finished.push(partial),
+            }
+        }
+        for (peak, _) in peaks.iter().zip(&claimed).filter(|(_, &c)| !c) {
+            let point = PartialPoint { frame: t, time, frequency: peak.frequency, amplitude: peak.amplitude };
+            continuing.push(Partial { points: vec![point] });
+        }
+        active = continuing;
+    }
+    finished.append(&mut active);
+
+    finished.retain(|p| p.points.len() >= config.min_frames);
+    finished.sort_by_key(|p| p.points[0].frame);
+    finished
+}
+
+/// Additive resynthesis of `partials` into `len` samples: one oscillator
+/// per partial whose frequency and amplitude are interpolated linearly
+/// between track points, ramping in and out over `ramp` samples at the
+/// ends. Phases are not tracked, so only the magnitude content survives.
+pub fn synthesize(partials: &[Partial], sample_rate: u32, len: usize, ramp: usize) -> Vec<f32> {
+    let rate = sample_rate as f64;
+    let mut output = vec![0.0f32; len];
+    for partial in partials {
+        let (Some(first), Some(last)) = (partial.points.first(), partial.points.last()) else { continue };
+
+        // breakpoints (sample position, frequency, amplitude), silent at both ends
+        let position = |time: f32| time as f64 * rate;
+        let mut breakpoints = vec![(position(first.time) - ramp as f64, first.frequency as f64, 0.0)];
+        breakpoints.extend(partial.points.iter().map(|p| (position(p.time), p.frequency as f64, p.amplitude as f64)));
+        breakpoints.push((position(last.time) + ramp as f64, last.frequency as f64, 0.0));
+
+        let mut phase = 0.0f64;
+        for pair in breakpoints.windows(2) {
+            let ((x0, f0, a0), (x1, f1, a1)) = (pair[0], pair[1]);
+            let start = x0.ceil().max(0.0) as usize;
+            let end = (x1.ceil().max(0.0) as usize).min(len);
+            for (i, out) in output.iter_mut().enumerate().take(end).skip(start) {
+                let t = if x1 > x0 { (i as f64 - x0) / (x1 - x0) } else { 0.0 };
+                let frequency = f0 + (f1 - f0) * t;
+                *out += ((a0 + (a1 - a0) * t) * phase.sin()) as f32;
+                phase = (phase + 2.0 * std::f64::consts::PI * frequency / rate) % (2.0 * std::f64::consts::PI);
+            }
+        }
+    }
+    output
+}
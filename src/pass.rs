@@ -0,0 +1,197 @@
+// Pluggable analysis passes: each one measures a decoded recording and adds
+// its results to a shared report, which ends up in the JSON output and the
+// plots of `analyze`. Downstream crates implement `AnalysisPass` and
+// register it next to the standard passes.
+
+use std::error::Error;
+
+use serde_json::{json, Map, Value};
+
+use crate::dynamics::{crest_factor_db, crest_factor_over_time};
+use crate::meter::{integrated_loudness, sample_peak, to_db, true_peak};
+use crate::sample::mix_to_mono;
+
+/// A decoded recording: interleaved samples normalized to [-1, 1].
+#[derive(Debug, Clone)]
+pub struct AudioBuffer {
+    pub samples: Vec<f32>,
+    pub channels: usize,
+    pub sample_rate: u32,
+}
+
+impl AudioBuffer {
+    pub fn new(samples: Vec<f32>, channels: usize, sample_rate: u32) -> Self {
+        AudioBuffer { samples, channels: channels.max(1), sample_rate }
+    }
+
+    /// Samples per channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.frames() as f64 / self.sample_rate as f64
+    }
+
+    /// The channels averaged into one.
+    pub fn mono(&self) -> Vec<f32> {
+        mix_to_mono(&self.samples, self.channels)
+    }
+}
+
+/// One labelled line of a report plot.
+#[derive(Debug, Clone)]
+pub struct PlotSeries {
+    pub label: String,
+    pub points: Vec<(f32, f32)>,
+}
+
+/// A line chart requested by a pass, rendered as `<name>.png`.
+#[derive(Debug, Clone)]
+pub struct ReportPlot {
+    pub name: String,
+    pub title: String,
+    pub x_desc: String,
+    pub y_desc: String,
+    pub log_x: bool,
+    pub series: Vec<PlotSeries>,
+}
+
+/// The results gathered by the passes: named JSON values and plots.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub values: Map<String, Value>,
+    pub plots: Vec<ReportPlot>,
+}
+
+impl Report {
+    /// Set a top-level JSON entry, replacing any earlier value.
+    pub fn insert(&mut self, key: &str, value: Value) {
+        self.values.insert(key.into(), value);
+    }
+
+    pub fn add_plot(&mut self, plot: ReportPlot) {
+        self.plots.push(plot);
+    }
+
+    /// The whole report as one JSON object.
+    pub fn to_json(&self) -> Value {
+        Value::Object(self.values.clone())
+    }
+}
+
+/// A measurement that can run as part of the standard pipeline.
+pub trait AnalysisPass {
+    /// Short identifier, used in error messages.
+    fn name(&self) -> &str;
+
+    /// Measure `audio` and record the results in `report`.
+    fn run(&self, audio: &AudioBuffer, report: &mut Report) -> Result<(), Box<dyn Error>>;
+}
+
+/// An ordered set of passes.
+#[derive(Default)]
+pub struct PassRegistry {
+    passes: Vec<Box<dyn AnalysisPass>>,
+}
+
+impl PassRegistry {
+    /// A registry without any passes.
+    pub fn new() -> Self {
+        PassRegistry::default()
+    }
+
+    /// The passes `analyze` always runs: levels and crest factor.
+    pub fn standard() -> Self {
+        let mut registry = PassRegistry::new();
+        registry.register(LevelsPass);
+        registry.passes.extend(PassRegistry::whole_signal().passes);
+        registry
+    }
+
+    /// The standard passes other than levels, for a caller that already
+    /// metered those block by block (see `LevelsPass::section`).
+    pub fn whole_signal() -> Self {
+        let mut registry = PassRegistry::new();
+        registry.register(CrestFactorPass);
+        registry
+    }
+
+    /// Add a pass; passes run in registration order.
+    pub fn register<P: AnalysisPass + 'static>(&mut self, pass: P) {
+        self.passes.push(Box::new(pass));
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    /// Run every pass in order, stopping at the first failure.
+    pub fn run(&self, audio: &AudioBuffer, report: &mut Report) -> Result<(), Box<dyn Error>> {
+        for pass in &self.passes {
+            pass.run(audio, report).map_err(|e| format!("{} pass: {}", pass.name(), e))?;
+        }
+        Ok(())
+    }
+}
+
+// JSON has no infinities; silence reports its peaks as null
+fn finite(db: f32) -> Option<f32> {
+    db.is_finite().then_some(db)
+}
+
+/// Sample peak, true peak and integrated loudness, as `levels`.
+pub struct LevelsPass;
+
+impl LevelsPass {
+    /// The `levels` entry for already measured linear peaks and loudness,
+    /// e.g. from a `LevelMeter`.
+    pub fn section(sample_peak: f32, true_peak: f32, loudness: Option<f32>) -> Value {
+        json!({
+            "sample_peak_dbfs": finite(to_db(sample_peak)),
+            "true_peak_dbtp": finite(to_db(true_peak)),
+            "integrated_lufs": loudness,
+        })
+    }
+}
+
+impl AnalysisPass for LevelsPass {
+    fn name(&self) -> &str {
+        "levels"
+    }
+
+    fn run(&self, audio: &AudioBuffer, report: &mut Report) -> Result<(), Box<dyn Error>> {
+        report.insert("levels", LevelsPass::section(
+            sample_peak(&audio.samples),
+            true_peak(&audio.samples, audio.channels),
+            integrated_loudness(&audio.samples, audio.channels, audio.sample_rate),
+        ));
+        Ok(())
+    }
+}
+
+/// Whole-file crest factor as `crest_factor_db`, and the `crest_factor`
+/// plot of it over time.
+pub struct CrestFactorPass;
+
+impl AnalysisPass for CrestFactorPass {
+    fn name(&self) -> &str {
+        "crest_factor"
+    }
+
+    fn run(&self, audio: &AudioBuffer, report: &mut Report) -> Result<(), Box<dyn Error>> {
+        report.insert("crest_factor_db", json!(finite(crest_factor_db(&audio.samples))));
+        report.add_plot(ReportPlot {
+            name: "crest_factor".into(),
+            title: "Crest Factor Over Time".into(),
+            x_desc: "Time (s)".into(),
+            y_desc: "Peak / RMS (dB)".into(),
+            log_x: false,
+            series: vec![PlotSeries {
+                label: "Crest factor".into(),
+                points: crest_factor_over_time(&audio.samples, audio.channels, audio.sample_rate),
+            }],
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+// Phase unwrapping and the group delay derived from an unwrapped phase.
+
+use std::f64::consts::PI;
+
+/// Unwrap a sequence of phases in radians: wherever consecutive values jump
+/// by more than pi, add the multiple of 2 pi that removes the jump, so the
+/// result is continuous. The first value is kept as is.
+pub fn unwrap_phase(phases: &[f64]) -> Vec<f64> {
+    let mut offset = 0.0;
+    let mut unwrapped = Vec::with_capacity(phases.len());
+    for (i, &p) in phases.iter().enumerate() {
+        if i > 0 {
+            let jump = p - phases[i - 1];
+            offset -= 2.0 * PI * ((jump + PI) / (2.0 * PI)).floor();
+        }
+        unwrapped.push(p + offset);
+    }
+    unwrapped
+}
+
+/// Group delay -d(phase)/d(omega) in seconds at each of `frequencies` (Hz)
+/// from an unwrapped phase (radians), by central differences (one-sided at
+/// the ends).
+pub fn group_delay(frequencies: &[f64], unwrapped: &[f64]) -> Vec<f64> {
+    let n = frequencies.len().min(unwrapped.len());
+    (0..n).map(|i| {
+        let (a, b) = (i.saturating_sub(1), (i + 1).min(n - 1));
+        let d_omega = 2.0 * PI * (frequencies[b] - frequencies[a]);
+        if d_omega == 0.0 { 0.0 } else { -(unwrapped[b] - unwrapped[a]) / d_omega }
+    }).collect()
+}
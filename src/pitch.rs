@@ -0,0 +1,213 @@
+// Monophonic pitch tracking with YIN (de Cheveigné & Kawahara, 2002): the
+// cumulative mean normalized difference function of each frame, its first
+// dip under a threshold, refined by a parabola. The f0 contour can then be
+// segmented into notes.
+
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+use crate::key::PITCH_NAMES;
+
+/// Tuning of `track_pitch`.
+#[derive(Debug, Clone, Copy)]
+pub struct PitchConfig {
+    pub min_freq: f32,  // lowest f0 searched, Hz; sets the frame length
+    pub max_freq: f32,  // highest f0 searched, Hz
+    pub hop: usize,     // samples between frames
+    pub threshold: f32, // YIN dip threshold; lower is stricter
+    pub floor_db: f32,  // frames quieter than this (dBFS RMS) are unvoiced
+}
+
+impl PitchConfig {
+    /// 50 Hz - 2 kHz, one frame every 10 ms.
+    pub fn new(sample_rate: u32) -> Self {
+        PitchConfig { min_freq: 50.0, max_freq: 2000.0, hop: (sample_rate / 100).max(1) as usize, threshold: 0.15, floor_db: -50.0 }
+    }
+
+    // longest and shortest lags searched, in samples
+    fn lags(&self, sample_rate: u32) -> (usize, usize) {
+        let max_lag = (sample_rate as f32 / self.min_freq).ceil() as usize;
+        let min_lag = ((sample_rate as f32 / self.max_freq).floor() as usize).max(2);
+        (min_lag, max_lag)
+    }
+
+    /// Samples each frame spans: the integration window plus the longest lag.
+    pub fn frame_len(&self, sample_rate: u32) -> usize {
+        2 * self.lags(sample_rate).1
+    }
+}
+
+/// The pitch estimate of the frame centered at `time`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitchPoint {
+    pub time: f32,              // seconds
+    pub frequency: Option<f32>, // Hz; None when unvoiced
+    pub clarity: f32,           // 1 - the normalized difference at the dip, 0..1
+    pub rms_db: f32,            // frame level, dBFS
+}
+
+/// f0 of every frame of a mono signal.
+pub fn track_pitch(samples: &[f32], sample_rate: u32, config: PitchConfig) -> Vec<PitchPoint> {
+    let (min_lag, max_lag) = config.lags(sample_rate);
+    let window = max_lag;
+    let frame_len = config.frame_len(sample_rate);
+    if samples.len() < frame_len || min_lag >= max_lag {
+        return Vec::new();
+    }
+
+    // cross term of the difference function by FFT; the size keeps the lags
+    // searched clear of circular wrap-around
+    let size = frame_len.next_power_of_two();
+    let mut planner = FftPlanner::new();
+    let forward = planner.plan_fft_forward(size);
+    let inverse = planner.plan_fft_inverse(size);
+    let mut head = vec![Complex { re: 0.0, im: 0.0 }; size];
+    let mut whole = vec![Complex { re: 0.0, im: 0.0 }; size];
+    let mut squares = vec![0f32; frame_len + 1];
+    let mut cmnd = vec![0f32; max_lag + 1];
+
+    (0..=samples.len() - frame_len).step_by(config.hop.max(1)).map(|start| {
+        let frame = &samples[start..start + frame_len];
+        let time = (start + frame_len / 2) as f32 / sample_rate as f32;
+        let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / frame_len as f32;
+        let rms_db = 10.0 * (mean_square + 1e-12).log10();
+
+        // prefix sums of squares give the energy of any window position
+        for (i, &s) in frame.iter().enumerate() {
+            squares[i + 1] = squares[i] + s * s;
+        }
+        for (i, (h, w)) in head.iter_mut().zip(whole.iter_mut()).enumerate() {
+            let s = frame.get(i).copied().unwrap_or(0.0);
+            *w = Complex { re: s, im: 0.0 };
+            *h = Complex { re: if i < window { s } else { 0.0 }, im: 0.0 };
+        }
+        forward.process(&mut head);
+        forward.process(&mut whole);
+        for (h, w) in head.iter_mut().zip(&whole) {
+            *h = h.conj() * w;
+        }
+        inverse.process(&mut head);
+
+        // d(tau) = e(0) + e(tau) - 2 r(tau), normalized by its running mean
+        let energy = |lag: usize| squares[lag + window] - squares[lag];
+        cmnd[0] = 1.0;
+        let mut running = 0.0;
+        for lag in 1..=max_lag {
+            let difference = (energy(0) + energy(lag) - 2.0 * head[lag].re / size as f32).max(0.0);
+            running += difference;
+            cmnd[lag] = if running > 0.0 { difference * lag as f32 / running } else { 1.0 };
+        }
+
+        // the first dip under the threshold, followed to its bottom; failing
+        // that, the deepest point, reported unvoiced
+        let dip = (min_lag..max_lag).find(|&lag| cmnd[lag] < config.threshold).map(|mut lag| {
+            while lag + 1 < max_lag && cmnd[lag + 1] < cmnd[lag] {
+                lag += 1;
+            }
+            lag
+        });
+        let lag = dip.unwrap_or_else(|| (min_lag..max_lag).min_by(|&a, &b| cmnd[a].total_cmp(&cmnd[b])).unwrap_or(min_lag));
+        let (a, b, c) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+        let curvature = a - 2.0 * b + c;
+        let offset = if curvature > 0.0 { (0.5 * (a - c) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+        let clarity = (1.0 - (b - 0.25 * (a - c) * offset)).clamp(0.0, 1.0);
+
+        let voiced = dip.is_some() && rms_db >= config.floor_db;
+        let frequency = voiced.then(|| sample_rate as f32 / (lag as f32 + offset));
+        PitchPoint { time, frequency, clarity, rms_db }
+    }).collect()
+}
+
+/// Fractional MIDI note number of a frequency (A4 = 440 Hz = 69).
+pub fn midi_pitch(frequency: f32) -> f32 {
+    69.0 + 12.0 * (frequency / 440.0).log2()
+}
+
+/// Scientific pitch name of a MIDI note number, e.g. 69 -> "A4".
+pub fn note_name(key: u8) -> String {
+    format!("{}{}", PITCH_NAMES[key as usize % 12], key as i32 / 12 - 1)
+}
+
+/// A note found in a pitch contour: a run of voiced frames near one pitch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub start: f32,               // seconds
+    pub end: f32,                 // seconds
+    pub key: u8,                  // MIDI note number, the run's median pitch rounded
+    pub level_db: f32,            // loudest frame, dBFS RMS
+    pub confidence: f32,          // mean clarity of its frames, 0..1
+    pub contour: Vec<(f32, f32)>, // (time, fractional MIDI pitch) of its frames
+}
+
+/// Tuning of `segment_notes`.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentConfig {
+    pub min_duration: f32, // shorter notes are dropped, seconds
+    pub tolerance: f32,    // semitones the pitch may stray from a note's key (vibrato, glides)
+    pub min_change: usize, // frames a new pitch must hold before it starts a new note
+}
+
+impl Default for SegmentConfig {
+    fn default() -> Self {
+        SegmentConfig { min_duration: 0.06, tolerance: 0.75, min_change: 3 }
+    }
+}
+
+/// Split a contour into notes. A note ends at an unvoiced frame or where
+/// the pitch has left the note's key for `min_change` frames in a row.
+pub fn segment_notes(points: &[PitchPoint], config: SegmentConfig) -> Vec<Note> {
+    let hop = match points {
+        [a, b, ..] => b.time - a.time,
+        _ => 0.0,
+    };
+    let mut notes = Vec::new();
+    let mut run: Vec<&PitchPoint> = Vec::new();
+    let mut key = 0.0;
+    let mut strays = 0;
+
+    let mut finish = |run: &mut Vec<&PitchPoint>| {
+        if run.is_empty() {
+            return;
+        }
+        let start = run[0].time - hop / 2.0;
+        let end = run[run.len() - 1].time + hop / 2.0;
+        if end - start >= config.min_duration {
+            let contour: Vec<(f32, f32)> = run.iter().filter_map(|p| Some((p.time, midi_pitch(p.frequency?)))).collect();
+            let mut pitches: Vec<f32> = contour.iter().map(|c| c.1).collect();
+            pitches.sort_by(f32::total_cmp);
+            let median = pitches[pitches.len() / 2];
+            let level_db = run.iter().map(|p| p.rms_db).fold(f32::NEG_INFINITY, f32::max);
+            let confidence = run.iter().map(|p| p.clarity).sum::<f32>() / run.len() as f32;
+            let key = median.round().clamp(0.0, 127.0) as u8;
+            notes.push(Note { start, end, key, level_db, confidence, contour });
+        }
+        run.clear();
+    };
+
+    for point in points {
+        let Some(frequency) = point.frequency else {
+            finish(&mut run);
+            strays = 0;
+            continue;
+        };
+        let pitch = midi_pitch(frequency);
+        if run.is_empty() {
+            key = pitch.round();
+        } else if (pitch - key).abs() > config.tolerance {
+            strays += 1;
+            if strays >= config.min_change {
+                // the strays belong to the new note
+                let tail = run.split_off(run.len() + 1 - strays);
+                finish(&mut run);
+                run = tail;
+                key = pitch.round();
+                strays = 0;
+            }
+        } else {
+            strays = 0;
+        }
+        run.push(point);
+    }
+    finish(&mut run);
+    notes
+}
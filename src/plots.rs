@@ -0,0 +1,608 @@
+// Shared chart helpers for the analysis subcommands.
+
+use std::error::Error;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use fft_rs::auditory::FreqScale;
+use fft_rs::kernels::amplitude_db;
+use fft_rs::modulation::ModulationBand;
+use fft_rs::stft::Spectrogram;
+use plotters::chart::MeshStyle;
+use plotters::coord::ranged1d::Ranged;
+use plotters::coord::types::RangedCoordf32;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use tracing::instrument;
+
+/// Chart background and text colors.
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// Color scale of heat maps (spectrograms, tempograms, similarity matrices).
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Gray,
+    Bone,
+    Copper,
+    Vulcano,
+}
+
+/// Frequency axis of spectrograms and spectrum plots.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FreqScaleArg {
+    Linear,
+    Mel,
+    Bark,
+    Erb,
+}
+
+impl From<FreqScaleArg> for FreqScale {
+    fn from(arg: FreqScaleArg) -> Self {
+        match arg {
+            FreqScaleArg::Linear => FreqScale::Linear,
+            FreqScaleArg::Mel => FreqScale::Mel,
+            FreqScaleArg::Bark => FreqScale::Bark,
+            FreqScaleArg::Erb => FreqScale::Erb,
+        }
+    }
+}
+
+// set once from the command line (or fft-rs.toml) before anything is drawn
+static STYLE: OnceLock<(Theme, Colormap, Option<FreqScale>)> = OnceLock::new();
+
+/// Choose the theme, colormap and frequency axis (None: each chart's own
+/// default) of every chart drawn afterwards.
+pub fn set_style(theme: Theme, colormap: Colormap, freq_scale: Option<FreqScale>) {
+    let _ = STYLE.set((theme, colormap, freq_scale));
+}
+
+fn style() -> (Theme, Colormap, Option<FreqScale>) {
+    STYLE.get().copied().unwrap_or_default()
+}
+
+/// The frequency axis of spectrograms and spectra, linear by default.
+pub fn freq_scale() -> FreqScale {
+    style().2.unwrap_or_default()
+}
+
+/// Description of a frequency axis drawn on `scale`.
+pub fn freq_desc(scale: FreqScale) -> String {
+    match scale {
+        FreqScale::Linear => "Frequency (Hz)".to_string(),
+        scale => format!("Frequency (Hz, {} scale)", scale.unit()),
+    }
+}
+
+/// The chart background color.
+pub fn background() -> RGBColor {
+    match style().0 {
+        Theme::Light => WHITE,
+        Theme::Dark => RGBColor(24, 24, 28),
+    }
+}
+
+/// The color of captions, axes, labels and neutral marks.
+pub fn foreground() -> RGBColor {
+    match style().0 {
+        Theme::Light => BLACK,
+        Theme::Dark => RGBColor(220, 220, 220),
+    }
+}
+
+/// Heat map color of `value` on the scale `min..max`.
+pub fn heat_color(value: f32, min: f32, max: f32) -> RGBColor {
+    let rgb = |(r, g, b): (u8, u8, u8)| RGBColor(r, g, b);
+    match style().1 {
+        Colormap::Viridis => ViridisRGB.get_color_normalized(value, min, max),
+        Colormap::Gray => BlackWhite.get_color_normalized(value, min, max),
+        Colormap::Bone => Bone.get_color_normalized(value, min, max),
+        Colormap::Copper => Copper.get_color_normalized(value, min, max),
+        Colormap::Vulcano => rgb(VulcanoHSL.get_color_normalized(value, min, max).rgb()),
+    }
+}
+
+/// The chart caption font in the theme's text color.
+pub fn caption_font(size: u32) -> TextStyle<'static> {
+    ("sans-serif", size).into_font().color(&foreground())
+}
+
+/// Color the axes, grid, tick labels and axis descriptions of a mesh for
+/// the theme.
+pub fn themed<'m, 'a, 'b, X: Ranged, Y: Ranged, DB: DrawingBackend>(
+    mesh: &'m mut MeshStyle<'a, 'b, X, Y, DB>,
+) -> &'m mut MeshStyle<'a, 'b, X, Y, DB> {
+    let fg = foreground();
+    mesh.axis_style(fg)
+        .bold_line_style(fg.mix(0.2))
+        .light_line_style(fg.mix(0.05))
+        .label_style(("sans-serif", 15).into_font().color(&fg))
+        .axis_desc_style(("sans-serif", 30).into_font().color(&fg))
+}
+
+/// One labelled line on a chart.
+pub struct Series<'a> {
+    pub label: &'a str,
+    pub points: &'a [(f32, f32)],
+    pub color: RGBColor,
+}
+
+/// Plots one or more series against shared axes, auto-ranged to the data.
+///
+/// # Arguments
+///
+/// * `caption` - The chart title.
+/// * `x_desc` / `y_desc` - The axis descriptions.
+/// * `series` - The lines to draw, each with its own legend entry.
+/// * `log_x` - Use a logarithmic x axis (x values must be positive).
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+pub fn plot_lines(
+    caption: &str,
+    x_desc: &str,
+    y_desc: &str,
+    series: &[Series],
+    log_x: bool,
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    // a log axis is drawn as a linear axis over log10(x) with relabelled ticks
+    let to_x = |x: f32| if log_x { x.max(f32::MIN_POSITIVE).log10() } else { x };
+    let root_area = BitMapBackend::new(output_path, (1280, 720)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    // Fit both axes to the data, with a little headroom on y
+    let points = || series.iter().flat_map(|s| s.points.iter());
+    let (x_min, x_max) = points().map(|p| to_x(p.0))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    let (y_min, y_max) = points().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+    if !x_min.is_finite() {
+        return Err("nothing to plot".into());
+    }
+    let pad = ((y_max - y_min) * 0.05).max(0.5);
+    let x_max = if x_max > x_min { x_max } else { x_min + 1.0 };
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption(caption, caption_font(40))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, (y_min - pad)..(y_max + pad))?;
+
+    let label_x = |x: &f32| match 10f32.powf(*x) {
+        value if log_x && value < 10.0 => format!("{:.1}", value),
+        value if log_x => format!("{:.0}", value),
+        _ => format!("{}", x),
+    };
+    themed(&mut chart.configure_mesh())
+        .x_label_formatter(&label_x)
+        .x_desc(x_desc)
+        .y_desc(y_desc)
+        .draw()?;
+
+    for s in series {
+        let color = s.color;
+        let points = s.points.iter().map(|&(x, y)| (to_x(x), y));
+        chart.draw_series(LineSeries::new(points, color.stroke_width(2)))?
+            .label(s.label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    }
+
+    chart
+        .configure_series_labels()
+        .label_font(caption_font(15))
+        .background_style(background().mix(0.8))
+        .border_style(foreground())
+        .draw()?;
+
+    Ok(())
+}
+
+/// Plots a waveform with optional cue markers, click ticks and a
+/// highlighted region.
+///
+/// # Arguments
+///
+/// * `samples` - The samples to draw, one point each.
+/// * `markers` - Labelled sample indices, drawn as green vertical lines.
+/// * `clicks` - Sample indices of detected clicks, drawn as red ticks.
+/// * `region` - A sample range to shade, such as a selected excerpt.
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+pub fn plot_waveform(
+    samples: &[f32],
+    markers: &[(usize, String)],
+    clicks: &[usize],
+    region: Option<(usize, usize)>,
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    // Define the dimensions of the plot
+    let root_area = BitMapBackend::new(output_path, (1280, 720)).into_drawing_area();
+    root_area.fill(&background())?;
+
+    // Create a chart context
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Audio Waveform", caption_font(40))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0..samples.len(), -1.0f32..1.0f32)?;
+
+    // Configure the mesh (axes)
+    themed(&mut chart.configure_mesh())
+        .x_desc("Sample Index")
+        .y_desc("Amplitude")
+        .draw()?;
+
+    // Shade the highlighted region behind the waveform
+    if let Some((start, end)) = region {
+        let shade = RGBColor(255, 200, 0);
+        chart.draw_series(std::iter::once(Rectangle::new([(start, -1.0), (end, 1.0)], shade.mix(0.3).filled())))?
+            .label("Selection")
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 20, y + 5)], shade.mix(0.3).filled()));
+    }
+
+    // Prepare the data as plot points
+    let plot_points: Vec<(usize, f32)> = samples.iter().enumerate().map(|(i, &y)| (i, y)).collect();
+
+    // Draw the waveform line
+    chart.draw_series(LineSeries::new(
+        plot_points,
+        BLUE, // Waveform color
+    ))?
+    .label("Waveform")
+    .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
+
+    // Draw cue markers as labeled vertical lines
+    for (x, label) in markers {
+        chart.draw_series(LineSeries::new(
+            vec![(*x, -1.0), (*x, 1.0)],
+            MARKER_COLOR.stroke_width(2),
+        ))?;
+        chart.draw_series(vec![
+            Text::new(
+                label.clone(),
+                (*x, 0.95), // Just inside the top of the plot
+                ("sans-serif", 18).into_font().color(&MARKER_COLOR),
+            )
+        ])?;
+    }
+
+    // Draw detected clicks as short red ticks along the top and bottom edges
+    if !clicks.is_empty() {
+        let click_color = RGBColor(220, 0, 0);
+        chart.draw_series(clicks.iter().flat_map(|&x| [
+            PathElement::new(vec![(x, 1.0), (x, 0.85)], click_color.stroke_width(2)),
+            PathElement::new(vec![(x, -1.0), (x, -0.85)], click_color.stroke_width(2)),
+        ]))?
+        .label(format!("Clicks ({})", clicks.len()))
+        .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], click_color));
+    }
+
+    // Draw the legend
+    chart
+        .configure_series_labels()
+        .label_font(caption_font(15))
+        .background_style(background().mix(0.8))
+        .border_style(foreground())
+        .draw()?;
+
+    Ok(())
+}
+
+// cue markers on the waveform and spectrogram plots
+const MARKER_COLOR: RGBColor = RGBColor(0, 160, 0);
+
+/// Plots a spectrogram as a heat map of magnitude in dB over time and frequency.
+///
+/// # Arguments
+///
+/// * `spectrogram` - The STFT magnitudes to draw.
+/// * `caption` - The chart title.
+/// * `tracks` - Lines of (time, frequency) points to draw over the heat map,
+///   such as partial tracks; may be empty.
+/// * `markers` - Labelled times in seconds, drawn as green vertical lines
+///   like the waveform's cue markers; may be empty.
+/// * `output_path` - The file path where the spectrogram image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+pub fn plot_spectrogram(
+    spectrogram: &Spectrogram,
+    caption: &str,
+    tracks: &[Vec<(f32, f32)>],
+    markers: &[(f32, String)],
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, SPECTROGRAM_SIZE).into_drawing_area();
+    root_area.fill(&background())?;
+
+    let duration = spectrogram.duration();
+    let nyquist = spectrogram.sample_rate as f32 / 2.0;
+    let mut chart = spectrogram_chart(&root_area, caption, 0.0..duration, 0.0..nyquist, freq_scale())?;
+
+    // Pool frames/bins down to at most one cell per pixel (keeping the max)
+    let (width, height) = chart.plotting_area().dim_in_pixel();
+    let frames = spectrogram.frames.len();
+    let bins = spectrogram.num_bins();
+    let cols = frames.min(width as usize).max(1);
+    let row_bins = spectrogram_rows(nyquist, bins, height);
+    let rows = row_bins.len();
+
+    let mut grid = vec![0f32; cols * rows];
+    for (t, frame) in spectrogram.frames.iter().enumerate() {
+        let col = t * cols / frames;
+        for (cell, range) in grid[col * rows..(col + 1) * rows].iter_mut().zip(&row_bins) {
+            *cell = frame[range.clone()].iter().fold(*cell, |a, &b| a.max(b));
+        }
+    }
+
+    // Map to dB with an 80 dB display range below the loudest cell
+    let mut levels = vec![0f32; grid.len()];
+    amplitude_db(&grid, 1e-9, &mut levels);
+    let max_db = levels.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let min_db = max_db - 80.0;
+
+    let tile = SpectrogramTile { start: 0.0, end: duration, nyquist, cols, rows, levels };
+    draw_levels(&mut chart, &tile, min_db, max_db)?;
+
+    let scale = freq_scale();
+    for track in tracks {
+        chart.draw_series(LineSeries::new(track.iter().map(|&(t, f)| (t, scale.from_hz(f))), RED.stroke_width(2)))?;
+    }
+
+    let (bottom, top) = (scale.from_hz(0.0), scale.from_hz(nyquist));
+    for (t, label) in markers {
+        chart.draw_series(LineSeries::new(vec![(*t, bottom), (*t, top)], MARKER_COLOR.stroke_width(2)))?;
+        chart.draw_series(std::iter::once(Text::new(
+            label.clone(),
+            (*t, top - (top - bottom) * 0.02), // Just inside the top of the plot
+            ("sans-serif", 18).into_font().color(&MARKER_COLOR),
+        )))?;
+    }
+
+    Ok(())
+}
+
+const SPECTROGRAM_SIZE: (u32, u32) = (1920, 1080);
+
+/// A stretch of spectrogram already pooled to the chart's cells, in dB:
+/// `levels[col * rows + row]`, with row 0 at 0 Hz and rows evenly spaced
+/// on `freq_scale`.
+pub struct SpectrogramTile {
+    pub start: f32, // seconds at the left edge
+    pub end: f32,   // seconds at the right edge
+    pub nyquist: f32,
+    pub cols: usize,
+    pub rows: usize,
+    pub levels: Vec<f32>,
+}
+
+/// The bins pooled into each cell row of a spectrogram `height` pixels
+/// high, bottom row first: at most one row per pixel, and on a linear axis
+/// no more rows than bins; a warped axis spreads low bins over several rows.
+pub fn spectrogram_rows(nyquist: f32, bins: usize, height: u32) -> Vec<Range<usize>> {
+    let rows = match freq_scale() {
+        FreqScale::Linear => bins.min(height as usize).max(1),
+        _ => height.max(1) as usize,
+    };
+    freq_scale().bin_ranges(nyquist, bins, rows)
+}
+
+/// Pixels of the heat map area of a spectrogram chart: the most cells a
+/// `SpectrogramTile` needs.
+pub fn spectrogram_area() -> Result<(u32, u32), Box<dyn Error>> {
+    let (width, height) = SPECTROGRAM_SIZE;
+    let mut buffer = vec![0u8; (width * height * 3) as usize];
+    let root_area = BitMapBackend::with_buffer(&mut buffer, SPECTROGRAM_SIZE).into_drawing_area();
+    let chart = spectrogram_chart(&root_area, "", 0.0..1.0, 0.0..1.0, FreqScale::Linear)?;
+    Ok(chart.plotting_area().dim_in_pixel())
+}
+
+/// Plots one tile of a spectrogram drawn piece by piece, on the fixed dB
+/// scale `min_db..max_db` shared by all its tiles.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+pub fn plot_spectrogram_tile(
+    tile: &SpectrogramTile,
+    min_db: f32,
+    max_db: f32,
+    caption: &str,
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root_area = BitMapBackend::new(output_path, SPECTROGRAM_SIZE).into_drawing_area();
+    root_area.fill(&background())?;
+    let mut chart = spectrogram_chart(&root_area, caption, tile.start..tile.end, 0.0..tile.nyquist, freq_scale())?;
+    draw_levels(&mut chart, tile, min_db, max_db)?;
+    Ok(())
+}
+
+/// Plots the envelopes of an auditory filterbank as a heat map, each
+/// channel a band around its center frequency, on the dB scale
+/// `min_db..max_db` and on the ERB-rate axis unless another is chosen.
+///
+/// # Arguments
+///
+/// * `envelopes` - Envelope amplitudes, `envelopes[channel][frame]`.
+/// * `centers` - Center frequency of each channel in Hz, ascending.
+/// * `duration` - Seconds spanned by the frames.
+/// * `min_db` / `max_db` - The color scale in dB re full scale amplitude.
+/// * `caption` - The chart title.
+/// * `output_path` - The file path where the cochleagram image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+pub fn plot_cochleagram(
+    envelopes: &[Vec<f32>],
+    centers: &[f32],
+    duration: f32,
+    min_db: f32,
+    max_db: f32,
+    caption: &str,
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let edges = channel_edges(centers)?;
+
+    let root_area = BitMapBackend::new(output_path, SPECTROGRAM_SIZE).into_drawing_area();
+    root_area.fill(&background())?;
+    let scale = style().2.unwrap_or(FreqScale::Erb);
+    let freqs = edges[0]..edges[edges.len() - 1];
+    let mut chart = spectrogram_chart(&root_area, caption, 0.0..duration, freqs, scale)?;
+
+    // pool frames down to at most one column per pixel (keeping the max)
+    let (width, _) = chart.plotting_area().dim_in_pixel();
+    let frames = envelopes[0].len();
+    let cols = frames.min(width as usize).max(1);
+    let cell_w = duration / cols as f32;
+    for (channel, envelope) in envelopes.iter().enumerate() {
+        let mut pooled = vec![0f32; cols];
+        for (t, &value) in envelope.iter().enumerate() {
+            let cell = &mut pooled[t * cols / frames];
+            *cell = cell.max(value);
+        }
+        let (lo, hi) = (scale.from_hz(edges[channel]), scale.from_hz(edges[channel + 1]));
+        chart.draw_series(pooled.iter().enumerate().map(|(col, &value)| {
+            let db = (20.0 * (value + 1e-9).log10()).max(min_db);
+            let x = col as f32 * cell_w;
+            Rectangle::new([(x, lo), (x + cell_w, hi)], heat_color(db, min_db, max_db).filled())
+        }))?;
+    }
+
+    Ok(())
+}
+
+/// Plots the modulation spectrum of each auditory filterbank channel as a
+/// heat map: modulation frequency across (log scale), the channels up on
+/// the ERB-rate axis unless another is chosen, and modulation depth in dB
+/// (0 dB is full depth) as color.
+///
+/// # Arguments
+///
+/// * `depths` - Modulation depth of each channel in each band, `depths[channel][band]`.
+/// * `bands` - The modulation bands, ascending.
+/// * `centers` - Center frequency of each channel in Hz, ascending.
+/// * `min_db` - The depth at the bottom of the color scale.
+/// * `caption` - The chart title.
+/// * `output_path` - The file path where the plot image will be saved.
+#[instrument(name = "render", level = "debug", skip_all, fields(path = output_path))]
+pub fn plot_modulation_spectrum(
+    depths: &[Vec<f32>],
+    bands: &[ModulationBand],
+    centers: &[f32],
+    min_db: f32,
+    caption: &str,
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let edges = channel_edges(centers)?;
+    let (first, last) = match bands {
+        [] => return Err("no modulation bands to plot".into()),
+        [first, .., last] => (first, last),
+        [only] => (only, only),
+    };
+
+    let root_area = BitMapBackend::new(output_path, SPECTROGRAM_SIZE).into_drawing_area();
+    root_area.fill(&background())?;
+    let scale = style().2.unwrap_or(FreqScale::Erb);
+    // the x axis runs over log2 of the modulation frequency
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption(caption, caption_font(40))
+        .margin(20)
+        .x_label_area_size(60)
+        .y_label_area_size(80)
+        .build_cartesian_2d(
+            first.lower.log2()..last.upper.log2(),
+            scale.from_hz(edges[0])..scale.from_hz(edges[edges.len() - 1]),
+        )?;
+
+    let label_x = |x: &f32| format!("{}", 2f32.powf(*x));
+    let label_y = |y: &f32| format!("{:.0}", scale.to_hz(*y));
+    let mut mesh = chart.configure_mesh();
+    mesh.x_label_formatter(&label_x);
+    if scale != FreqScale::Linear {
+        mesh.y_label_formatter(&label_y);
+    }
+    themed(&mut mesh)
+        .disable_mesh()
+        .x_desc("Modulation frequency (Hz)")
+        .y_desc(freq_desc(scale))
+        .draw()?;
+
+    for (channel, row) in depths.iter().enumerate() {
+        let (lo, hi) = (scale.from_hz(edges[channel]), scale.from_hz(edges[channel + 1]));
+        chart.draw_series(bands.iter().zip(row).map(|(band, &depth)| {
+            let db = (20.0 * (depth + 1e-9).log10()).clamp(min_db, 0.0);
+            let (x0, x1) = (band.lower.log2(), band.upper.log2());
+            Rectangle::new([(x0, lo), (x1, hi)], heat_color(db, min_db, 0.0).filled())
+        }))?;
+    }
+
+    Ok(())
+}
+
+// edges of the filterbank channels around `centers`: halfway between
+// neighbors on the ERB-rate scale (the filters' own spacing), mirrored at
+// the ends
+fn channel_edges(centers: &[f32]) -> Result<Vec<f32>, Box<dyn Error>> {
+    let rate: Vec<f32> = centers.iter().map(|&f| FreqScale::Erb.from_hz(f)).collect();
+    let mut edges = Vec::with_capacity(rate.len() + 1);
+    match rate.as_slice() {
+        [] => return Err("no channels to plot".into()),
+        [only] => edges.extend([only - 0.5, only + 0.5]),
+        [first, second, ..] => {
+            edges.push(first - (second - first) / 2.0);
+            edges.extend(rate.windows(2).map(|pair| (pair[0] + pair[1]) / 2.0));
+            let (last, before) = (rate[rate.len() - 1], rate[rate.len() - 2]);
+            edges.push(last + (last - before) / 2.0);
+        }
+    }
+    Ok(edges.iter().map(|&e| FreqScale::Erb.to_hz(e).max(0.0)).collect())
+}
+
+type SpectrogramChart<'a, 'b> = ChartContext<'a, BitMapBackend<'b>, Cartesian2d<RangedCoordf32, RangedCoordf32>>;
+
+// caption and time/frequency axes of a spectrogram over `time` seconds and
+// `freqs` Hz; the y axis runs over positions on `scale`, labelled in Hz
+fn spectrogram_chart<'a, 'b>(
+    root_area: &'a DrawingArea<BitMapBackend<'b>, Shift>,
+    caption: &str,
+    time: Range<f32>,
+    freqs: Range<f32>,
+    scale: FreqScale,
+) -> Result<SpectrogramChart<'a, 'b>, Box<dyn Error>> {
+    let mut chart = ChartBuilder::on(root_area)
+        .caption(caption, caption_font(40))
+        .margin(20)
+        .x_label_area_size(60)
+        .y_label_area_size(80)
+        .build_cartesian_2d(time, scale.from_hz(freqs.start)..scale.from_hz(freqs.end))?;
+
+    let label_y = |y: &f32| format!("{:.0}", scale.to_hz(*y));
+    let mut mesh = chart.configure_mesh();
+    if scale != FreqScale::Linear {
+        mesh.y_label_formatter(&label_y);
+    }
+    themed(&mut mesh)
+        .disable_mesh()
+        .x_desc("Time (s)")
+        .y_desc(freq_desc(scale))
+        .draw()?;
+    Ok(chart)
+}
+
+// one rectangle per cell of `tile`; cells below `min_db` take its color,
+// and NaN cells (past the end of the signal) are left blank
+fn draw_levels(chart: &mut SpectrogramChart, tile: &SpectrogramTile, min_db: f32, max_db: f32) -> Result<(), Box<dyn Error>> {
+    let scale = freq_scale();
+    let bottom = scale.from_hz(0.0);
+    let cell_w = (tile.end - tile.start) / tile.cols as f32;
+    let cell_h = (scale.from_hz(tile.nyquist) - bottom) / tile.rows as f32;
+    chart.draw_series(tile.levels.iter().enumerate().filter(|(_, db)| !db.is_nan()).map(|(i, &db)| {
+        let (col, row) = (i / tile.rows, i % tile.rows);
+        let x = tile.start + col as f32 * cell_w;
+        let y = bottom + row as f32 * cell_h;
+        let color = heat_color(db.max(min_db), min_db, max_db);
+        Rectangle::new([(x, y), (x + cell_w, y + cell_h)], color.filled())
+    }))?;
+    Ok(())
+}
@@ -0,0 +1,67 @@
+// Apache Parquet tables of per-frame features: one row per STFT frame,
+// one float column per feature dimension, written in row groups so long
+// recordings never need a whole-file batch in memory.
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float32Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::{ParquetError, Result};
+use parquet::file::properties::WriterProperties;
+
+use crate::export::NamedArray;
+
+// frames per row group
+const ROW_GROUP_FRAMES: usize = 65536;
+
+// column names of one feature: "rms" for a vector, "mfcc_0", "mfcc_1", ...
+// for a matrix
+fn column_names(array: &NamedArray) -> Vec<String> {
+    match array.shape.as_slice() {
+        [_] => vec![array.name.clone()],
+        [_, width] => (0..*width).map(|i| format!("{}_{}", array.name, i)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Write `features` as a Snappy-compressed Parquet table with one row per
+/// frame. Every feature must be a vector of one value per frame or a
+/// (frames x width) matrix, which becomes `width` columns.
+pub fn write_parquet_file<P: AsRef<Path>>(path: P, features: &[NamedArray]) -> Result<()> {
+    let frames = features.first().map_or(0, |array| array.shape[0]);
+    for array in features {
+        if array.shape.len() > 2 || array.shape.first() != Some(&frames) {
+            return Err(ParquetError::General(format!("{} does not have one row per frame", array.name)));
+        }
+    }
+
+    let fields: Vec<Field> = features.iter()
+        .flat_map(column_names)
+        .map(|name| Field::new(name, DataType::Float32, false))
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_max_row_group_size(ROW_GROUP_FRAMES)
+        .build();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(properties))?;
+
+    for start in (0..frames).step_by(ROW_GROUP_FRAMES) {
+        let end = (start + ROW_GROUP_FRAMES).min(frames);
+        let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+        for array in features {
+            let width = array.shape.get(1).copied().unwrap_or(1);
+            for i in 0..width {
+                let values = (start..end).map(|frame| array.data[frame * width + i]);
+                columns.push(Arc::new(Float32Array::from_iter_values(values)));
+            }
+        }
+        writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+    writer.close()?;
+    Ok(())
+}
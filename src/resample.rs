@@ -0,0 +1,120 @@
+// Sample-rate conversion by band-limited (windowed-sinc) interpolation.
+
+use std::f64::consts::PI;
+
+use crate::stft::{StftConfig, StftStream};
+use crate::window::Window;
+
+// zero crossings of the sinc kept on each side of the interpolation point;
+// more is sharper and slower
+const ZERO_CROSSINGS: f64 = 16.0;
+
+// frame length of the spectrum decimation_aliasing_db measures with: fine
+// enough that a tone just below the new Nyquist doesn't leak past it
+const ALIASING_NFFT: usize = 8192;
+
+/// Resample interleaved audio from `from_rate` to `to_rate`. When
+/// downsampling, the sinc cutoff is lowered to the new Nyquist so content
+/// that can't be represented is filtered out instead of aliasing.
+pub fn resample(samples: &[f32], channels: usize, from_rate: u32, to_rate: u32) -> Vec<f32> {
+    let channels = channels.max(1);
+    if from_rate == to_rate || from_rate == 0 || to_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let ratio = to_rate as f64 / from_rate as f64;
+    let cutoff = ratio.min(1.0); // fraction of the input Nyquist to keep
+    let half_width = (ZERO_CROSSINGS / cutoff).ceil() as isize; // in input frames
+
+    let frames_in = samples.len() / channels;
+    let frames_out = (frames_in as f64 * ratio).ceil() as usize;
+    let mut out = vec![0.0f32; frames_out * channels];
+    let mut acc = vec![0.0f64; channels];
+
+    for n in 0..frames_out {
+        // position of this output frame on the input time axis
+        let t = n as f64 / ratio;
+        let center = t.floor() as isize;
+        acc.iter_mut().for_each(|a| *a = 0.0);
+
+        for k in (center - half_width + 1)..=(center + half_width) {
+            if k < 0 || k as usize >= frames_in {
+                continue;
+            }
+            let x = t - k as f64;
+            let w = cutoff * sinc(cutoff * x) * hann(x / half_width as f64);
+            let frame = &samples[k as usize * channels..(k as usize + 1) * channels];
+            for (a, &s) in acc.iter_mut().zip(frame) {
+                *a += w * s as f64;
+            }
+        }
+
+        for (o, &a) in out[n * channels..(n + 1) * channels].iter_mut().zip(&acc) {
+            *o = a as f32;
+        }
+    }
+
+    out
+}
+
+/// The band-limited counterpart of `samples.iter().step_by(step)` on
+/// interleaved audio: each kept sample is first low-passed (within its own
+/// channel) to the Nyquist frequency of the decimated stream. Only the kept
+/// samples are computed.
+pub fn decimate(samples: &[f32], channels: usize, step: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    if step <= 1 {
+        return samples.to_vec();
+    }
+    // a channel keeps one sample per step / channels of its frames
+    let cutoff = (channels as f64 / step as f64).min(1.0);
+    let half_width = (ZERO_CROSSINGS / cutoff).ceil() as isize;
+    let frames = (samples.len() / channels) as isize;
+    let kernel: Vec<f64> = (-half_width + 1..half_width)
+        .map(|k| cutoff * sinc(cutoff * k as f64) * hann(k as f64 / half_width as f64))
+        .collect();
+
+    (0..samples.len()).step_by(step).map(|i| {
+        let (frame, channel) = ((i / channels) as isize, i % channels);
+        let acc: f64 = kernel.iter().zip(-half_width + 1..half_width)
+            .filter(|&(_, k)| frame + k >= 0 && frame + k < frames)
+            .map(|(&h, k)| h * samples[(frame + k) as usize * channels + channel] as f64)
+            .sum();
+        acc as f32
+    }).collect()
+}
+
+/// Aliasing from plain decimation (keeping every `factor`th sample of mono
+/// `samples`), in dB: the energy above the new Nyquist, 1 / (2 * factor)
+/// cycles per sample, relative to the energy below it, from a
+/// Blackman-windowed average spectrum of the full-rate signal. That is what
+/// folds down, measured where it starts rather than against a filter that
+/// rolls off on its own. `None` if nothing sits below the new Nyquist.
+pub fn decimation_aliasing_db(samples: &[f32], factor: usize) -> Option<f32> {
+    if factor <= 1 || samples.len() < 2 {
+        return None;
+    }
+    // the largest power of two the signal fills, capped to keep it a cheap pass
+    let nfft = ALIASING_NFFT.min(1 << samples.len().ilog2());
+    let cutoff_bin = nfft / (2 * factor);
+    let mut stream = StftStream::new(StftConfig::with_overlap(nfft, 0.5), Window::Blackman);
+    let (mut kept, mut aliased) = (0.0f64, 0.0f64);
+    for chunk in samples.chunks(nfft) {
+        stream.push(chunk, |magnitudes| {
+            for (k, &m) in magnitudes.iter().enumerate() {
+                let power = m as f64 * m as f64;
+                if k < cutoff_bin { kept += power } else { aliased += power }
+            }
+        });
+    }
+    (kept > 0.0).then(|| (10.0 * (aliased.max(1e-20) / kept).log10()) as f32)
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-12 { 1.0 } else { (PI * x).sin() / (PI * x) }
+}
+
+// Hann taper over [-1, 1], zero outside
+fn hann(x: f64) -> f64 {
+    if x.abs() >= 1.0 { 0.0 } else { 0.5 * (1.0 + (PI * x).cos()) }
+}
@@ -0,0 +1,89 @@
+// Reverberation time from an impulse response: Schroeder backward
+// integration and line fits to the decay curve (ISO 3382).
+
+use crate::bands::{band_layout, BandFraction};
+use crate::biquad::Biquad;
+
+// the last part of the response is taken as the noise floor
+const NOISE_TAIL: f32 = 0.1;
+
+/// Decay times in seconds, extrapolated to 60 dB. A field is `None` when
+/// the decay curve never falls far enough for that fit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReverbTimes {
+    pub edt: Option<f32>, // early decay time, fit over 0 to -10 dB
+    pub t20: Option<f32>, // fit over -5 to -25 dB
+    pub t30: Option<f32>, // fit over -5 to -35 dB
+}
+
+/// Schroeder decay curve in dB (0 dB at the start), integrated backwards
+/// from the end of the response. The response is taken from its peak on,
+/// and the noise floor measured in the last 10% is subtracted first so it
+/// doesn't flatten the tail.
+pub fn schroeder_decay(ir: &[f32]) -> Vec<f32> {
+    let onset = ir.iter().enumerate()
+        .fold((0, 0.0f32), |best, (i, &s)| if s.abs() > best.1 { (i, s.abs()) } else { best })
+        .0;
+    let ir = &ir[onset..];
+    if ir.is_empty() {
+        return Vec::new();
+    }
+
+    let tail = &ir[ir.len() - ((ir.len() as f32 * NOISE_TAIL) as usize).max(1)..];
+    let noise = tail.iter().map(|&s| s as f64 * s as f64).sum::<f64>() / tail.len() as f64;
+
+    let mut energy = vec![0.0f64; ir.len()];
+    let mut acc = 0.0;
+    for i in (0..ir.len()).rev() {
+        acc += (ir[i] as f64 * ir[i] as f64 - noise).max(0.0);
+        energy[i] = acc;
+    }
+
+    let total = energy[0].max(f64::MIN_POSITIVE);
+    energy.iter().map(|&e| (10.0 * (e / total).log10()).max(-200.0) as f32).collect()
+}
+
+/// EDT, T20 and T30 from a Schroeder decay curve.
+pub fn reverb_times(decay_db: &[f32], sample_rate: u32) -> ReverbTimes {
+    ReverbTimes {
+        edt: decay_time(decay_db, sample_rate, 0.0, -10.0),
+        t20: decay_time(decay_db, sample_rate, -5.0, -25.0),
+        t30: decay_time(decay_db, sample_rate, -5.0, -35.0),
+    }
+}
+
+// least-squares slope of the curve between two levels, scaled to 60 dB
+fn decay_time(decay_db: &[f32], sample_rate: u32, from_db: f32, to_db: f32) -> Option<f32> {
+    let start = decay_db.iter().position(|&d| d <= from_db)?;
+    let end = decay_db.iter().position(|&d| d <= to_db)?;
+    if end <= start + 1 {
+        return None;
+    }
+
+    let n = (end - start) as f64;
+    let (mut sx, mut sy, mut sxx, mut sxy) = (0.0, 0.0, 0.0, 0.0);
+    for (i, &d) in decay_db[start..end].iter().enumerate() {
+        let (x, y) = ((start + i) as f64 / sample_rate as f64, d as f64);
+        sx += x;
+        sy += y;
+        sxx += x * x;
+        sxy += x * y;
+    }
+    let slope = (n * sxy - sx * sy) / (n * sxx - sx * sx);
+    (slope < 0.0).then(|| (-60.0 / slope) as f32)
+}
+
+/// The response band-limited to each octave band from 63 Hz to 8 kHz (a
+/// cascade of two band-passes), with the band's nominal center frequency.
+pub fn octave_band_responses(ir: &[f32], sample_rate: u32) -> Vec<(f32, Vec<f32>)> {
+    band_layout(BandFraction::Octave, sample_rate as f32 / 2.0).into_iter()
+        .filter(|band| (63.0..=8000.0).contains(&band.nominal))
+        .map(|band| {
+            let mut filters = [Biquad::bandpass(sample_rate, band.center as f64, std::f64::consts::SQRT_2); 2];
+            let filtered = ir.iter()
+                .map(|&s| filters.iter_mut().fold(s as f64, |x, f| f.process(x)) as f32)
+                .collect();
+            (band.nominal, filtered)
+        })
+        .collect()
+}
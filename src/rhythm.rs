@@ -0,0 +1,127 @@
+// Onset novelty and the tempogram: how strongly each tempo is present
+// over time.
+
+use crate::stft::{Spectrogram, StftConfig};
+
+// log compression gamma: log(1 + C |X|) evens out loud and quiet onsets
+const COMPRESSION: f32 = 100.0;
+
+// centre of the tempo preference of `Tempogram::dominant_tempo`
+const PREFERRED_BPM: f32 = 120.0;
+
+/// Onset novelty of every frame after the first: the summed increase of
+/// log-compressed magnitudes from the previous frame, minus its local mean
+/// (over about half a second) and half-wave rectified. One value per hop,
+/// so its rate is `sample_rate / hop`.
+pub fn onset_novelty(spectrogram: &Spectrogram) -> Vec<f32> {
+    let compressed: Vec<Vec<f32>> = spectrogram.frames.iter().map(|frame| log_compress(frame)).collect();
+    let flux: Vec<f32> = compressed.windows(2).map(|pair| spectral_flux(&pair[0], &pair[1])).collect();
+    novelty_from_flux(&flux, spectrogram.sample_rate as f32 / spectrogram.config.hop as f32)
+}
+
+/// Magnitudes mapped to log(1 + C |X|), the input of `spectral_flux`.
+pub fn log_compress(frame: &[f32]) -> Vec<f32> {
+    frame.iter().map(|&m| (1.0 + COMPRESSION * m).ln()).collect()
+}
+
+/// Summed increase from `previous` to `current` (both log-compressed).
+pub fn spectral_flux(previous: &[f32], current: &[f32]) -> f32 {
+    current.iter().zip(previous).map(|(b, a)| (b - a).max(0.0)).sum()
+}
+
+/// The novelty curve of `onset_novelty` from its per-frame flux values,
+/// `rate` values per second.
+pub fn novelty_from_flux(flux: &[f32], rate: f32) -> Vec<f32> {
+    let half = (0.25 * rate).round() as usize;
+    (0..flux.len()).map(|i| {
+        let (lo, hi) = (i.saturating_sub(half), (i + half + 1).min(flux.len()));
+        let mean = flux[lo..hi].iter().sum::<f32>() / (hi - lo) as f32;
+        (flux[i] - mean).max(0.0)
+    }).collect()
+}
+
+/// Autocorrelation tempogram of a novelty curve.
+#[derive(Debug, Clone)]
+pub struct Tempogram {
+    pub times: Vec<f32>, // window centers in seconds
+    pub tempi: Vec<f32>, // BPM of each lag, falling as the lag grows
+    pub strength: Vec<Vec<f32>>, // strength[t][i], autocorrelation normalized by lag 0
+}
+
+impl Tempogram {
+    /// The tempo with the highest mean strength over all windows, weighted
+    /// by a log-Gaussian preference for tempi near 120 BPM (one octave
+    /// standard deviation), since every multiple of the beat period
+    /// correlates too.
+    pub fn dominant_tempo(&self) -> Option<f32> {
+        (0..self.tempi.len())
+            .map(|i| {
+                let octaves = (self.tempi[i] / PREFERRED_BPM).log2();
+                let weight = (-0.5 * octaves * octaves).exp();
+                (self.tempi[i], weight * self.strength.iter().map(|row| row[i]).sum::<f32>())
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(tempo, _)| tempo)
+    }
+}
+
+/// Autocorrelate windows of `window` novelty values every `hop` values,
+/// keeping the lags of tempi in `bpm_range`. `rate` is novelty values per
+/// second.
+pub fn tempogram(novelty: &[f32], rate: f32, window: usize, hop: usize, bpm_range: (f32, f32)) -> Tempogram {
+    let min_lag = ((60.0 * rate / bpm_range.1).round() as usize).max(1);
+    let max_lag = ((60.0 * rate / bpm_range.0).round() as usize).min(window.saturating_sub(1));
+    let lags: Vec<usize> = (min_lag..=max_lag).collect();
+    let tempi = lags.iter().map(|&lag| 60.0 * rate / lag as f32).collect();
+
+    let mut times = Vec::new();
+    let mut strength = Vec::new();
+    let mut start = 0;
+    while start + window <= novelty.len() {
+        let block = &novelty[start..start + window];
+        let energy: f32 = block.iter().map(|x| x * x).sum();
+        strength.push(lags.iter().map(|&lag| {
+            if energy <= 0.0 {
+                return 0.0;
+            }
+            // left biased (unscaled for the shrinking overlap), so a beat
+            // period wins over its multiples
+            let r: f32 = block.iter().zip(&block[lag..]).map(|(a, b)| a * b).sum();
+            r / energy
+        }).collect());
+        times.push((start + window / 2) as f32 / rate);
+        start += hop.max(1);
+    }
+    Tempogram { times, tempi, strength }
+}
+
+// framing and window of `estimate_tempo`
+const TEMPO_NFFT: usize = 2048;
+const TEMPO_HOP: usize = 512;
+const TEMPO_WINDOW_SECS: f32 = 8.0;
+
+/// Overall tempo of a mono signal in BPM (30-300): the dominant tempo of
+/// its tempogram over 8 s windows, or over the whole signal if shorter.
+/// `None` if the signal is too short to hold two beats at 30 BPM.
+pub fn estimate_tempo(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let config = tempo_config();
+    config.validate(samples.len()).ok()?;
+    let novelty = onset_novelty(&Spectrogram::compute(samples, sample_rate, config));
+    tempo_of_novelty(&novelty, sample_rate)
+}
+
+/// Framing `estimate_tempo` computes its novelty curve with.
+pub fn tempo_config() -> StftConfig {
+    StftConfig { nfft: TEMPO_NFFT, hop: TEMPO_HOP }
+}
+
+/// The tempo `estimate_tempo` finds in a novelty curve taken with
+/// `tempo_config`.
+pub fn tempo_of_novelty(novelty: &[f32], sample_rate: u32) -> Option<f32> {
+    let rate = sample_rate as f32 / TEMPO_HOP as f32;
+    let window = ((TEMPO_WINDOW_SECS * rate) as usize).min(novelty.len());
+    if (window as f32) < 4.0 * rate {
+        return None;
+    }
+    tempogram(novelty, rate, window, window / 8, (30.0, 300.0)).dominant_tempo()
+}
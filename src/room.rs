@@ -0,0 +1,97 @@
+// Low-frequency resonance (room mode) detection on a magnitude spectrum.
+
+use crate::spectrum::Spectrum;
+
+// modes are only searched for below this frequency
+const MAX_MODE_HZ: f32 = 300.0;
+const MIN_MODE_HZ: f32 = 15.0;
+
+// smoothing widths in fractions of an octave: a light pass to tame bin
+// noise, and a broad pass as the baseline the modes stand out from
+const DETAIL_SMOOTHING: f32 = 12.0;
+const BASELINE_SMOOTHING: f32 = 1.0;
+
+// how far a peak or dip must stray from the baseline to count
+const MODE_THRESHOLD_DB: f32 = 3.0;
+
+// of two candidates closer than this (in octaves) only the stronger is kept
+const MIN_SEPARATION_OCTAVES: f32 = 1.0 / 6.0;
+
+/// One candidate room mode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoomMode {
+    pub frequency: f32,
+    pub deviation_db: f32, // above (peak) or below (dip) the smoothed baseline
+    pub q: f32,            // center frequency over the 3 dB bandwidth
+}
+
+impl RoomMode {
+    pub fn is_peak(&self) -> bool {
+        self.deviation_db > 0.0
+    }
+}
+
+/// Narrow peaks and dips between 15 and 300 Hz that deviate at least 3 dB
+/// from an octave-smoothed baseline, at most one per 1/6 octave, lowest
+/// frequency first. Feed it a
+/// long transform (or an averaged one) of a sweep or noise measurement.
+pub fn find_room_modes(spectrum: &Spectrum) -> Vec<RoomMode> {
+    let range: Vec<usize> = (0..spectrum.frequencies.len())
+        .filter(|&k| (MIN_MODE_HZ..=MAX_MODE_HZ).contains(&spectrum.frequencies[k]))
+        .collect();
+    if range.len() < 3 {
+        return Vec::new();
+    }
+
+    let levels: Vec<f32> = spectrum.magnitudes.iter().map(|m| 20.0 * (m + 1e-12).log10()).collect();
+    let detail: Vec<f32> = range.iter().map(|&k| smooth(spectrum, &levels, k, DETAIL_SMOOTHING)).collect();
+    let baseline: Vec<f32> = range.iter().map(|&k| smooth(spectrum, &levels, k, BASELINE_SMOOTHING)).collect();
+
+    let mut modes = Vec::new();
+    for i in 1..range.len() - 1 {
+        let deviation = detail[i] - baseline[i];
+        let is_peak = detail[i] > detail[i - 1] && detail[i] >= detail[i + 1];
+        let is_dip = detail[i] < detail[i - 1] && detail[i] <= detail[i + 1];
+        if !((is_peak && deviation >= MODE_THRESHOLD_DB) || (is_dip && deviation <= -MODE_THRESHOLD_DB)) {
+            continue;
+        }
+
+        // walk out to where the response is 3 dB back toward the baseline
+        let edge = |step: isize| {
+            let mut j = i as isize;
+            while j + step >= 0 && ((j + step) as usize) < detail.len()
+                && (detail[(j + step) as usize] - detail[i]).abs() < 3.0
+            {
+                j += step;
+            }
+            spectrum.frequencies[range[j as usize]]
+        };
+        let frequency = spectrum.frequencies[range[i]];
+        let bin_width = spectrum.frequencies[1];
+        let bandwidth = (edge(1) - edge(-1)).max(bin_width);
+
+        modes.push(RoomMode { frequency, deviation_db: deviation, q: frequency / bandwidth });
+    }
+
+    // strongest first, dropping anything too close to one already kept
+    modes.sort_by(|a, b| b.deviation_db.abs().total_cmp(&a.deviation_db.abs()));
+    let mut kept: Vec<RoomMode> = Vec::new();
+    for mode in modes {
+        if kept.iter().all(|k| (mode.frequency / k.frequency).log2().abs() >= MIN_SEPARATION_OCTAVES) {
+            kept.push(mode);
+        }
+    }
+    kept.sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+    kept
+}
+
+// mean dB level over a 1/`fraction` octave window around bin `k`
+fn smooth(spectrum: &Spectrum, levels: &[f32], k: usize, fraction: f32) -> f32 {
+    let f = spectrum.frequencies[k];
+    let half = 2f32.powf(1.0 / (2.0 * fraction));
+    let (lo, hi) = (f / half, f * half);
+    let (sum, count) = spectrum.frequencies.iter().zip(levels)
+        .filter(|(&bin, _)| bin >= lo && bin <= hi)
+        .fold((0.0, 0), |(sum, count), (_, &level)| (sum + level, count + 1));
+    sum / count.max(1) as f32
+}
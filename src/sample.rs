@@ -0,0 +1,252 @@
+use std::borrow::Cow;
+
+// Sample types that can appear in a WAV data chunk. Everything downstream
+// works in normalized f32, so a new bit depth only needs a Sample impl and
+// a line in `decode`.
+
+/// A PCM sample type with a fixed little-endian encoding.
+pub trait Sample: Copy + Send + Sync + 'static {
+    /// Magnitude that maps to 1.0 when normalized.
+    const FULL_SCALE: f32;
+    /// Encoded size in bytes.
+    const BYTES: usize;
+
+    fn to_f32(self) -> f32;
+    /// Convert from normalized f32, clamping to the representable range.
+    fn from_f32(value: f32) -> Self;
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+    fn write_le_bytes(self, out: &mut Vec<u8>);
+}
+
+/// 24-bit signed integer sample, held sign-extended in an i32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct I24(pub i32);
+
+impl Sample for u8 {
+    // 8-bit PCM is unsigned with a 128 midpoint
+    const FULL_SCALE: f32 = 128.0;
+    const BYTES: usize = 1;
+
+    fn to_f32(self) -> f32 {
+        (self as f32 - 128.0) / Self::FULL_SCALE
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value * Self::FULL_SCALE + 128.0).round().clamp(0.0, 255.0) as u8
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.push(self);
+    }
+}
+
+impl Sample for i16 {
+    const FULL_SCALE: f32 = 32768.0;
+    const BYTES: usize = 2;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / Self::FULL_SCALE
+    }
+
+    fn from_f32(value: f32) -> Self {
+        (value * Self::FULL_SCALE).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        i16::from_le_bytes([bytes[0], bytes[1]])
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Sample for I24 {
+    const FULL_SCALE: f32 = 8388608.0;
+    const BYTES: usize = 3;
+
+    fn to_f32(self) -> f32 {
+        self.0 as f32 / Self::FULL_SCALE
+    }
+
+    fn from_f32(value: f32) -> Self {
+        I24((value * Self::FULL_SCALE).round().clamp(-8388608.0, 8388607.0) as i32)
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        // place the 3 bytes in the top of an i32 and shift back to sign-extend
+        I24(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8)
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.0.to_le_bytes()[0..3]);
+    }
+}
+
+impl Sample for i32 {
+    const FULL_SCALE: f32 = 2147483648.0;
+    const BYTES: usize = 4;
+
+    fn to_f32(self) -> f32 {
+        self as f32 / Self::FULL_SCALE
+    }
+
+    fn from_f32(value: f32) -> Self {
+        // float -> int `as` casts saturate, so +1.0 lands on i32::MAX
+        (value.clamp(-1.0, 1.0) as f64 * Self::FULL_SCALE as f64).round() as i32
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        i32::from_le_bytes(bytes[0..4].try_into().unwrap())
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Sample for f32 {
+    const FULL_SCALE: f32 = 1.0;
+    const BYTES: usize = 4;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        f32::from_le_bytes(bytes[0..4].try_into().unwrap())
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+impl Sample for f64 {
+    const FULL_SCALE: f32 = 1.0;
+    const BYTES: usize = 8;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        f64::from_le_bytes(bytes[0..8].try_into().unwrap())
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
+
+/// Decoded contents of a data chunk, in the file's own sample type. 8- and
+/// 16-bit PCM borrow the payload bytes where the layout allows.
+#[derive(Debug, Clone)]
+pub enum Samples<'a> {
+    U8(Cow<'a, [u8]>),
+    I16(Cow<'a, [i16]>),
+    I24(Vec<I24>),
+    I32(Vec<i32>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+impl Samples<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            Samples::U8(v) => v.len(),
+            Samples::I16(v) => v.len(),
+            Samples::I24(v) => v.len(),
+            Samples::I32(v) => v.len(),
+            Samples::F32(v) => v.len(),
+            Samples::F64(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Normalized f32 copy, whatever the stored type.
+    pub fn to_f32(&self) -> Vec<f32> {
+        let mut out = Vec::with_capacity(self.len());
+        self.extend_f32(&mut out);
+        out
+    }
+
+    /// Convert every sample to `T` (via normalized f32).
+    pub fn to_vec<T: Sample>(&self) -> Vec<T> {
+        match self {
+            Samples::U8(v) => convert(v.as_ref()),
+            Samples::I16(v) => convert(v.as_ref()),
+            Samples::I24(v) => convert(v),
+            Samples::I32(v) => convert(v),
+            Samples::F32(v) => convert(v),
+            Samples::F64(v) => convert(v),
+        }
+    }
+
+    /// Append the samples normalized to f32, reusing `out`'s allocation.
+    pub fn extend_f32(&self, out: &mut Vec<f32>) {
+        match self {
+            Samples::U8(v) => out.extend(v.iter().map(|s| s.to_f32())),
+            Samples::I16(v) => out.extend(v.iter().map(|s| s.to_f32())),
+            Samples::I24(v) => out.extend(v.iter().map(|s| s.to_f32())),
+            Samples::I32(v) => out.extend(v.iter().map(|s| s.to_f32())),
+            Samples::F32(v) => out.extend_from_slice(v),
+            Samples::F64(v) => out.extend(v.iter().map(|s| s.to_f32())),
+        }
+    }
+
+    /// Detach from the bytes this was decoded from, copying borrowed samples.
+    pub fn into_owned(self) -> Samples<'static> {
+        match self {
+            Samples::U8(v) => Samples::U8(Cow::Owned(v.into_owned())),
+            Samples::I16(v) => Samples::I16(Cow::Owned(v.into_owned())),
+            Samples::I24(v) => Samples::I24(v),
+            Samples::I32(v) => Samples::I32(v),
+            Samples::F32(v) => Samples::F32(v),
+            Samples::F64(v) => Samples::F64(v),
+        }
+    }
+}
+
+/// Convert a slice of one sample type into another via normalized f32.
+pub fn convert<S: Sample, T: Sample>(samples: &[S]) -> Vec<T> {
+    samples.iter().map(|&s| T::from_f32(s.to_f32())).collect()
+}
+
+/// Decode little-endian bytes into samples of type `S`. A trailing partial
+/// sample is dropped.
+pub fn decode<S: Sample>(bytes: &[u8]) -> Vec<S> {
+    bytes.chunks_exact(S::BYTES).map(S::from_le_bytes).collect()
+}
+
+/// Encode samples as little-endian bytes.
+pub fn encode<S: Sample>(samples: &[S]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(samples.len() * S::BYTES);
+    for &s in samples {
+        s.write_le_bytes(&mut out);
+    }
+    out
+}
+
+/// Average interleaved channels down to one.
+pub fn mix_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    samples.chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect()
+}
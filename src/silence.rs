@@ -0,0 +1,77 @@
+// Silence detection on short-window RMS levels.
+
+use std::ops::Range;
+
+// analysis window; short enough to find gap edges to within ~10 ms
+const WINDOW_SECS: f32 = 0.01;
+
+/// Frame ranges where the level stays below `threshold_db` (dBFS RMS over
+/// all channels) for at least `min_duration` seconds.
+pub fn detect_silence(
+    samples: &[f32],
+    channels: usize,
+    sample_rate: u32,
+    threshold_db: f32,
+    min_duration: f32,
+) -> Vec<Range<usize>> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let window = ((sample_rate as f32 * WINDOW_SECS) as usize).max(1);
+    let min_frames = (min_duration * sample_rate as f32).round() as usize;
+    let threshold = 10f32.powf(threshold_db / 10.0); // as mean square
+
+    let mut gaps = Vec::new();
+    let mut gap_start: Option<usize> = None;
+
+    for start in (0..frames).step_by(window) {
+        let end = (start + window).min(frames);
+        let block = &samples[start * channels..end * channels];
+        let mean_square = block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32;
+
+        match (mean_square < threshold, gap_start) {
+            (true, None) => gap_start = Some(start),
+            (false, Some(from)) => {
+                if start - from >= min_frames {
+                    gaps.push(from..start);
+                }
+                gap_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(from) = gap_start {
+        if frames - from >= min_frames {
+            gaps.push(from..frames);
+        }
+    }
+
+    gaps
+}
+
+/// The sounding regions between silent gaps. Inner gaps are split down the
+/// middle so each region keeps a little of its lead-in and tail; gaps at
+/// the very start or end are dropped entirely.
+pub fn split_points(gaps: &[Range<usize>], total_frames: usize) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    let mut start = 0;
+
+    for gap in gaps {
+        if gap.start == 0 {
+            start = gap.end;
+            continue;
+        }
+        if gap.end >= total_frames {
+            regions.push(start..gap.start);
+            return regions;
+        }
+        let middle = (gap.start + gap.end) / 2;
+        regions.push(start..middle);
+        start = middle;
+    }
+
+    if start < total_frames {
+        regions.push(start..total_frames);
+    }
+    regions
+}
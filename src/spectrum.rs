@@ -0,0 +1,107 @@
+use crate::analyzer::with_shared;
+
+/// What the values in `Spectrum::magnitudes` measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpectrumScale {
+    Raw,       // |X[k]|, the plain FFT sums
+    Amplitude, // sine amplitude re full scale
+    Power,     // mean square per bin (FS^2), right for tones
+    Psd,       // power spectral density (FS^2/Hz), right for noise
+}
+
+/// Single-sided magnitude spectrum of a block of samples.
+#[derive(Debug, Clone)]
+pub struct Spectrum {
+    pub fft_size: usize,
+    pub frequencies: Vec<f32>, // bin center frequencies in Hz
+    pub magnitudes: Vec<f32>,  // |X[k]| for each bin up to Nyquist
+    pub window_sum: f32,       // sum of the analysis window (the sample count if unwindowed)
+    pub window_power: f32,     // sum of the squared window (likewise)
+    pub scale: SpectrumScale,
+}
+
+/// Runs a forward FFT over `samples` (zero-padded to the next power of two)
+/// and returns the magnitude of every bin below Nyquist.
+pub fn compute_spectrum(samples: &[f32], sample_rate: u32) -> Spectrum {
+    compute_spectrum_padded(samples, sample_rate, 1)
+}
+
+/// Like `compute_spectrum`, but pads a further `zero_pad` times past the next
+/// power of two. This does not add resolution, it interpolates the spectrum
+/// onto a denser grid of bins.
+pub fn compute_spectrum_padded(samples: &[f32], sample_rate: u32, zero_pad: usize) -> Spectrum {
+    let fft_size = samples.len().next_power_of_two() * zero_pad.max(1);
+    compute_spectrum_sized(samples, sample_rate, fft_size)
+}
+
+/// Runs an FFT of exactly `fft_size` points (any length, not just powers of
+/// two) over `samples` zero-padded to that size.
+///
+/// Panics if `samples` is longer than `fft_size`.
+pub fn compute_spectrum_sized(samples: &[f32], sample_rate: u32, fft_size: usize) -> Spectrum {
+    with_shared(|analyzer| analyzer.spectrum(samples, sample_rate, fft_size))
+}
+
+impl Spectrum {
+    /// Amplitude of a sine whose peak bin has `magnitude`: corrects for the
+    /// window's coherent gain and for the single-sided spectrum, so a
+    /// full-scale sine reads 1.0 (0 dBFS) whatever the window.
+    pub fn sine_amplitude(&self, magnitude: f32) -> f32 {
+        match self.scale {
+            SpectrumScale::Raw => 2.0 * magnitude / self.window_sum.max(f32::MIN_POSITIVE),
+            SpectrumScale::Amplitude => magnitude,
+            SpectrumScale::Power => (2.0 * magnitude).sqrt(),
+            SpectrumScale::Psd => (2.0 * magnitude * self.enbw_hz()).sqrt(),
+        }
+    }
+
+    /// Equivalent noise bandwidth of the analysis window in Hz: the bin
+    /// width times the window's ENBW in bins, fs sum(w^2) / sum(w)^2.
+    pub fn enbw_hz(&self) -> f32 {
+        let sample_rate = self.frequencies.get(1).map_or(0.0, |&df| df * self.fft_size as f32);
+        sample_rate * self.window_power / (self.window_sum * self.window_sum).max(f32::MIN_POSITIVE)
+    }
+
+    /// The same spectrum in other units. Amplitude takes 2 / sum(w) for
+    /// every bin but DC and Nyquist, which have no mirror image and take
+    /// 1 / sum(w); power is half the squared amplitude (a sine's mean
+    /// square), and PSD divides the power by the ENBW. Only a `Raw`
+    /// spectrum can be converted; others come back unchanged.
+    pub fn to_scale(&self, scale: SpectrumScale) -> Spectrum {
+        if self.scale != SpectrumScale::Raw {
+            return self.clone();
+        }
+        let sum = self.window_sum.max(f32::MIN_POSITIVE);
+        let enbw_hz = self.enbw_hz().max(f32::MIN_POSITIVE);
+        let magnitudes = self.magnitudes.iter().enumerate().map(|(k, &m)| {
+            let unmirrored = k == 0 || 2 * k == self.fft_size;
+            let amplitude = if unmirrored { m / sum } else { 2.0 * m / sum };
+            // unmirrored bins hold all their power; the others' amplitude
+            // already counts the mirror, and a sine's mean square is A^2 / 2
+            let power = if unmirrored { amplitude * amplitude } else { amplitude * amplitude / 2.0 };
+            match scale {
+                SpectrumScale::Raw => m,
+                SpectrumScale::Amplitude => amplitude,
+                SpectrumScale::Power => power,
+                SpectrumScale::Psd => power / enbw_hz,
+            }
+        }).collect();
+        Spectrum { magnitudes, scale, ..self.clone() }
+    }
+
+    /// The `n` strongest non-zero bins as (frequency, magnitude), loudest first.
+    pub fn top_peaks(&self, n: usize) -> Vec<(f32, f32)> {
+        let mut freq_magnitude_map: Vec<(f32, f32)> = self.frequencies.iter()
+            .cloned()
+            .zip(self.magnitudes.iter().cloned())
+            .collect();
+
+        // Sort by magnitude descending
+        freq_magnitude_map.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+        freq_magnitude_map.into_iter()
+            .filter(|&(_, magnitude)| magnitude > 0.0)
+            .take(n)
+            .collect()
+    }
+}
@@ -0,0 +1,123 @@
+// Matrices too large to hold in memory, written a row at a time to a
+// temporary file and read back in blocks, or row by row at any position.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// distinguishes the spill files of one process
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A finished matrix of f32 rows on disk, stored as little-endian values in
+/// row-major order (the layout of .npy data). The file is removed on drop.
+pub struct SpillMatrix {
+    path: PathBuf,
+    rows: usize,
+    columns: usize,
+}
+
+impl SpillMatrix {
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Size of the data on disk in bytes.
+    pub fn byte_len(&self) -> u64 {
+        (self.rows * self.columns * 4) as u64
+    }
+
+    /// Open the matrix for reading rows out of order.
+    pub fn reader(&self) -> io::Result<SpillReader> {
+        Ok(SpillReader { file: File::open(&self.path)?, columns: self.columns, bytes: Vec::new() })
+    }
+
+    /// Call `f` with successive blocks of up to `rows` rows, concatenated.
+    pub fn for_each_block<F>(&self, rows: usize, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&[f32]) -> io::Result<()>,
+    {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let block_rows = rows.max(1);
+        let mut bytes = Vec::new();
+        let mut values = Vec::new();
+        let mut done = 0;
+        while done < self.rows {
+            let count = block_rows.min(self.rows - done);
+            bytes.resize(count * self.columns * 4, 0);
+            reader.read_exact(&mut bytes)?;
+            values.clear();
+            values.extend(bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+            f(&values)?;
+            done += count;
+        }
+        Ok(())
+    }
+}
+
+/// Reads rows of a `SpillMatrix` at arbitrary positions.
+pub struct SpillReader {
+    file: File,
+    columns: usize,
+    bytes: Vec<u8>,
+}
+
+impl SpillReader {
+    /// Replace the contents of `out` with rows `start..start + count`.
+    pub fn read_rows(&mut self, start: usize, count: usize, out: &mut Vec<f32>) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start((start * self.columns * 4) as u64))?;
+        self.bytes.resize(count * self.columns * 4, 0);
+        self.file.read_exact(&mut self.bytes)?;
+        out.clear();
+        out.extend(self.bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+        Ok(())
+    }
+}
+
+impl Drop for SpillMatrix {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Collects the rows of a `SpillMatrix` in a new temporary file.
+pub struct SpillWriter {
+    matrix: SpillMatrix,
+    writer: BufWriter<File>,
+}
+
+impl SpillWriter {
+    /// Start an empty matrix of `columns` values per row.
+    pub fn create(columns: usize) -> io::Result<Self> {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("fft-rs-spill-{}-{}.f32", std::process::id(), id));
+        let file = File::options().write(true).create_new(true).open(&path)?;
+        Ok(SpillWriter { matrix: SpillMatrix { path, rows: 0, columns }, writer: BufWriter::new(file) })
+    }
+
+    /// Append one row, which must have `columns` values.
+    pub fn push_row(&mut self, row: &[f32]) -> io::Result<()> {
+        if row.len() != self.matrix.columns {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "row length does not match the matrix"));
+        }
+        for value in row {
+            self.writer.write_all(&value.to_le_bytes())?;
+        }
+        self.matrix.rows += 1;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.matrix.rows
+    }
+
+    /// Flush the rows written so far and hand over the matrix for reading.
+    pub fn finish(mut self) -> io::Result<SpillMatrix> {
+        self.writer.flush()?;
+        Ok(self.matrix)
+    }
+}
@@ -0,0 +1,91 @@
+// Stereo image analysis on interleaved two-channel audio.
+
+use std::f32::consts::SQRT_2;
+
+use crate::stft::StftConfig;
+use crate::transfer::CrossSpectra;
+
+// width-over-time window and step
+const WIDTH_WINDOW_SECS: f32 = 0.4;
+const WIDTH_STEP_SECS: f32 = 0.1;
+
+// windows quieter than this (dBFS mid+side energy) are skipped
+const WIDTH_FLOOR_DB: f32 = -70.0;
+
+/// Side-to-mid energy ratio in dB: very negative means (near) mono, 0 dB
+/// means as much difference as sum, positive means out-of-phase content.
+/// `None` if the input is silent.
+pub fn side_mid_ratio_db(stereo: &[f32]) -> Option<f32> {
+    let (mid, side) = stereo.chunks_exact(2).fold((0.0f32, 0.0f32), |(m, s), lr| {
+        let (mid, side) = ((lr[0] + lr[1]) * 0.5, (lr[0] - lr[1]) * 0.5);
+        (m + mid * mid, s + side * side)
+    });
+    if mid + side == 0.0 {
+        return None;
+    }
+    Some(10.0 * (side.max(1e-20) / mid.max(1e-20)).log10())
+}
+
+/// Side/mid ratio over time as (window start in seconds, dB), from 400 ms
+/// windows every 100 ms. Near-silent windows are left out.
+pub fn width_over_time(stereo: &[f32], sample_rate: u32) -> Vec<(f32, f32)> {
+    let frames = stereo.len() / 2;
+    let window = ((sample_rate as f32 * WIDTH_WINDOW_SECS) as usize).max(1);
+    let step = ((sample_rate as f32 * WIDTH_STEP_SECS) as usize).max(1);
+    if frames < window {
+        return Vec::new();
+    }
+
+    (0..=frames - window).step_by(step).filter_map(|start| {
+        let block = &stereo[start * 2..(start + window) * 2];
+        let energy = block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32;
+        if 10.0 * energy.log10() <= WIDTH_FLOOR_DB {
+            return None;
+        }
+        side_mid_ratio_db(block).map(|db| (start as f32 / sample_rate as f32, db))
+    }).collect()
+}
+
+/// Magnitude-squared coherence between left and right per STFT bin, as
+/// (frequency in Hz, coherence in [0, 1]), Welch-averaged over all frames:
+/// |Sxy|^2 / (Sxx Syy). 1 means the channels are linearly related at that
+/// frequency, 0 means unrelated. Needs several frames to be meaningful.
+pub fn coherence(stereo: &[f32], sample_rate: u32, config: StftConfig) -> Vec<(f32, f32)> {
+    let left: Vec<f32> = stereo.iter().step_by(2).copied().collect();
+    let right: Vec<f32> = stereo.iter().skip(1).step_by(2).copied().collect();
+    let spectra = CrossSpectra::compute(&left, &right, sample_rate, config);
+    (0..spectra.sxx.len()).map(|k| (spectra.frequency(k), spectra.coherence(k))).collect()
+}
+
+/// Correlation of left and right, sum(LR) / sqrt(sum(L^2) sum(R^2)), as on
+/// a phase correlation meter: +1 is mono, 0 unrelated channels, -1 one
+/// channel the inverse of the other. `None` if either channel is silent.
+pub fn correlation(stereo: &[f32]) -> Option<f32> {
+    let (lr, ll, rr) = stereo.chunks_exact(2).fold((0f64, 0f64, 0f64), |(lr, ll, rr), s| {
+        let (l, r) = (s[0] as f64, s[1] as f64);
+        (lr + l * r, ll + l * l, rr + r * r)
+    });
+    (ll > 0.0 && rr > 0.0).then(|| (lr / (ll * rr).sqrt()) as f32)
+}
+
+/// Goniometer (vectorscope) density: how many sample pairs land in each
+/// cell of a `size` x `size` grid spanning -`range`..`range` on both axes,
+/// `counts[row * size + col]` with row 0 at the bottom. Each pair is turned
+/// 45 degrees, mid (L + R) / sqrt(2) up and side (R - L) / sqrt(2) across,
+/// so mono content is a vertical line, left alone leans up-left and
+/// out-of-phase content lies flat.
+pub fn vectorscope(stereo: &[f32], size: usize, range: f32) -> Vec<u32> {
+    let mut counts = vec![0u32; size * size];
+    if size == 0 || range <= 0.0 {
+        return counts;
+    }
+    let cell = |v: f32| ((v / range + 1.0) / 2.0 * size as f32).floor();
+    for s in stereo.chunks_exact(2) {
+        let (l, r) = (s[0], s[1]);
+        let (col, row) = (cell((r - l) / SQRT_2), cell((l + r) / SQRT_2));
+        if (0.0..size as f32).contains(&col) && (0.0..size as f32).contains(&row) {
+            counts[row as usize * size + col as usize] += 1;
+        }
+    }
+    counts
+}
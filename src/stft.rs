@@ -0,0 +1,346 @@
+// Short-time Fourier transform: windowed, overlapping FFT frames.
+
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+
+use crate::analyzer::with_shared;
+use crate::kernels::{self, amplitude_db, window_frame};
+use crate::spectrum::{Spectrum, SpectrumScale};
+use crate::window::Window;
+
+/// Framing parameters: window length and the step between windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StftConfig {
+    pub nfft: usize, // window (and FFT) length in samples
+    pub hop: usize,  // samples between the starts of consecutive frames
+}
+
+impl StftConfig {
+    /// Config whose hop gives the requested fractional overlap in [0, 1).
+    pub fn with_overlap(nfft: usize, overlap: f32) -> Self {
+        let hop = (nfft as f32 * (1.0 - overlap)).round().max(1.0) as usize;
+        StftConfig { nfft, hop }
+    }
+
+    /// Config for a `padded_stft` / `trimmed_istft` round trip: 75% overlap
+    /// keeps the Hann analysis/synthesis pair smooth.
+    pub fn for_resynthesis(nfft: usize) -> Self {
+        StftConfig::with_overlap(nfft, 0.75)
+    }
+
+    pub fn overlap(&self) -> f32 {
+        1.0 - self.hop as f32 / self.nfft as f32
+    }
+
+    /// Check the parameters against each other and the signal length.
+    pub fn validate(&self, signal_len: usize) -> Result<(), String> {
+        if self.nfft < 2 {
+            return Err(format!("window size must be at least 2 (got {})", self.nfft));
+        }
+        if self.hop == 0 {
+            return Err("hop must be at least 1 sample".into());
+        }
+        if self.hop > self.nfft {
+            return Err(format!(
+                "hop {} is larger than the window {}: samples between frames would be skipped",
+                self.hop, self.nfft
+            ));
+        }
+        if self.nfft > signal_len {
+            return Err(format!(
+                "window {} is longer than the signal ({} samples)",
+                self.nfft, signal_len
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of whole frames that fit in `signal_len` samples.
+    pub fn num_frames(&self, signal_len: usize) -> usize {
+        if signal_len < self.nfft { 0 } else { 1 + (signal_len - self.nfft) / self.hop }
+    }
+}
+
+/// Periodic Hann window of length `n`.
+pub fn hann(n: usize) -> Vec<f32> {
+    (0..n).map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / n as f32).cos()).collect()
+}
+
+/// Time derivative (per sample) of the periodic Hann window of length `n`.
+pub fn hann_derivative(n: usize) -> Vec<f32> {
+    (0..n).map(|i| PI / n as f32 * (2.0 * PI * i as f32 / n as f32).sin()).collect()
+}
+
+/// Hann-windowed FFT of every frame; each frame keeps bins 0..=nfft/2.
+pub fn stft(samples: &[f32], config: StftConfig) -> Vec<Vec<Complex<f32>>> {
+    stft_with_window(samples, config, &hann(config.nfft))
+}
+
+/// STFT with an arbitrary analysis `window` of length `config.nfft`.
+pub fn stft_with_window(samples: &[f32], config: StftConfig, window: &[f32]) -> Vec<Vec<Complex<f32>>> {
+    with_shared(|analyzer| analyzer.stft(samples, config, window))
+}
+
+/// STFT of a signal that arrives in pieces: each `push` hands over every
+/// frame the new samples complete, as magnitudes of bins 0..=nfft/2, with
+/// the framing of `stft_with_window`. Holds less than one window plus the
+/// last piece, however long the signal.
+pub struct StftStream {
+    config: StftConfig,
+    window: Arc<[f32]>,
+    fft: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    pending: Vec<f32>, // samples from the start of the next frame on
+}
+
+impl StftStream {
+    pub fn new(config: StftConfig, window: Window) -> Self {
+        with_shared(|analyzer| analyzer.stream(config, window))
+    }
+
+    /// Stream with an already computed `window` and planned forward `fft`,
+    /// both of length `config.nfft`.
+    pub fn with_plan(config: StftConfig, window: Arc<[f32]>, fft: Arc<dyn Fft<f32>>) -> Self {
+        StftStream {
+            config,
+            window,
+            fft,
+            buffer: vec![Complex { re: 0.0, im: 0.0 }; config.nfft],
+            pending: Vec::new(),
+        }
+    }
+
+    /// Append `samples` and call `on_frame` with each completed frame.
+    pub fn push(&mut self, samples: &[f32], mut on_frame: impl FnMut(&[f32])) {
+        let mut magnitudes = vec![0.0f32; self.config.nfft / 2 + 1];
+        self.pending.extend_from_slice(samples);
+        let mut start = 0;
+        while start + self.config.nfft <= self.pending.len() {
+            window_frame(&self.pending[start..start + self.config.nfft], &self.window, &mut self.buffer);
+            self.fft.process(&mut self.buffer);
+            kernels::magnitudes(&self.buffer, &mut magnitudes);
+            on_frame(&magnitudes);
+            start += self.config.hop;
+        }
+        self.pending.drain(..start);
+    }
+}
+
+/// Resynthesize `len` samples from STFT frames (bins 0..=nfft/2, as from
+/// `stft`) by weighted overlap-add with a Hann synthesis window. Samples no
+/// frame covers with any weight, such as the very first, come out as zero.
+pub fn istft(frames: &[Vec<Complex<f32>>], config: StftConfig, len: usize) -> Vec<f32> {
+    let n = config.nfft;
+    let ifft = FftPlanner::new().plan_fft_inverse(n);
+    let window = hann(n);
+    let mut output = vec![0.0f32; len];
+    let mut weight = vec![0.0f32; len];
+    let mut buffer = vec![Complex { re: 0.0, im: 0.0 }; n];
+
+    for (t, frame) in frames.iter().enumerate() {
+        // rebuild the conjugate-symmetric upper half
+        for k in 0..n {
+            buffer[k] = if k <= n / 2 { frame[k] } else { frame[n - k].conj() };
+        }
+        ifft.process(&mut buffer);
+
+        let start = t * config.hop;
+        for (i, (b, &w)) in buffer.iter().zip(&window).enumerate() {
+            if start + i >= len {
+                break;
+            }
+            output[start + i] += b.re / n as f32 * w;
+            weight[start + i] += w * w;
+        }
+    }
+
+    for (o, &w) in output.iter_mut().zip(&weight) {
+        *o = if w > 1e-6 { *o / w } else { 0.0 };
+    }
+    output
+}
+
+/// `stft` of `samples` with a window of silence added on either side, so
+/// every input sample sits under full window weight. Frame t is centered
+/// `t * hop + nfft / 2 - nfft` samples into the input.
+pub fn padded_stft(samples: &[f32], config: StftConfig) -> Vec<Vec<Complex<f32>>> {
+    let n = config.nfft;
+    let mut padded = vec![0.0f32; n];
+    padded.extend_from_slice(samples);
+    padded.resize(samples.len() + 2 * n, 0.0);
+    stft(&padded, config)
+}
+
+/// The inverse of `padded_stft`: resynthesize `frames` (as from it, or
+/// masked copies of them) and drop the padding, leaving `len` samples.
+pub fn trimmed_istft(frames: &[Vec<Complex<f32>>], config: StftConfig, len: usize) -> Vec<f32> {
+    let n = config.nfft;
+    istft(frames, config, len + 2 * n)[n..n + len].to_vec()
+}
+
+// bins weaker than this are too noisy to estimate a frequency from
+const SST_THRESHOLD: f32 = 1e-6;
+
+/// Synchrosqueezed STFT: each coefficient is moved from its own bin to the
+/// bin of its instantaneous frequency, sharpening ridges of tones, vibrato and
+/// chirps. Frequencies come from the ratio with a derivative-window STFT.
+pub fn synchrosqueeze(samples: &[f32], config: StftConfig) -> Vec<Vec<Complex<f32>>> {
+    let n = config.nfft;
+    let plain = stft(samples, config);
+    let derivative = stft_with_window(samples, config, &hann_derivative(n));
+
+    plain.iter().zip(&derivative).map(|(x, dx)| {
+        let mut squeezed = vec![Complex { re: 0.0, im: 0.0 }; x.len()];
+        for (k, (&c, &dc)) in x.iter().zip(dx).enumerate() {
+            if c.norm() < SST_THRESHOLD {
+                continue;
+            }
+            // instantaneous frequency offset from the bin center, in bins
+            let offset = -(dc / c).im * n as f32 / (2.0 * PI);
+            let target = (k as f32 + offset).round();
+            if target >= 0.0 && (target as usize) < squeezed.len() {
+                // refer phases to the window center so neighbouring bins add up
+                let center = Complex::from_polar(1.0, PI * k as f32);
+                squeezed[target as usize] += c * center;
+            }
+        }
+        squeezed
+    }).collect()
+}
+
+// a window size serves frequencies it holds at least this many periods of
+const MULTI_RES_CYCLES: f32 = 16.0;
+
+/// How `Spectrogram::average` combines frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Averaging {
+    Linear, // mean of magnitudes
+    Db,     // mean of levels in dB, so loud frames weigh less
+}
+
+/// Magnitudes of an STFT, frame by frame.
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    pub config: StftConfig,
+    pub sample_rate: u32,
+    pub frames: Vec<Vec<f32>>, // frames[t][k] = |X_t[k]|
+    pub window: Window,
+}
+
+impl Spectrogram {
+    pub fn compute(samples: &[f32], sample_rate: u32, config: StftConfig) -> Self {
+        Spectrogram::compute_windowed(samples, sample_rate, config, Window::Hann)
+    }
+
+    /// Like `compute`, with another analysis window than Hann.
+    pub fn compute_windowed(samples: &[f32], sample_rate: u32, config: StftConfig, window: Window) -> Self {
+        with_shared(|analyzer| analyzer.spectrogram(samples, sample_rate, config, window))
+    }
+
+    /// Magnitudes of the synchrosqueezed STFT, on the same grid as `compute`.
+    pub fn synchrosqueezed(samples: &[f32], sample_rate: u32, config: StftConfig) -> Self {
+        let frames = synchrosqueeze(samples, config).into_iter()
+            .map(|frame| frame.iter().map(|c| c.norm()).collect())
+            .collect();
+        Spectrogram { config, sample_rate, frames, window: Window::Hann }
+    }
+
+    /// Merge spectrograms taken with several window `sizes` sharing one `hop`.
+    /// Each bin of the largest window's grid is read from the shortest window
+    /// that still holds `MULTI_RES_CYCLES` periods of its frequency, so lows
+    /// get long windows and highs get short ones. Frames are matched by their
+    /// centers and magnitudes are rescaled to the largest window's gain.
+    pub fn multi_resolution(samples: &[f32], sample_rate: u32, sizes: &[usize], hop: usize) -> Self {
+        let mut sizes = sizes.to_vec();
+        sizes.sort_unstable();
+        sizes.dedup();
+        let largest = *sizes.last().expect("at least one window size");
+        let config = StftConfig { nfft: largest, hop };
+
+        let layers: Vec<Spectrogram> = sizes.iter()
+            .map(|&nfft| Spectrogram::compute(samples, sample_rate, StftConfig { nfft, hop }))
+            .collect();
+        let num_frames = config.num_frames(samples.len());
+
+        let frames = (0..num_frames).map(|t| {
+            let center = t * hop + largest / 2;
+            (0..largest / 2 + 1).map(|k| {
+                let freq = k as f32 * sample_rate as f32 / largest as f32;
+                let layer = layers.iter()
+                    .find(|l| l.config.nfft as f32 * freq >= MULTI_RES_CYCLES * sample_rate as f32)
+                    .unwrap_or(&layers[layers.len() - 1]);
+                let n = layer.config.nfft;
+                let frame = ((center - n / 2) as f32 / hop as f32).round() as usize;
+                let frame = frame.min(layer.frames.len() - 1);
+                let bin = (k * n + largest / 2) / largest;
+                layer.frames[frame][bin] * largest as f32 / n as f32
+            }).collect()
+        }).collect();
+
+        Spectrogram { config, sample_rate, frames, window: Window::Hann }
+    }
+
+    pub fn num_bins(&self) -> usize {
+        self.config.nfft / 2 + 1
+    }
+
+    /// Start time of frame `t` in seconds.
+    pub fn frame_time(&self, t: usize) -> f32 {
+        (t * self.config.hop) as f32 / self.sample_rate as f32
+    }
+
+    /// Center frequency of bin `k` in Hz.
+    pub fn bin_frequency(&self, k: usize) -> f32 {
+        k as f32 * self.sample_rate as f32 / self.config.nfft as f32
+    }
+
+    /// Mean spectrum over all frames (Welch-style), which smooths out the
+    /// variance a single transform of a noisy or long signal shows.
+    pub fn average(&self, averaging: Averaging) -> Spectrum {
+        let count = self.frames.len().max(1) as f32;
+        let mut sums = vec![0.0f32; self.num_bins()];
+        let mut levels = vec![0.0f32; self.num_bins()];
+        for frame in &self.frames {
+            let values = match averaging {
+                Averaging::Linear => &frame[..],
+                Averaging::Db => {
+                    amplitude_db(frame, 1e-9, &mut levels);
+                    &levels[..]
+                }
+            };
+            for (sum, &v) in sums.iter_mut().zip(values) {
+                *sum += v;
+            }
+        }
+        let magnitudes = sums.into_iter().map(|sum| match averaging {
+            Averaging::Linear => sum / count,
+            Averaging::Db => 10f32.powf(sum / count / 20.0),
+        }).collect();
+        self.to_spectrum(magnitudes)
+    }
+
+    /// Per-bin maximum over all frames (peak hold), which keeps intermittent
+    /// tones that averaging or a single transform would bury.
+    pub fn max_hold(&self) -> Spectrum {
+        let magnitudes = (0..self.num_bins())
+            .map(|k| self.frames.iter().map(|frame| frame[k]).fold(0.0, f32::max))
+            .collect();
+        self.to_spectrum(magnitudes)
+    }
+
+    fn to_spectrum(&self, magnitudes: Vec<f32>) -> Spectrum {
+        let frequencies = (0..self.num_bins()).map(|k| self.bin_frequency(k)).collect();
+        let window = self.window.coefficients(self.config.nfft);
+        let window_sum = window.iter().sum();
+        let window_power = window.iter().map(|w| w * w).sum();
+        Spectrum { fft_size: self.config.nfft, frequencies, magnitudes, window_sum, window_power, scale: SpectrumScale::Raw }
+    }
+
+    /// Total time covered by the frames, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frame_time(self.frames.len().saturating_sub(1))
+            + self.config.nfft as f32 / self.sample_rate as f32
+    }
+}
@@ -0,0 +1,219 @@
+// Frame features for song structure analysis (chroma, MFCCs) and the
+// self-similarity matrix built from them.
+
+use std::f32::consts::PI;
+
+use crate::auditory::FreqScale;
+use crate::stft::Spectrogram;
+
+// chroma only counts bins in the range where pitches are well resolved
+const CHROMA_RANGE: (f32, f32) = (55.0, 5000.0);
+
+const MEL_BANDS: usize = 40;
+
+/// Energy per pitch class (C = 0 ... B = 11) of every frame, each vector
+/// scaled so its largest class is 1.
+pub fn chroma(spectrogram: &Spectrogram) -> Vec<Vec<f32>> {
+    spectrogram.frames.iter()
+        .map(|frame| chroma_vector(frame, spectrogram.sample_rate, spectrogram.config.nfft))
+        .collect()
+}
+
+/// Chroma of a single magnitude frame of an `nfft`-point transform.
+pub fn chroma_vector(frame: &[f32], sample_rate: u32, nfft: usize) -> Vec<f32> {
+    let mut classes = vec![0.0f32; 12];
+    for (k, &m) in frame.iter().enumerate() {
+        let f = k as f32 * sample_rate as f32 / nfft as f32;
+        if f < CHROMA_RANGE.0 || f > CHROMA_RANGE.1 {
+            continue;
+        }
+        // semitones above C, with A4 = 440 Hz 9 semitones above C4
+        let semitone = (12.0 * (f / 440.0).log2()).round() as i32 + 9;
+        classes[semitone.rem_euclid(12) as usize] += m * m;
+    }
+    let max = classes.iter().copied().fold(0.0, f32::max);
+    if max > 0.0 {
+        classes.iter_mut().for_each(|c| *c /= max);
+    }
+    classes
+}
+
+/// Mel-frequency cepstral coefficients 1..=`count` of every frame (c0,
+/// which only follows loudness, is left out): the DCT-II of the log
+/// energies of 40 triangular mel bands up to Nyquist.
+pub fn mfcc(spectrogram: &Spectrogram, count: usize) -> Vec<Vec<f32>> {
+    spectrogram.frames.iter()
+        .map(|frame| mfcc_vector(frame, spectrogram.sample_rate, spectrogram.config.nfft, count))
+        .collect()
+}
+
+/// MFCCs of a single magnitude frame of an `nfft`-point transform.
+pub fn mfcc_vector(frame: &[f32], sample_rate: u32, nfft: usize, count: usize) -> Vec<f32> {
+    let nyquist = sample_rate as f32 / 2.0;
+    let edges: Vec<f32> = (0..MEL_BANDS + 2)
+        .map(|i| FreqScale::Mel.to_hz(FreqScale::Mel.from_hz(nyquist) * i as f32 / (MEL_BANDS + 1) as f32))
+        .collect();
+
+    let log_energies: Vec<f32> = edges.windows(3).map(|band| {
+        let (lo, center, hi) = (band[0], band[1], band[2]);
+        let energy: f32 = frame.iter().enumerate().map(|(k, &m)| {
+            let f = k as f32 * sample_rate as f32 / nfft as f32;
+            let weight = if f > lo && f <= center {
+                (f - lo) / (center - lo)
+            } else if f > center && f < hi {
+                (hi - f) / (hi - center)
+            } else {
+                0.0
+            };
+            weight * m * m
+        }).sum();
+        (energy + 1e-10).ln()
+    }).collect();
+
+    (1..=count).map(|n| {
+        log_energies.iter().enumerate()
+            .map(|(b, &e)| e * (PI * n as f32 * (b as f32 + 0.5) / MEL_BANDS as f32).cos())
+            .sum()
+    }).collect()
+}
+
+/// Average consecutive groups of `size` feature vectors, which trades time
+/// resolution for a smaller, steadier matrix.
+pub fn pool(features: &[Vec<f32>], size: usize) -> Vec<Vec<f32>> {
+    features.chunks(size.max(1)).map(|group| {
+        let mut mean = vec![0.0f32; group[0].len()];
+        for vector in group {
+            for (m, &v) in mean.iter_mut().zip(vector) {
+                *m += v / group.len() as f32;
+            }
+        }
+        mean
+    }).collect()
+}
+
+/// Cosine similarity of every pair of feature vectors: `matrix[i][j]` is 1
+/// where frames i and j point the same way. Repeated sections show up as
+/// stripes parallel to the diagonal.
+pub fn self_similarity(features: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    let norms: Vec<f32> = features.iter()
+        .map(|v| v.iter().map(|x| x * x).sum::<f32>().sqrt())
+        .collect();
+    features.iter().zip(&norms).map(|(a, &na)| {
+        features.iter().zip(&norms).map(|(b, &nb)| {
+            if na > 0.0 && nb > 0.0 {
+                a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>() / (na * nb)
+            } else {
+                0.0
+            }
+        }).collect()
+    }).collect()
+}
+
+/// Structural novelty along the diagonal of a self-similarity matrix
+/// (Foote 2000): correlation with a Gaussian-tapered checkerboard kernel
+/// `kernel` cells wide, high where the past and future of a cell are each
+/// self-similar but unlike one another, i.e. at section boundaries.
+pub fn structural_novelty(matrix: &[Vec<f32>], kernel: usize) -> Vec<f32> {
+    let half = (kernel / 2).max(1) as isize;
+    let n = matrix.len() as isize;
+    let taper = |x: isize| {
+        let u = (x as f32 + 0.5) / half as f32;
+        (-2.0 * u * u).exp()
+    };
+    let norm: f32 = (-half..half).flat_map(|a| (-half..half).map(move |b| taper(a) * taper(b))).sum();
+
+    (0..n).map(|i| {
+        let mut sum = 0.0;
+        for a in -half..half {
+            for b in -half..half {
+                let (row, col) = (i + a, i + b);
+                if row < 0 || col < 0 || row >= n || col >= n {
+                    continue;
+                }
+                // +1 within the past or the future, -1 across them
+                let sign = if (a < 0) == (b < 0) { 1.0 } else { -1.0 };
+                sum += sign * taper(a) * taper(b) * matrix[row as usize][col as usize];
+            }
+        }
+        (sum / norm).max(0.0)
+    }).collect()
+}
+
+/// Start cell of the `length`-cell excerpt that best represents the whole
+/// piece: the one whose cells are, on average, most similar to all cells
+/// of the self-similarity `matrix`. `None` if the piece is shorter.
+pub fn thumbnail(matrix: &[Vec<f32>], length: usize) -> Option<usize> {
+    let n = matrix.len();
+    if length == 0 || length > n {
+        return None;
+    }
+    let row_means: Vec<f32> = matrix.iter().map(|row| row.iter().sum::<f32>() / n as f32).collect();
+    (0..=n - length)
+        .map(|start| (start, row_means[start..start + length].iter().sum::<f32>()))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(start, _)| start)
+}
+
+// sections whose mean features are at least this similar share a label
+const SAME_SECTION: f32 = 0.9;
+
+/// A stretch between two detected boundaries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub start: f32, // seconds
+    pub end: f32,
+    pub label: usize, // sections with the same label sound alike
+}
+
+impl Section {
+    /// "A", "B", ... in order of first appearance ("S27" and up past Z).
+    pub fn name(&self) -> String {
+        if self.label < 26 {
+            ((b'A' + self.label as u8) as char).to_string()
+        } else {
+            format!("S{}", self.label + 1)
+        }
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norms = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norms > 0.0 { dot / norms } else { 0.0 }
+}
+
+/// Split pooled `features` (each covering `step` seconds) into sections at
+/// the peaks of the structural novelty (checkerboard `kernel` cells wide)
+/// that rise above its mean and are at least half a kernel apart, then
+/// give sections with similar mean features the same label.
+pub fn segment(features: &[Vec<f32>], step: f32, kernel: usize) -> Vec<Section> {
+    let n = features.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let novelty = structural_novelty(&self_similarity(features), kernel);
+    let mean = novelty.iter().sum::<f32>() / n as f32;
+    let reach = (kernel / 2).max(1);
+
+    // the kernel hangs off the matrix near the ends, so no boundaries there
+    let mut bounds = vec![0];
+    for i in reach..n.saturating_sub(reach) {
+        let (lo, hi) = (i.saturating_sub(reach), (i + reach + 1).min(n));
+        let is_peak = novelty[lo..hi].iter().all(|&v| v <= novelty[i]);
+        if is_peak && novelty[i] > mean && i - bounds[bounds.len() - 1] >= reach {
+            bounds.push(i);
+        }
+    }
+    bounds.push(n);
+
+    let mut labelled: Vec<Vec<f32>> = Vec::new(); // mean features of each label's first section
+    bounds.windows(2).map(|pair| {
+        let mean = pool(&features[pair[0]..pair[1]], pair[1] - pair[0]).remove(0);
+        let label = labelled.iter().position(|other| cosine(other, &mean) >= SAME_SECTION)
+            .unwrap_or_else(|| {
+                labelled.push(mean);
+                labelled.len() - 1
+            });
+        Section { start: pair[0] as f32 * step, end: pair[1] as f32 * step, label }
+    }).collect()
+}
@@ -0,0 +1,118 @@
+// Transfer-function estimation between a stimulus and a response.
+
+use rustfft::{FftPlanner, num_complex::Complex};
+
+use crate::stft::{stft, StftConfig};
+
+/// Impulse response that turns `reference` into `recording`, by spectral
+/// division: H = Y X* / (|X|^2 + eps), with eps = `regularization` times
+/// the peak of |X|^2 so bands the stimulus never excited don't blow up.
+/// The result has the recording's length.
+pub fn deconvolve(reference: &[f32], recording: &[f32], regularization: f32) -> Vec<f32> {
+    // long enough that the circular convolution doesn't wrap
+    let n = (reference.len() + recording.len()).next_power_of_two();
+    let mut planner = FftPlanner::<f32>::new();
+    let forward = planner.plan_fft_forward(n);
+    let inverse = planner.plan_fft_inverse(n);
+
+    let spectrum = |signal: &[f32]| {
+        let mut buffer: Vec<Complex<f32>> = signal.iter().map(|&s| Complex { re: s, im: 0.0 }).collect();
+        buffer.resize(n, Complex { re: 0.0, im: 0.0 });
+        forward.process(&mut buffer);
+        buffer
+    };
+    let x = spectrum(reference);
+    let mut y = spectrum(recording);
+
+    let peak = x.iter().map(|c| c.norm_sqr()).fold(0.0f32, f32::max);
+    let eps = regularization * peak + f32::MIN_POSITIVE;
+    for (yk, xk) in y.iter_mut().zip(&x) {
+        *yk = *yk * xk.conj() / (xk.norm_sqr() + eps);
+    }
+
+    inverse.process(&mut y);
+    y.iter().take(recording.len()).map(|c| c.re / n as f32).collect()
+}
+
+// bins where the reference is this far below its strongest bin carry no
+// usable response
+const REFERENCE_FLOOR_DB: f32 = -60.0;
+
+/// Magnitude response of `processed` relative to `reference` as
+/// (frequency in Hz, gain in dB): the ratio of their Welch-averaged power
+/// spectra. Bins the reference barely excites are left out.
+pub fn relative_response(
+    reference: &[f32],
+    processed: &[f32],
+    sample_rate: u32,
+    config: StftConfig,
+) -> Vec<(f32, f32)> {
+    let power = |signal: &[f32]| {
+        let frames = stft(signal, config);
+        let mut sum = vec![0.0f64; config.nfft / 2 + 1];
+        for frame in &frames {
+            for (s, c) in sum.iter_mut().zip(frame) {
+                *s += c.norm_sqr() as f64;
+            }
+        }
+        sum.iter().map(|s| s / frames.len().max(1) as f64).collect::<Vec<f64>>()
+    };
+    let (pxx, pyy) = (power(reference), power(processed));
+
+    let floor = pxx.iter().cloned().fold(0.0, f64::max) * 10f64.powf(REFERENCE_FLOOR_DB as f64 / 10.0);
+    pxx.iter().zip(&pyy).enumerate()
+        .filter(|&(k, (&x, _))| k > 0 && x > floor)
+        .map(|(k, (&x, &y))| {
+            let freq = k as f32 * sample_rate as f32 / config.nfft as f32;
+            (freq, (10.0 * (y.max(f64::MIN_POSITIVE) / x).log10()) as f32)
+        })
+        .collect()
+}
+
+/// Welch-averaged auto and cross spectra of two equally long signals, one
+/// entry per STFT bin (0..=nfft/2).
+#[derive(Debug, Clone)]
+pub struct CrossSpectra {
+    pub config: StftConfig,
+    pub sample_rate: u32,
+    pub sxx: Vec<f64>,
+    pub syy: Vec<f64>,
+    pub sxy: Vec<Complex<f64>>, // conj(X) Y
+}
+
+impl CrossSpectra {
+    pub fn compute(x: &[f32], y: &[f32], sample_rate: u32, config: StftConfig) -> Self {
+        let bins = config.nfft / 2 + 1;
+        let (fx, fy) = (stft(x, config), stft(y, config));
+        let mut sxx = vec![0.0; bins];
+        let mut syy = vec![0.0; bins];
+        let mut sxy = vec![Complex::new(0.0, 0.0); bins];
+        for (a, b) in fx.iter().zip(&fy) {
+            for k in 0..bins {
+                let (xa, yb) = (
+                    Complex::new(a[k].re as f64, a[k].im as f64),
+                    Complex::new(b[k].re as f64, b[k].im as f64),
+                );
+                sxx[k] += xa.norm_sqr();
+                syy[k] += yb.norm_sqr();
+                sxy[k] += xa.conj() * yb;
+            }
+        }
+        CrossSpectra { config, sample_rate, sxx, syy, sxy }
+    }
+
+    pub fn frequency(&self, k: usize) -> f32 {
+        k as f32 * self.sample_rate as f32 / self.config.nfft as f32
+    }
+
+    /// Magnitude-squared coherence |Sxy|^2 / (Sxx Syy) of bin `k`, in [0, 1].
+    pub fn coherence(&self, k: usize) -> f32 {
+        let denominator = self.sxx[k] * self.syy[k];
+        if denominator > 0.0 { (self.sxy[k].norm_sqr() / denominator) as f32 } else { 0.0 }
+    }
+
+    /// H1 transfer estimate Sxy / Sxx of bin `k`, unbiased by noise on y.
+    pub fn h1(&self, k: usize) -> Complex<f64> {
+        if self.sxx[k] > 0.0 { self.sxy[k] / self.sxx[k] } else { Complex::new(0.0, 0.0) }
+    }
+}
@@ -0,0 +1,124 @@
+// Instrument tuning: YIN pitch readings of a running input, each matched
+// to the nearest open string of an instrument (or the nearest semitone)
+// with its deviation in cents.
+
+use crate::pitch::{midi_pitch, track_pitch, PitchConfig};
+
+/// Tuning presets: the open strings, in standard tuning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instrument {
+    Guitar,
+    Bass,
+    Ukulele,
+    Violin,
+    Viola,
+    Cello,
+    /// Any note of the equal-tempered scale.
+    Chromatic,
+}
+
+impl Instrument {
+    /// MIDI note numbers of the open strings, string 1 (the thinnest) first.
+    pub fn strings(self) -> &'static [u8] {
+        match self {
+            Instrument::Guitar => &[64, 59, 55, 50, 45, 40],
+            Instrument::Bass => &[43, 38, 33, 28],
+            Instrument::Ukulele => &[69, 64, 60, 67],
+            Instrument::Violin => &[76, 69, 62, 55],
+            Instrument::Viola => &[69, 62, 55, 48],
+            Instrument::Cello => &[57, 50, 43, 36],
+            Instrument::Chromatic => &[],
+        }
+    }
+
+    // f0 search range covering the strings with a few semitones to spare
+    fn range(self) -> (f32, f32) {
+        let strings = self.strings();
+        match (strings.iter().min(), strings.iter().max()) {
+            (Some(&low), Some(&high)) => (key_frequency(low as f32 - 5.0), key_frequency(high as f32 + 5.0)),
+            _ => (30.0, 4200.0),
+        }
+    }
+}
+
+fn key_frequency(key: f32) -> f32 {
+    440.0 * 2f32.powf((key - 69.0) / 12.0)
+}
+
+/// One tuner reading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub frequency: f32,        // detected f0, Hz
+    pub target: u8,            // MIDI note number of the nearest string or semitone
+    pub string: Option<usize>, // its string number, 1 = thinnest; None for chromatic
+    pub cents: f32,            // deviation from the target; positive is sharp
+    pub clarity: f32,          // of the YIN dip, 0..1
+}
+
+impl Reading {
+    pub fn in_tune(&self, tolerance_cents: f32) -> bool {
+        self.cents.abs() <= tolerance_cents
+    }
+}
+
+/// The nearest target of `instrument` to `frequency`.
+pub fn nearest_target(instrument: Instrument, frequency: f32) -> (u8, Option<usize>, f32) {
+    let pitch = midi_pitch(frequency);
+    let nearest = instrument.strings().iter().enumerate()
+        .min_by(|a, b| (pitch - *a.1 as f32).abs().total_cmp(&(pitch - *b.1 as f32).abs()));
+    match nearest {
+        Some((index, &key)) => (key, Some(index + 1), (pitch - key as f32) * 100.0),
+        None => {
+            let key = pitch.round().clamp(0.0, 127.0);
+            (key as u8, None, (pitch - key) * 100.0)
+        }
+    }
+}
+
+/// Takes mono input in blocks of any size and reads the pitch of its most
+/// recent frame every `hop` samples.
+pub struct Tuner {
+    instrument: Instrument,
+    sample_rate: u32,
+    config: PitchConfig,
+    buffer: Vec<f32>, // ends with the latest frame_len samples
+    frame_len: usize,
+    pending: usize, // samples since the last reading
+}
+
+impl Tuner {
+    /// A tuner reading `readings_per_sec` times a second.
+    pub fn new(instrument: Instrument, sample_rate: u32, readings_per_sec: f32) -> Self {
+        let (min_freq, max_freq) = instrument.range();
+        let max_freq = max_freq.min(sample_rate as f32 / 4.0);
+        let hop = ((sample_rate as f32 / readings_per_sec.max(0.1)) as usize).max(1);
+        let config = PitchConfig { min_freq, max_freq, hop, ..PitchConfig::new(sample_rate) };
+        let frame_len = config.frame_len(sample_rate);
+        Tuner { instrument, sample_rate, config, buffer: Vec::with_capacity(2 * frame_len), frame_len, pending: 0 }
+    }
+
+    /// Feed samples; `f` gets a reading, or `None` for an unvoiced frame,
+    /// each time a hop's worth has arrived.
+    pub fn push<F: FnMut(Option<Reading>)>(&mut self, samples: &[f32], mut f: F) {
+        for &sample in samples {
+            // samples older than a frame are dropped a frame's worth at a time
+            if self.buffer.len() == 2 * self.frame_len {
+                self.buffer.drain(..self.frame_len);
+            }
+            self.buffer.push(sample);
+            self.pending += 1;
+            if self.pending >= self.config.hop && self.buffer.len() >= self.frame_len {
+                self.pending = 0;
+                f(self.read());
+            }
+        }
+    }
+
+    fn read(&self) -> Option<Reading> {
+        let frame = &self.buffer[self.buffer.len() - self.frame_len..];
+        let point = track_pitch(frame, self.sample_rate, self.config).pop()?;
+        let frequency = point.frequency?;
+        let (target, string, cents) = nearest_target(self.instrument, frequency);
+        Some(Reading { frequency, target, string, cents, clarity: point.clarity })
+    }
+}
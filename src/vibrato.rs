@@ -0,0 +1,155 @@
+// Vibrato (periodic pitch modulation) measured cycle by cycle from a note's
+// pitch contour: the slow pitch trend is removed, the peaks and troughs of
+// what is left are found, and each half cycle between a peak and a trough
+// gives a rate and an extent.
+
+/// Vibrato rates accepted, in Hz; singers and string players mostly stay
+/// within 4-8 Hz.
+pub const VIBRATO_LOW: f32 = 3.0;
+pub const VIBRATO_HIGH: f32 = 10.0;
+
+// swings smaller than this (semitones, peak to trough) are pitch jitter,
+// not vibrato
+const MIN_SWING: f32 = 0.1;
+
+// a note needs this many half cycles in a row to count as having vibrato
+const MIN_HALF_CYCLES: usize = 3;
+
+/// One half cycle of vibrato, from a peak to a trough or back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VibratoPoint {
+    pub time: f32,   // seconds, halfway between the two turning points
+    pub rate: f32,   // Hz, from the half cycle's length
+    pub extent: f32, // cents, half the peak-to-trough swing (the +/- depth)
+}
+
+/// Vibrato of one note: its half cycles and their statistics.
+#[derive(Debug, Clone)]
+pub struct Vibrato {
+    pub points: Vec<VibratoPoint>,
+    pub rate: f32,       // mean, Hz
+    pub rate_sd: f32,    // standard deviation of the rate, Hz; low is steady
+    pub extent: f32,     // mean, cents
+    pub extent_max: f32, // widest half cycle, cents
+}
+
+/// Measure the vibrato in `contour`, a note's (time, fractional MIDI pitch)
+/// frames at a steady frame rate. `None` unless at least
+/// `MIN_HALF_CYCLES` consecutive half cycles fall within `VIBRATO_LOW` to
+/// `VIBRATO_HIGH`.
+pub fn measure_vibrato(contour: &[(f32, f32)]) -> Option<Vibrato> {
+    let frame = match contour {
+        [a, b, ..] => b.0 - a.0,
+        _ => return None,
+    };
+    let deviation = detrend(contour, (1.0 / (VIBRATO_LOW * frame)).round() as usize);
+    let turns = turning_points(&deviation, frame);
+
+    // the longest run of half cycles at vibrato rates
+    let mut best: Vec<VibratoPoint> = Vec::new();
+    let mut run = Vec::new();
+    for pair in turns.windows(2) {
+        let ((t0, d0), (t1, d1)) = (pair[0], pair[1]);
+        let rate = 1.0 / (2.0 * (t1 - t0));
+        if (VIBRATO_LOW..=VIBRATO_HIGH).contains(&rate) {
+            let time = contour[0].0 + (t0 + t1) / 2.0;
+            run.push(VibratoPoint { time, rate, extent: (d1 - d0).abs() / 2.0 * 100.0 });
+        } else if run.len() > best.len() {
+            best = std::mem::take(&mut run);
+        } else {
+            run.clear();
+        }
+    }
+    if run.len() > best.len() {
+        best = run;
+    }
+    if best.len() < MIN_HALF_CYCLES {
+        return None;
+    }
+
+    let count = best.len() as f32;
+    let rate = best.iter().map(|p| p.rate).sum::<f32>() / count;
+    let variance = best.iter().map(|p| (p.rate - rate).powi(2)).sum::<f32>() / count;
+    Some(Vibrato {
+        rate,
+        rate_sd: variance.sqrt(),
+        extent: best.iter().map(|p| p.extent).sum::<f32>() / count,
+        extent_max: best.iter().map(|p| p.extent).fold(0.0, f32::max),
+        points: best,
+    })
+}
+
+// pitch minus its trend: a centered moving average over `window` frames
+// taken twice (a triangular window, whose low sidelobes leave less of the
+// vibrato in the trend), which keeps oscillations faster than 1 / window
+fn detrend(contour: &[(f32, f32)], window: usize) -> Vec<f32> {
+    let pitch: Vec<f32> = contour.iter().map(|c| c.1).collect();
+    let trend = moving_average(&moving_average(&pitch, window), window);
+    pitch.iter().zip(trend).map(|(p, t)| p - t).collect()
+}
+
+// centered moving average over `window` values, shrunk at the ends
+fn moving_average(values: &[f32], window: usize) -> Vec<f32> {
+    let half = window.max(1) / 2;
+    let mut prefix = vec![0f64; values.len() + 1];
+    for (i, &value) in values.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + value as f64;
+    }
+    (0..values.len())
+        .map(|i| {
+            let (lo, hi) = (i.saturating_sub(half), (i + half + 1).min(values.len()));
+            ((prefix[hi] - prefix[lo]) / (hi - lo) as f64) as f32
+        })
+        .collect()
+}
+
+// alternating peaks and troughs of `values` as (seconds from the first
+// frame, value), each at least MIN_SWING from the one before and refined by
+// a parabola through its neighbours
+fn turning_points(values: &[f32], frame: f32) -> Vec<(f32, f32)> {
+    let refine = |i: usize| {
+        if i == 0 || i + 1 == values.len() {
+            return (i as f32 * frame, values[i]);
+        }
+        let (a, b, c) = (values[i - 1], values[i], values[i + 1]);
+        let curvature = a - 2.0 * b + c;
+        let offset = if curvature.abs() > 1e-9 { (0.5 * (a - c) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+        ((i as f32 + offset) * frame, b - 0.25 * (a - c) * offset)
+    };
+
+    let mut turns = Vec::new();
+    // Some(true) while rising toward a peak; high and low are the extremes
+    // since the last turn
+    let mut rising = None;
+    let (mut high, mut low) = (0, 0);
+    for (i, &value) in values.iter().enumerate().skip(1) {
+        if value > values[high] {
+            high = i;
+        }
+        if value < values[low] {
+            low = i;
+        }
+        match rising {
+            Some(true) if values[high] - value >= MIN_SWING => {
+                turns.push(refine(high));
+                (rising, low) = (Some(false), i);
+            }
+            Some(false) if value - values[low] >= MIN_SWING => {
+                turns.push(refine(low));
+                (rising, high) = (Some(true), i);
+            }
+            None if values[high] - values[low] >= MIN_SWING => {
+                // the first swing sets the direction; its start only counts
+                // as a turn if it isn't the first frame
+                let up = low < high;
+                let start = if up { low } else { high };
+                if start > 0 {
+                    turns.push(refine(start));
+                }
+                rising = Some(up);
+            }
+            _ => {}
+        }
+    }
+    turns
+}
@@ -0,0 +1,718 @@
+use std::borrow::Cow;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+use tracing::instrument;
+
+use crate::sample::{self, Samples};
+
+// ---- core structs ----
+
+/// A parsed RIFF/WAVE file. Chunks that weren't present are `None`.
+#[derive(Debug)]
+pub struct WavFile {
+    pub header: Header,
+    pub fmt:   Option<FmtChunk>,
+    pub list:  Option<ListChunk>,
+    pub smpl:  Option<SmplChunk>,
+    pub cue:   Option<CueChunk>,
+    pub labels: Vec<CueLabel>, // "labl" entries from a LIST/adtl chunk
+    pub data:  Option<DataChunk>,
+}
+
+impl WavFile {
+    /// Parse a whole WAV stream, data chunk included, from any reader.
+    #[instrument(name = "parse", level = "debug", skip_all)]
+    pub fn parse<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Self::parse_chunks(reader, None)
+    }
+
+    /// Parse a WAV file held entirely in memory.
+    pub fn parse_bytes(bytes: &[u8]) -> io::Result<Self> {
+        Self::parse(&mut Cursor::new(bytes))
+    }
+
+    // walk the chunk list. with `skip_data` set, the data chunk body is
+    // skipped over using it and only its position/size are recorded.
+    fn parse_chunks<R: Read>(
+        reader: &mut R,
+        skip_data: Option<fn(&mut R, u32) -> io::Result<()>>,
+    ) -> io::Result<Self> {
+        let mut wav = WavFile::from_header(Header::read(reader)?)?;
+
+        // byte position in the stream, tracked by hand so plain readers work
+        let mut pos: u64 = 12;
+
+        while let Some(hdr) = read_chunk_header(reader)? {
+            let offset = pos + 8;
+            pos = offset + hdr.size as u64 + (hdr.size % 2) as u64;
+
+            if let (Some(skip), b"data") = (skip_data, &hdr.id) {
+                skip(reader, hdr.size)?;
+                wav.data = Some(DataChunk::deferred(offset, &hdr));
+                continue;
+            }
+
+            let body = read_chunk_body(reader, hdr.size)?;
+            wav.add_chunk(&hdr, body, offset);
+        }
+
+        Ok(wav)
+    }
+
+    // a file with no chunks yet, if the header is RIFF/WAVE
+    pub(crate) fn from_header(header: Header) -> io::Result<Self> {
+        if !(&header.chunk_id == b"RIFF" && &header.format == b"WAVE") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not RIFF/WAVE",
+            ));
+        }
+
+        Ok(WavFile {
+            header,
+            fmt: None,
+            list: None,
+            smpl: None,
+            cue: None,
+            labels: Vec::new(),
+            data: None,
+        })
+    }
+
+    // file a chunk read at stream position `offset`. the body is moved in,
+    // so a data chunk keeps the caller's buffer
+    pub(crate) fn add_chunk(&mut self, hdr: &ChunkHeader, body: Vec<u8>, offset: u64) {
+        match &hdr.id {
+            b"fmt " => { self.fmt = Some(FmtChunk::parse(&body, hdr)); }
+            b"LIST" if body.starts_with(b"adtl") => { self.labels = parse_labels(&body); }
+            b"LIST" => { self.list = Some(ListChunk::parse(&body, hdr)); }
+            b"cue " => { self.cue = Some(CueChunk::parse(&body, hdr)); }
+            b"smpl" => { self.smpl = SmplChunk::parse(&body, hdr); }
+            b"data" => { self.data = Some(DataChunk::parse(body, offset, hdr)); }
+            _ => { /* ignore unknown chunks */ }
+        }
+    }
+
+    /// Number of sample frames (one sample per channel) in the data chunk.
+    pub fn num_frames(&self) -> Option<u64> {
+        let (fmt, data) = (self.fmt.as_ref()?, self.data.as_ref()?);
+        if fmt.block_align == 0 {
+            return None;
+        }
+        Some(data.chunk_size as u64 / fmt.block_align as u64)
+    }
+
+    /// Playing time in seconds.
+    pub fn duration(&self) -> Option<f64> {
+        let fmt = self.fmt.as_ref()?;
+        if fmt.sample_rate == 0 {
+            return None;
+        }
+        Some(self.num_frames()? as f64 / fmt.sample_rate as f64)
+    }
+
+    /// Bits per second actually stored: the data chunk's size in bits over
+    /// `duration()`. `None` for an empty or unplayable data chunk.
+    pub fn effective_bitrate(&self) -> Option<u64> {
+        let bits = self.data.as_ref()?.chunk_size as f64 * 8.0;
+        let duration = self.duration()?;
+        (duration > 0.0).then(|| (bits / duration).round() as u64)
+    }
+
+    /// Nominal bits per second, from the fmt chunk's sample rate and frame
+    /// size (block_align) rather than its separately stored byte_rate field.
+    pub fn nominal_bitrate(&self) -> Option<u64> {
+        let fmt = self.fmt.as_ref()?;
+        Some(fmt.sample_rate as u64 * fmt.block_align as u64 * 8)
+    }
+
+    /// Decode the data chunk into the concrete sample type named by the fmt
+    /// chunk (8/16/24/32-bit int, 32/64-bit float).
+    #[instrument(name = "decode", level = "debug", skip_all)]
+    pub fn decode_samples(&self) -> io::Result<Samples<'_>> {
+        let fmt = self.fmt.as_ref().ok_or_else(|| invalid("no fmt chunk"))?;
+        let bytes = self.data.as_ref().and_then(|d| d.bytes()).ok_or_else(missing_data)?;
+
+        fmt.decode(bytes)
+    }
+
+    /// Decode the data chunk to f32 in [-1.0, 1.0), scaling by the full-scale
+    /// value of the file's sample format.
+    pub fn to_normalized_samples(&self) -> io::Result<Vec<f32>> {
+        Ok(self.decode_samples()?.to_f32())
+    }
+
+    /// Cue points as (frame position, label), sorted by position. The label
+    /// comes from the matching adtl "labl" entry, if there is one.
+    pub fn markers(&self) -> Vec<(u32, Option<&str>)> {
+        let Some(cue) = &self.cue else {
+            return Vec::new();
+        };
+
+        let mut markers: Vec<_> = cue.points.iter().map(|point| {
+            let label = self.labels.iter()
+                .find(|l| l.cue_id == point.id)
+                .map(|l| l.text.as_str());
+            (point.sample_offset, label)
+        }).collect();
+        markers.sort_by_key(|&(frame, _)| frame);
+        markers
+    }
+
+    /// Walk the sample frames as (timestamp in seconds, one normalized
+    /// sample per channel), decoding a block at a time into one reused
+    /// buffer. Fails if the fmt or data chunk is missing or the data hasn't
+    /// been loaded.
+    pub fn iter_frames(&self) -> io::Result<Frames<'_>> {
+        let fmt = self.fmt.as_ref().ok_or_else(|| invalid("no fmt chunk"))?;
+        let bytes = self.data.as_ref().ok_or_else(missing_data)?
+            .bytes()
+            .ok_or_else(|| invalid("data chunk not loaded"))?;
+        if fmt.block_align == 0 {
+            return Err(invalid("fmt chunk has a zero block_align"));
+        }
+        fmt.decode(&[])?; // reject unsupported formats up front
+
+        Ok(Frames { fmt, bytes, buffer: Vec::new(), first: 0, index: 0 })
+    }
+}
+
+// frames decoded per refill of the `Frames` buffer
+const FRAMES_PER_BLOCK: usize = 4096;
+
+/// Frame cursor returned by `WavFile::iter_frames`. Not an `Iterator`: each
+/// frame borrows the cursor's buffer, so step through with `next_frame`.
+pub struct Frames<'a> {
+    fmt:    &'a FmtChunk,
+    bytes:  &'a [u8],   // whole data chunk payload
+    buffer: Vec<f32>,   // normalized samples of the block starting at `first`
+    first:  usize,      // frame index of buffer[0]
+    index:  usize,      // next frame to yield
+}
+
+impl Frames<'_> {
+    /// The next frame, or `None` after the last whole frame.
+    pub fn next_frame(&mut self) -> Option<(f64, &[f32])> {
+        let block = self.fmt.block_align as usize;
+        let channels = self.fmt.num_channels.max(1) as usize;
+
+        if self.index >= self.first + self.buffer.len() / channels {
+            let from = self.index * block;
+            let to = (from + FRAMES_PER_BLOCK * block).min(self.bytes.len() / block * block);
+            if from >= to {
+                return None;
+            }
+            self.buffer.clear();
+            self.fmt.decode(&self.bytes[from..to]).ok()?.extend_f32(&mut self.buffer);
+            self.first = self.index;
+        }
+
+        let start = (self.index - self.first) * channels;
+        let time = self.index as f64 / self.fmt.sample_rate.max(1) as f64;
+        self.index += 1;
+        Some((time, &self.buffer[start..start + channels]))
+    }
+
+    /// Whole frames not yet yielded.
+    pub fn remaining(&self) -> usize {
+        (self.bytes.len() / self.fmt.block_align as usize).saturating_sub(self.index)
+    }
+}
+
+#[derive(Debug)]
+pub struct Header {
+    pub chunk_id: [u8;4], // "RIFF"
+    pub chunk_size: u32,  // file bytes after this field, minus 8 overall header bytes
+    pub format:   [u8;4], // "WAVE"
+}
+
+impl Header {
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut buf = [0u8; 12];
+        reader.read_exact(&mut buf)?;
+        Ok(Header::from_bytes(&buf))
+    }
+
+    pub(crate) fn from_bytes(buf: &[u8; 12]) -> Self {
+        Header {
+            chunk_id: buf[0..4].try_into().unwrap(),
+            chunk_size: u32::from_le_bytes(buf[4..8].try_into().unwrap()),
+            format: buf[8..12].try_into().unwrap(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FmtChunk {
+    pub chunk_id: [u8;4],    // "fmt "
+    pub chunk_size: u32,     // usually 16 for PCM
+    pub audio_format: u16,   // PCM = 1
+    pub num_channels: u16,   // 1=mono,2=stereo,...
+    pub sample_rate: u32,    // 44100, 48000, ...
+    pub byte_rate: u32,      // sample_rate * block_align
+    pub block_align: u16,    // num_channels * bits_per_sample/8
+    pub bits_per_sample: u16, // 8, 16, 24, ...
+    pub sub_format: Option<u16>, // WAVE_FORMAT_EXTENSIBLE only: the real format code
+}
+
+impl FmtChunk {
+    fn parse(body: &[u8], hdr: &ChunkHeader) -> Self {
+        FmtChunk {
+            chunk_id: hdr.id,
+            chunk_size: hdr.size,
+            audio_format: u16::from_le_bytes(body[0..2].try_into().unwrap()),
+            num_channels: u16::from_le_bytes(body[2..4].try_into().unwrap()),
+            sample_rate: u32::from_le_bytes(body[4..8].try_into().unwrap()),
+            byte_rate:   u32::from_le_bytes(body[8..12].try_into().unwrap()),
+            block_align: u16::from_le_bytes(body[12..14].try_into().unwrap()),
+            bits_per_sample: u16::from_le_bytes(body[14..16].try_into().unwrap()),
+            // extensible layout: cbSize, valid bits, channel mask, then the
+            // subformat GUID whose first two bytes are the format code
+            sub_format: body.get(24..26).map(|b| u16::from_le_bytes([b[0], b[1]])),
+        }
+    }
+
+    /// Decode raw payload bytes laid out as this chunk describes. 8-bit and
+    /// (on little-endian hosts) 16-bit PCM are viewed in place, not copied.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> io::Result<Samples<'a>> {
+        let samples = match (self.format_code(), self.bits_per_sample) {
+            (WAVE_FORMAT_PCM, 8) => Samples::U8(Cow::Borrowed(bytes)),
+            (WAVE_FORMAT_PCM, 16) => Samples::I16(pcm16_samples(bytes)),
+            (WAVE_FORMAT_PCM, 24) => Samples::I24(sample::decode(bytes)),
+            (WAVE_FORMAT_PCM, 32) => Samples::I32(sample::decode(bytes)),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => Samples::F32(sample::decode(bytes)),
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => Samples::F64(sample::decode(bytes)),
+            (code, bits) => {
+                return Err(invalid(&format!(
+                    "unsupported sample format {} with {} bits", code, bits
+                )));
+            }
+        };
+
+        Ok(samples)
+    }
+
+    /// Bytes per sample of one channel.
+    pub fn sample_bytes(&self) -> usize {
+        (self.block_align / self.num_channels.max(1)).max(1) as usize
+    }
+
+    /// The effective format code, looking through WAVE_FORMAT_EXTENSIBLE.
+    pub fn format_code(&self) -> u16 {
+        match (self.audio_format, self.sub_format) {
+            (WAVE_FORMAT_EXTENSIBLE, Some(code)) => code,
+            (code, _) => code,
+        }
+    }
+}
+
+pub const WAVE_FORMAT_PCM: u16 = 1;
+pub const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+pub const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Debug)]
+pub struct ListDataChunk {
+    pub info_id:   [u8;4],
+    pub info_size: u32,
+    pub info:      String,
+}
+
+#[derive(Debug)]
+pub struct ListChunk {
+    pub chunk_id:     [u8;4],   // "LIST"
+    pub chunk_size:   u32,
+    pub list_type_id: [u8;4],   // "INFO"
+    pub data:         Vec<ListDataChunk>,
+}
+
+impl ListChunk {
+    fn parse(body: &[u8], hdr: &ChunkHeader) -> Self {
+        // first 4 bytes of body = list_type_id
+        let list_type_id: [u8;4] = body[0..4].try_into().unwrap();
+        let mut data_chunks = Vec::new();
+        let mut offset = 4;
+
+        // walk subchunks inside LIST
+        while offset + 8 <= body.len() {
+            let info_id: [u8;4] = body[offset..offset+4].try_into().unwrap();
+            let info_size = u32::from_le_bytes(body[offset+4..offset+8].try_into().unwrap());
+
+            let start = offset + 8;
+            let end   = start + info_size as usize;
+            if end > body.len() { break; }
+
+            // interpret text payload
+            let raw = &body[start..end];
+            if let Ok(s) = std::str::from_utf8(raw) {
+                let cleaned = s.trim_end_matches('\0').to_string();
+                data_chunks.push(ListDataChunk {
+                    info_id,
+                    info_size,
+                    info: cleaned,
+                });
+            }
+
+            // advance offset, respecting word alignment
+            let pad = (info_size as usize) % 2;
+            offset = end + pad;
+        }
+
+        ListChunk {
+            chunk_id: hdr.id,
+            chunk_size: hdr.size,
+            list_type_id,
+            data: data_chunks,
+        }
+    }
+}
+
+/// Sampler metadata: MIDI root note and loop points.
+#[derive(Debug)]
+pub struct SmplChunk {
+    pub chunk_id:        [u8;4], // "smpl"
+    pub chunk_size:      u32,
+    pub manufacturer:    u32,
+    pub product:         u32,
+    pub sample_period:   u32,    // nanoseconds per sample
+    pub midi_unity_note: u32,    // note played back at the original pitch
+    pub midi_pitch_fraction: u32,
+    pub smpte_format:    u32,
+    pub smpte_offset:    u32,
+    pub loops:           Vec<SampleLoop>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SampleLoop {
+    pub cue_point_id: u32,
+    pub loop_type:    u32, // 0 forward, 1 ping-pong, 2 reverse
+    pub start:        u32, // first frame of the loop
+    pub end:          u32, // last frame of the loop (inclusive)
+    pub fraction:     u32,
+    pub play_count:   u32, // 0 = loop forever
+}
+
+impl SmplChunk {
+    // None if the body is too short for its fixed fields
+    fn parse(body: &[u8], hdr: &ChunkHeader) -> Option<Self> {
+        let word = |i: usize| body.get(i * 4..i * 4 + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+
+        let num_loops = word(7)? as usize;
+        // word 8 is the sampler-specific data size; loops follow at byte 36
+        let loops = (0..num_loops).map_while(|n| {
+            let base = 9 + n * 6;
+            Some(SampleLoop {
+                cue_point_id: word(base)?,
+                loop_type:    word(base + 1)?,
+                start:        word(base + 2)?,
+                end:          word(base + 3)?,
+                fraction:     word(base + 4)?,
+                play_count:   word(base + 5)?,
+            })
+        }).collect();
+
+        Some(SmplChunk {
+            chunk_id: hdr.id,
+            chunk_size: hdr.size,
+            manufacturer: word(0)?,
+            product: word(1)?,
+            sample_period: word(2)?,
+            midi_unity_note: word(3)?,
+            midi_pitch_fraction: word(4)?,
+            smpte_format: word(5)?,
+            smpte_offset: word(6)?,
+            loops,
+        })
+    }
+}
+
+/// Marker positions; labels for them live in a LIST/adtl chunk.
+#[derive(Debug)]
+pub struct CueChunk {
+    pub chunk_id:   [u8;4], // "cue "
+    pub chunk_size: u32,
+    pub points:     Vec<CuePoint>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CuePoint {
+    pub id:            u32,
+    pub position:      u32,    // play-order position
+    pub data_chunk_id: [u8;4], // "data" for plain files
+    pub chunk_start:   u32,
+    pub block_start:   u32,
+    pub sample_offset: u32,    // frame the marker sits on
+}
+
+impl CueChunk {
+    fn parse(body: &[u8], hdr: &ChunkHeader) -> Self {
+        let count = body.get(0..4)
+            .map_or(0, |b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+
+        // 24-byte records after the count; a truncated tail is ignored
+        let points = body.get(4..).unwrap_or(&[])
+            .chunks_exact(24)
+            .take(count)
+            .map(|rec| {
+                let word = |i: usize| u32::from_le_bytes(rec[i * 4..i * 4 + 4].try_into().unwrap());
+                CuePoint {
+                    id: word(0),
+                    position: word(1),
+                    data_chunk_id: rec[8..12].try_into().unwrap(),
+                    chunk_start: word(3),
+                    block_start: word(4),
+                    sample_offset: word(5),
+                }
+            })
+            .collect();
+
+        CueChunk { chunk_id: hdr.id, chunk_size: hdr.size, points }
+    }
+}
+
+/// A "labl" entry naming a cue point.
+#[derive(Debug)]
+pub struct CueLabel {
+    pub cue_id: u32,
+    pub text:   String,
+}
+
+// walk a LIST/adtl body for labl entries (note/ltxt entries are skipped)
+fn parse_labels(body: &[u8]) -> Vec<CueLabel> {
+    let mut labels = Vec::new();
+    let mut offset = 4;
+
+    while offset + 8 <= body.len() {
+        let id = &body[offset..offset+4];
+        let size = u32::from_le_bytes(body[offset+4..offset+8].try_into().unwrap()) as usize;
+        let start = offset + 8;
+        let end = start + size;
+        if end > body.len() { break; }
+
+        if id == b"labl" && size >= 4 {
+            let cue_id = u32::from_le_bytes(body[start..start+4].try_into().unwrap());
+            let text = String::from_utf8_lossy(&body[start+4..end])
+                .trim_end_matches('\0')
+                .to_string();
+            labels.push(CueLabel { cue_id, text });
+        }
+
+        offset = end + size % 2;
+    }
+
+    labels
+}
+
+#[derive(Debug)]
+pub struct DataChunk {
+    pub chunk_id:   [u8;4],      // "data"
+    pub chunk_size: u32,         // number of bytes in PCM payload
+    pub offset:     u64,         // stream position of the first payload byte
+    bytes:      Option<Vec<u8>>, // raw little-endian payload; None until loaded
+}
+
+impl DataChunk {
+    fn parse(body: Vec<u8>, offset: u64, hdr: &ChunkHeader) -> Self {
+        // keep the body as-is; FmtChunk::decode interprets it on demand
+        DataChunk {
+            chunk_id: hdr.id,
+            chunk_size: hdr.size,
+            offset,
+            bytes: Some(body),
+        }
+    }
+
+    // placeholder for a chunk whose payload hasn't been read yet
+    fn deferred(offset: u64, hdr: &ChunkHeader) -> Self {
+        DataChunk {
+            chunk_id: hdr.id,
+            chunk_size: hdr.size,
+            offset,
+            bytes: None,
+        }
+    }
+
+    /// Sample count (all channels) according to the chunk header, for data
+    /// laid out as `fmt` describes. Available without loading.
+    pub fn num_samples(&self, fmt: &FmtChunk) -> usize {
+        self.chunk_size as usize / fmt.sample_bytes()
+    }
+
+    /// The raw payload bytes, or `None` if the chunk was deferred.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.bytes.as_deref()
+    }
+}
+
+// reinterpret little-endian PCM16 bytes as samples. on little-endian hosts
+// with a 2-byte aligned buffer this borrows the bytes directly (no copy);
+// otherwise the samples are decoded into a fresh Vec.
+fn pcm16_samples(bytes: &[u8]) -> Cow<'_, [i16]> {
+    let even = &bytes[..bytes.len() & !1];
+
+    if cfg!(target_endian = "little") {
+        if let Ok(view) = bytemuck::try_cast_slice::<u8, i16>(even) {
+            return Cow::Borrowed(view);
+        }
+    }
+
+    Cow::Owned(
+        even.chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect(),
+    )
+}
+
+/// A parsed header plus the open reader. The data chunk is only read when
+/// `samples()` or `read_range()` asks for it, so metadata queries stay cheap
+/// no matter how large the file is.
+pub struct LazyWavFile<R> {
+    pub wav: WavFile,
+    reader:  R,
+}
+
+impl<R: Read + Seek> LazyWavFile<R> {
+    pub fn open(mut reader: R) -> io::Result<Self> {
+        let wav = WavFile::parse_chunks(&mut reader, Some(skip_chunk_body::<R>))?;
+        Ok(LazyWavFile { wav, reader })
+    }
+
+    /// Load the whole data chunk (once) and decode it, in the file's own
+    /// sample type.
+    pub fn samples(&mut self) -> io::Result<Samples<'_>> {
+        let data = self.wav.data.as_mut().ok_or_else(missing_data)?;
+
+        if data.bytes.is_none() {
+            self.reader.seek(SeekFrom::Start(data.offset))?;
+            let mut body = vec![0u8; data.chunk_size as usize];
+            self.reader.read_exact(&mut body)?;
+            data.bytes = Some(body);
+        }
+
+        self.wav.decode_samples()
+    }
+
+    /// Decode `count` whole frames starting at frame `start`, clamped to the
+    /// end of the chunk. Only that byte range is read from the source.
+    pub fn read_range(&mut self, start: u64, count: u64) -> io::Result<Samples<'static>> {
+        let bytes = self.read_frames(start, count)?;
+        let fmt = self.wav.fmt.as_ref().ok_or_else(|| invalid("no fmt chunk"))?;
+        Ok(fmt.decode(&bytes)?.into_owned())
+    }
+
+    /// Decode the data chunk `frames` frames at a time, as interleaved
+    /// samples normalized like `to_normalized_samples`. Only one block is in
+    /// memory at once, so this works for files of any length.
+    pub fn blocks(&mut self, frames: u64) -> Blocks<'_, R> {
+        Blocks { lazy: self, frames: frames.max(1), next: 0 }
+    }
+
+    /// Read the raw bytes of `count` whole frames starting at frame `start`,
+    /// clamped to the end of the chunk. Works for any sample format, since
+    /// the range is measured in block_align units.
+    pub fn read_frames(&mut self, start: u64, count: u64) -> io::Result<Vec<u8>> {
+        let fmt = self.wav.fmt.as_ref().ok_or_else(|| invalid("no fmt chunk"))?;
+        let data = self.wav.data.as_ref().ok_or_else(missing_data)?;
+        let block = fmt.block_align.max(1) as u64;
+
+        let total = data.chunk_size as u64 / block;
+        let start = start.min(total);
+        let end   = start.saturating_add(count).min(total);
+        let (from, to) = ((start * block) as usize, (end * block) as usize);
+
+        if let Some(bytes) = &data.bytes {
+            return Ok(bytes[from..to].to_vec());
+        }
+
+        self.reader.seek(SeekFrom::Start(data.offset + from as u64))?;
+        let mut buf = vec![0u8; to - from];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Iterator returned by `LazyWavFile::blocks`.
+pub struct Blocks<'a, R> {
+    lazy: &'a mut LazyWavFile<R>,
+    frames: u64, // frames per block
+    next: u64,   // first frame of the next block
+}
+
+impl<R: Read + Seek> Iterator for Blocks<'_, R> {
+    type Item = io::Result<Vec<f32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.lazy.read_frames(self.next, self.frames).and_then(|bytes| {
+            let fmt = self.lazy.wav.fmt.as_ref().ok_or_else(|| invalid("no fmt chunk"))?;
+            Ok(fmt.decode(&bytes)?.to_f32())
+        });
+        match result {
+            Ok(block) if block.is_empty() => None,
+            Ok(block) => {
+                self.next += self.frames;
+                Some(Ok(block))
+            }
+            Err(e) => {
+                self.next = u64::MAX; // stop after reporting the error
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn missing_data() -> io::Error {
+    invalid("no data chunk")
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// tiny struct for generic chunk header
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ChunkHeader {
+    pub(crate) id:   [u8;4],
+    pub(crate) size: u32,
+}
+
+impl ChunkHeader {
+    pub(crate) fn from_bytes(buf: &[u8; 8]) -> Self {
+        ChunkHeader {
+            id:   buf[0..4].try_into().unwrap(),
+            size: u32::from_le_bytes(buf[4..8].try_into().unwrap()),
+        }
+    }
+}
+
+// read 8-byte chunk header; return None on EOF
+fn read_chunk_header<R: Read>(reader: &mut R) -> io::Result<Option<ChunkHeader>> {
+    let mut buf = [0u8; 8];
+    match reader.read_exact(&mut buf) {
+        Ok(_) => Ok(Some(ChunkHeader::from_bytes(&buf))),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// read exactly `size` bytes of body, plus eat the 1-byte pad if size is odd
+fn read_chunk_body<R: Read>(reader: &mut R, size: u32) -> io::Result<Vec<u8>> {
+    let mut body = vec![0u8; size as usize];
+    reader.read_exact(&mut body)?;
+    if (size % 2) == 1 {
+        let mut pad = [0u8;1];
+        // ignore errors on pad? we'll just try to read it
+        let _ = reader.read_exact(&mut pad);
+    }
+    Ok(body)
+}
+
+// seek past a chunk body (and its pad byte) without reading it
+fn skip_chunk_body<R: Seek>(reader: &mut R, size: u32) -> io::Result<()> {
+    let padded = size as i64 + (size % 2) as i64;
+    reader.seek(SeekFrom::Current(padded))?;
+    Ok(())
+}
+
+pub fn fourcc_to_string(id: [u8;4]) -> String {
+    String::from_utf8_lossy(&id).to_string()
+}
@@ -0,0 +1,49 @@
+// Analysis windows and the correction factors that make magnitudes read the
+// same whichever window was used.
+
+use std::f32::consts::PI;
+
+/// Window function applied to each analysis frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Window {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+    FlatTop, // near-exact amplitude of tones between bins, at the cost of resolution
+}
+
+impl Window {
+    /// Periodic (DFT-even) coefficients of length `n`.
+    pub fn coefficients(self, n: usize) -> Vec<f32> {
+        // cosine-sum windows: sum of a_k cos(2 pi k i / n) with alternating signs
+        let terms: &[f32] = match self {
+            Window::Rectangular => &[1.0],
+            Window::Hann => &[0.5, 0.5],
+            Window::Hamming => &[0.54, 0.46],
+            Window::Blackman => &[0.42, 0.5, 0.08],
+            Window::FlatTop => &[0.215_578_95, 0.416_631_58, 0.277_263_16, 0.083_578_95, 0.006_947_368],
+        };
+        (0..n).map(|i| {
+            let x = 2.0 * PI * i as f32 / n as f32;
+            terms.iter().enumerate()
+                .map(|(k, &a)| if k % 2 == 0 { a } else { -a } * (k as f32 * x).cos())
+                .sum()
+        }).collect()
+    }
+}
+
+/// Coherent gain: the mean of the window, by which it scales a tone's
+/// amplitude. Divide magnitudes by `n * coherent_gain` (the window sum) to
+/// read amplitudes.
+pub fn coherent_gain(window: &[f32]) -> f32 {
+    window.iter().sum::<f32>() / window.len().max(1) as f32
+}
+
+/// Equivalent noise bandwidth in bins: how much wider than one bin the
+/// window's passband looks to broadband noise. Power densities divide by it.
+pub fn enbw(window: &[f32]) -> f32 {
+    let sum = window.iter().sum::<f32>();
+    let power = window.iter().map(|w| w * w).sum::<f32>();
+    window.len() as f32 * power / (sum * sum).max(f32::MIN_POSITIVE)
+}
@@ -0,0 +1,136 @@
+// WAV writer: the inverse of the parser in `wav`, producing a canonical
+// RIFF/WAVE file with a fmt chunk followed by a data chunk.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::dither::{requantize, Dither};
+use crate::sample::{encode, Sample, I24};
+use crate::wav::{FmtChunk, WAVE_FORMAT_IEEE_FLOAT, WAVE_FORMAT_PCM};
+
+/// Sample encodings the writer can produce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    U8,
+    I16,
+    I24,
+    I32,
+    F32,
+}
+
+impl SampleFormat {
+    /// Integer PCM format for a bit depth, or 32-bit float if `float` is set.
+    pub fn from_bits(bits: u16, float: bool) -> Option<Self> {
+        match (bits, float) {
+            (8, false)  => Some(SampleFormat::U8),
+            (16, false) => Some(SampleFormat::I16),
+            (24, false) => Some(SampleFormat::I24),
+            (32, false) => Some(SampleFormat::I32),
+            (32, true)  => Some(SampleFormat::F32),
+            _ => None,
+        }
+    }
+
+    /// The writer format closest to a parsed fmt chunk; 64-bit float, which
+    /// the writer can't produce, is narrowed to 32-bit float.
+    pub fn matching(fmt: &FmtChunk) -> Self {
+        let float = fmt.format_code() == WAVE_FORMAT_IEEE_FLOAT;
+        SampleFormat::from_bits(fmt.bits_per_sample, float).unwrap_or(SampleFormat::F32)
+    }
+
+    pub fn bits(self) -> u16 {
+        match self {
+            SampleFormat::U8  => 8,
+            SampleFormat::I16 => 16,
+            SampleFormat::I24 => 24,
+            SampleFormat::I32 | SampleFormat::F32 => 32,
+        }
+    }
+
+    fn format_code(self) -> u16 {
+        match self {
+            SampleFormat::F32 => WAVE_FORMAT_IEEE_FLOAT,
+            _ => WAVE_FORMAT_PCM,
+        }
+    }
+}
+
+/// Layout of the file to write.
+#[derive(Debug, Clone, Copy)]
+pub struct WavSpec {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub format: SampleFormat,
+}
+
+/// Encode interleaved normalized samples per `spec` and write a complete
+/// WAV stream. `dither` applies when the target is an integer format.
+pub fn write_wav<W: Write>(writer: &mut W, spec: WavSpec, samples: &[f32], dither: Dither) -> io::Result<()> {
+    let channels = spec.channels as usize;
+    let payload = match spec.format {
+        SampleFormat::U8  => encode(&requantize::<u8>(samples, channels, dither)),
+        SampleFormat::I16 => encode(&requantize::<i16>(samples, channels, dither)),
+        SampleFormat::I24 => encode(&requantize::<I24>(samples, channels, dither)),
+        SampleFormat::I32 => encode(&requantize::<i32>(samples, channels, dither)),
+        SampleFormat::F32 => encode(&samples.iter().map(|&s| f32::from_f32(s)).collect::<Vec<_>>()),
+    };
+
+    write_encoded(writer, spec, &payload)
+}
+
+/// Write a WAV stream around a payload that's already encoded per `spec`,
+/// e.g. bytes copied straight out of another file with the same layout.
+pub fn write_encoded<W: Write>(writer: &mut W, spec: WavSpec, payload: &[u8]) -> io::Result<()> {
+    let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "data chunk exceeds 4 GiB");
+    let data_size = u32::try_from(payload.len()).map_err(|_| too_large())?;
+    let pad = data_size % 2;
+
+    let block_align = spec.channels * spec.format.bits() / 8;
+    let byte_rate = spec.sample_rate * block_align as u32;
+
+    // float fmt chunks carry a (zero) cbSize extension
+    let fmt_size: u32 = if spec.format == SampleFormat::F32 { 18 } else { 16 };
+    // the RIFF size also counts the headers, so it overflows a little before
+    // the data size does
+    let riff_size = (4 + (8 + fmt_size) + 8 + pad).checked_add(data_size).ok_or_else(too_large)?;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&riff_size.to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&fmt_size.to_le_bytes())?;
+    writer.write_all(&spec.format.format_code().to_le_bytes())?;
+    writer.write_all(&spec.channels.to_le_bytes())?;
+    writer.write_all(&spec.sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&spec.format.bits().to_le_bytes())?;
+    if fmt_size == 18 {
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    writer.write_all(payload)?;
+    if pad == 1 {
+        writer.write_all(&[0])?;
+    }
+
+    Ok(())
+}
+
+/// `write_wav` into a newly created file at `path`.
+pub fn write_wav_file<P: AsRef<Path>>(path: P, spec: WavSpec, samples: &[f32], dither: Dither) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_wav(&mut writer, spec, samples, dither)?;
+    writer.flush()
+}
+
+/// `write_encoded` into a newly created file at `path`.
+pub fn write_encoded_file<P: AsRef<Path>>(path: P, spec: WavSpec, payload: &[u8]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_encoded(&mut writer, spec, payload)?;
+    writer.flush()
+}